@@ -1,116 +1,4633 @@
 use crate::{
-    meter,
-    twitter::{self, Twitter},
-    util::days_since_monday,
-    util::get_env_var,
-    wakatime::WakaTime,
+    blended::{AccountFailure, BlendWeights, BlendedSource},
+    bluesky::Bluesky,
+    cache::{self, CachedHours},
+    circuit::Circuits,
+    cli::Args,
+    config::{Config, MeterConfig},
+    discord::Discord,
+    error::BurnoutError,
+    estimate::{open_fraction, Estimate},
+    exec::{run_hook, Exec, Payload},
+    github::{self, GitHub},
+    grafana,
+    hardstop::{self, Step},
+    mastodon::{self, Mastodon},
+    meter::{self, Band, MeterStyle, Orientation, Unit},
+    metrics, ratelimit, shields,
+    slack::{Destination, Slack},
+    state::{HistoryEntry, State},
+    store::{FileStore, StateStore},
+    summary::{summarize, MeterOutput, RunReport, Timings},
+    svg,
+    telegram::Telegram,
+    template,
+    twitter::{self, RetryOn, TargetField, Twitter},
+    util::{
+        day_id_offset, emoji_to_ascii, emoji_to_shortcode, format_updated_offset, get_env_var,
+        http_client, shortcode,
+    },
+    util::{
+        days_since_week_start_offset, offset_from_minutes, parse_weekday, week_id_starting_offset,
+    },
+    wakatime::{DumpTarget, WakaTime},
+    workhours::{WorkHours, DEFAULT_OFF_HOURS_MULTIPLIER},
 };
-use std::error::Error;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use terminal_size::{terminal_size, Width};
+use time::{OffsetDateTime, UtcOffset, Weekday};
+
+/// How many weeks of history to look at when suggesting a burnout limit.
+const SUGGEST_LIMIT_WEEKS: i64 = 8;
+
+/// Consecutive failures before a backend is skipped in daemon mode.
+const CIRCUIT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long a failing backend is skipped before it's tried again.
+const CIRCUIT_COOLDOWN: time::Duration = time::Duration::minutes(30);
+
+/// Builds the location from a run, in place of `LOCATION_TEMPLATE`.
+pub type LocationFn = Box<dyn Fn(&RunReport) -> String>;
 
 pub struct App {
     wakatime: WakaTime,
+    /// The accounts from `WAKATIME_API_KEYS` after the first, whose hours
+    /// are added to its.
+    extra_wakatime: Vec<WakaTime>,
+    github: Option<GitHub>,
     twitter: Twitter,
+    exec: Option<Exec>,
+    mastodon: Vec<Mastodon>,
+    discord: Option<Discord>,
+    telegram: Option<Telegram>,
+    slack: Option<Slack>,
+    /// Sets the Slack status, with a user token rather than the bot's.
+    slack_status: Option<Slack>,
+    bluesky: Option<Bluesky>,
+    /// Sets the GitHub status, with just the token.
+    github_status: Option<GitHub>,
+    /// A circuit breaker for each backend called, by name.
+    circuits: Circuits,
     burnout_meter: meter::Builder,
+    location_fn: Option<LocationFn>,
+    /// The state from the last run, flushed after each run and on shutdown.
+    state: State,
+    store: Box<dyn StateStore>,
     settings: AppSettings,
 }
 
 impl App {
+    /// Create the app, with its state kept in the file at `STATE_PATH`.
     pub fn new(settings: AppSettings) -> Result<Self, Box<dyn Error>> {
+        let store = FileStore::open(settings.state_path())?;
+
+        Self::with_store(settings, Box::new(store))
+    }
+
+    /// Create the app, with its state kept in `store`.
+    pub fn with_store(
+        settings: AppSettings,
+        store: Box<dyn StateStore>,
+    ) -> Result<Self, Box<dyn Error>> {
+        // catch a typo in the template before any requests
+        template::validate(&settings.location_template())?;
+
+        // both pick the days to count, so one would quietly win
+        if settings.hours_window() == HoursWindow::Rolling
+            && settings.lookback_mode() != LookbackMode::CalendarWeek
+        {
+            return Err("LOOKBACK_MODE can't be rolling with HOURS_WINDOW=rolling".into());
+        }
+
+        // one connection pool and set of timeouts for every request
+        let client = http_client()?;
+
+        let mut wakatime = WakaTime::with_client(settings.wakatime_api_key(), client.clone())?;
+        wakatime
+            .set_dump_target(settings.dump_target())
+            .set_utc_offset(settings.utc_offset());
+
+        if let Some(base_url) = settings.wakatime_base_url() {
+            wakatime.set_base_url(base_url.parse()?);
+        }
+
+        // the shared total is only kept for the first account
+        let extra_wakatime = settings
+            .wakatime_extra_keys()
+            .iter()
+            .map(|api_key| {
+                let mut account = wakatime.clone();
+                account.set_api_key(api_key);
+
+                account
+            })
+            .collect();
+
+        if let Some(interval) = settings.wakatime_min_interval() {
+            wakatime.set_min_interval(settings.shared_dir(), interval);
+        }
+
+        let github = match settings.github_credentials() {
+            Some((token, user)) => {
+                let mut github = GitHub::with_client(&token, &user, client.clone())?;
+
+                if let Some(base_url) = settings.github_base_url() {
+                    github.set_base_url(base_url.parse()?);
+                }
+
+                Some(github)
+            }
+            None => None,
+        };
+
+        let mut twitter = Twitter::with_client(settings.twitter_credentials(), client.clone())?;
+        twitter
+            .set_retry_on(settings.twitter_retry_on())
+            .set_max_rate_limit_wait(settings.twitter_rate_limit_max_wait());
+
+        if let Some(base_url) = settings.twitter_base_url() {
+            twitter.set_base_url(base_url.parse()?);
+        }
+
+        let exec = match settings.backend() {
+            Backend::Exec => match settings.exec_command() {
+                Some(command) => Some(Exec::new(command)),
+                None => return Err("EXEC_COMMAND must be set when PROFILE_BACKEND=exec".into()),
+            },
+            _ => None,
+        };
+
+        let mastodon = match settings.backend() {
+            Backend::Mastodon | Backend::TwitterAndMastodon
+                if settings.mastodon_instances().is_empty() =>
+            {
+                return Err("MASTODON_INSTANCES must be set to post to Mastodon".into())
+            }
+            Backend::Mastodon | Backend::TwitterAndMastodon => settings
+                .mastodon_instances()
+                .iter()
+                .map(|(base_url, token)| Mastodon::with_client(base_url, token, client.clone()))
+                .collect::<Result<_, _>>()?,
+            _ => Vec::new(),
+        };
+
+        let discord = match settings.discord_webhook_url() {
+            Some(webhook_url) => Some(Discord::with_client(webhook_url, client.clone())?),
+            None => None,
+        };
+
+        let telegram = match settings.telegram_credentials()? {
+            Some((bot_token, chat_id)) => {
+                let mut telegram = Telegram::with_client(bot_token, chat_id, client.clone())?;
+
+                if let Some(base_url) = settings.telegram_base_url() {
+                    telegram.set_base_url(base_url.parse()?);
+                }
+
+                Some(telegram)
+            }
+            None => None,
+        };
+
+        let slack = match settings.slack_destination()? {
+            Some(destination) => Some(Slack::with_client(destination, client.clone())?),
+            None => None,
+        };
+
+        let slack_status = settings
+            .slack_user_token()
+            .map(|token| Slack::status_only(Some(token), client.clone()));
+
+        let bluesky = match settings.bluesky_credentials()? {
+            Some((handle, app_password)) => {
+                let mut bluesky = Bluesky::with_client(handle, app_password, client.clone())?;
+
+                if let Some(base_url) = settings.bluesky_base_url() {
+                    bluesky.set_base_url(base_url.parse()?);
+                }
+
+                Some(bluesky)
+            }
+            None => None,
+        };
+
+        // opted into separately, as the token alone is for blending
+        let github_status = match (settings.github_status(), settings.github_token()) {
+            (true, Some(token)) => {
+                let mut github = GitHub::status_only(token, client.clone());
+
+                if let Some(base_url) = settings.github_base_url() {
+                    github.set_base_url(base_url.parse()?);
+                }
+
+                Some(github)
+            }
+            (true, None) => return Err("GITHUB_TOKEN must be set when GITHUB_STATUS=true".into()),
+            (false, _) => None,
+        };
+
+        let state = State::load_from(store.as_ref())?;
+
+        let mut circuits = Circuits::new(CIRCUIT_FAILURE_THRESHOLD, CIRCUIT_COOLDOWN);
+        circuits.restore(&state.circuits);
+
         Ok(Self {
-            wakatime: WakaTime::new(settings.wakatime_api_key())?,
-            twitter: Twitter::new(settings.twitter_credentials())?,
+            wakatime,
+            extra_wakatime,
+            github,
+            twitter,
+            exec,
+            mastodon,
+            discord,
+            telegram,
+            slack,
+            slack_status,
+            bluesky,
+            github_status,
+            circuits,
             burnout_meter: meter::Builder::new(),
+            location_fn: None,
+            state,
+            store,
             settings,
         })
     }
 
-    pub async fn run(&mut self) -> Result<(), Box<dyn Error>> {
-        // start week on Monday, end week on Sunday
-        let offset_hours = self.settings.timezone_offset();
-        let days_since_monday = days_since_monday(offset_hours);
-        // uses `time` crate to get the number of days since Monday
-        let hours = match self.wakatime.get_time_last_n_days(days_since_monday).await {
-            Ok(hours) => match hours {
-                Some(hours) => hours,
-                None => panic!("No hours found from WakaTime"),
+    /// Update the profile once.
+    ///
+    /// Returns the report of what went into the update, which is also printed
+    /// as JSON with `OUTPUT_FORMAT=json`.
+    pub async fn run(&mut self) -> Result<RunReport, Box<dyn Error>> {
+        let started = Instant::now();
+        let now = OffsetDateTime::now_utc();
+
+        // start week on WEEK_START, Monday unless it's set
+        let offset = self.settings.utc_offset();
+        let days_since_start =
+            days_since_week_start_offset(now, offset, self.settings.week_start());
+
+        // work on a copy so a failed run leaves the last good state
+        let mut state = self.state.clone();
+
+        let lookback_days =
+            self.settings
+                .lookback_mode()
+                .days(now, offset, self.settings.week_start())?;
+
+        // an open circuit falls back on the cache like any other failure
+        let fetched = match self.circuits.backend("wakatime").is_allowed(now) {
+            true => {
+                let fetched = self.get_hours(lookback_days).await;
+                self.circuits.backend("wakatime").record(&fetched, now);
+                fetched
+            }
+            false => Err(BurnoutError::CircuitOpen("wakatime".to_string()).into()),
+        };
+
+        let hours = match fetched {
+            Ok(Some(hours)) => {
+                // forced hours aren't real, so they mustn't stand in later
+                if self.settings.force_hours().is_none() {
+                    self.save_disk_cache(hours, now);
+                }
+                hours
+            }
+            // e.g. a new account that hasn't tracked anything yet
+            Ok(None) if self.settings.treat_empty_as_zero() => 0f64,
+            Ok(None) => return self.post_no_data(days_since_start).await,
+            Err(e) => match self.disk_cached_hours(now) {
+                Some(hours) => {
+                    log::warn!("{e}, using the {hours} hours cached on disk");
+                    hours
+                }
+                None if self.settings.rate_limit_fallback() => {
+                    fall_back_to_cache(e, &state, now, offset, self.settings.week_start())?
+                }
+                None => return Err(e),
             },
-            Err(_) => panic!("Failed to get hours from WakaTime"),
         };
 
+        // don't post garbage from a data glitch
+        check_sanity(hours, self.settings.sanity_max_hours())?;
+
+        // today's data is still coming in, so show a range if a margin is set
+        let estimate = Estimate::new(
+            hours,
+            self.settings.provisional_margin().unwrap_or(0f64),
+            open_fraction(now, offset, days_since_start),
+        );
+
+        let away_days = self.settings.away_days();
+
+        // the recent daily hours, if anything needs them, going back far
+        // enough to see a whole run of away days
+        let recent_daily = if self.settings.daily_limit().is_some()
+            || self.settings.summary()
+            || away_days.is_some()
+            || self.location_fn.is_some()
+        {
+            let days = days_since_start.max(away_days.unwrap_or(0) as i64 - 1);
+
+            Some(self.wakatime.get_daily_time_last_n_days(days).await?)
+        } else {
+            None
+        };
+
+        let away = match (away_days, &recent_daily) {
+            (Some(away_days), Some(daily)) => meter::is_away(daily, away_days),
+            _ => false,
+        };
+
+        // keep the start of the week to today
+        let week_daily = recent_daily.map(|mut daily| {
+            let start = daily.len().saturating_sub(days_since_start as usize + 1);
+
+            daily.split_off(start)
+        });
+
+        // only ask for the projects if the location shows one
+        let dominant_project = match &self.location_fn {
+            None if self
+                .settings
+                .location_template()
+                .contains("{dominant_project}") =>
+            {
+                let projects = self
+                    .wakatime
+                    .get_project_hours_last_n_days(days_since_start)
+                    .await?;
+
+                dominant_project(&projects, self.settings.dominant_share())
+                    .unwrap_or_default()
+                    .to_string()
+            }
+            _ => String::new(),
+        };
+
+        // likewise the languages, which the top language comes from too
+        let languages_in_template =
+            self.location_fn.is_none() && self.settings.location_template().contains("{languages}");
+
+        let language_hours = match languages_in_template || self.settings.show_top_language() {
+            true => {
+                self.wakatime
+                    .get_language_hours_last_n_days(days_since_start)
+                    .await?
+            }
+            false => Vec::new(),
+        };
+
+        let languages = match languages_in_template {
+            true => meter::render_languages(
+                &language_hours,
+                self.settings.burnout_limit(),
+                self.settings.meter_length(),
+                self.settings.top_languages(),
+            ),
+            false => String::new(),
+        };
+
+        let fetched = Instant::now();
+
+        let unit = self.settings.meter_unit().unwrap_or(self.source().unit());
+
         self.burnout_meter
             .set_max(self.settings.burnout_limit())
             .set_length(self.settings.meter_length())
-            .set_current(hours)
+            .set_style(self.settings.meter_style())
+            .set_hour_thresholds(self.settings.thresholds())
+            .set_centered(self.settings.centered())
+            .set_reversed(self.settings.reversed())
+            .set_orientation(self.settings.orientation())
+            .set_unit(unit)
+            .set_away(away)
+            .set_current(estimate.high)
             .build()?;
 
-        let hours_rounded = hours.round() as i64;
+        let meter = match (self.settings.daily_limit(), &week_daily) {
+            (Some(daily_limit), Some(daily)) if !away => {
+                let week = meter::render_week(daily, daily_limit);
+
+                match self.settings.meter_style() {
+                    MeterStyle::Emoji => week,
+                    MeterStyle::Shortcode => emoji_to_shortcode(&week),
+                    MeterStyle::Ascii { filled, empty } => emoji_to_ascii(&week, filled, empty),
+                    // a day is a whole cell, there's no part of one to show
+                    MeterStyle::SmoothBlocks => {
+                        emoji_to_ascii(&week, meter::FULL_BLOCK, meter::EMPTY_BLOCK)
+                    }
+                }
+            }
+            _ => self.burnout_meter.to_string(),
+        };
+
+        let mut report = RunReport {
+            hours,
+            limit: *self.burnout_meter.max(),
+            days: days_since_start as u32 + 1,
+            active_days: week_daily.as_deref().map_or(0, RunReport::active_days),
+            band: self.burnout_meter.band(),
+            meter,
+            wakatime_rate_limit: self.wakatime.rate_limit(),
+            twitter_rate_limit: None,
+            timings: Timings::default(),
+        };
+
+        // over the limit shows in the meter, so the number can stop at the max
+        let display_hours = match self.settings.clamp_display() {
+            true => estimate.clamped(*self.burnout_meter.max()).render(),
+            false => estimate.render(),
+        };
+
+        let mut location = match &self.location_fn {
+            Some(location_fn) => location_fn(&report),
+            None => template::render(
+                &self.settings.location_template(),
+                &[
+                    ("meter", report.meter.clone()),
+                    ("hours", display_hours),
+                    ("max", self.burnout_meter.max().to_string()),
+                    // of the hours, like the remaining hours and the payload
+                    (
+                        "percent",
+                        meter::Builder::percent_of(hours, *self.burnout_meter.max())
+                            .round()
+                            .to_string(),
+                    ),
+                    (
+                        "remaining",
+                        (self.burnout_meter.max() - hours)
+                            .max(0f64)
+                            .round()
+                            .to_string(),
+                    ),
+                    ("updated", format_updated_offset(now, offset)),
+                    (
+                        "hours_label",
+                        self.settings.hours_label(self.burnout_meter.unit()),
+                    ),
+                    ("quantity", self.burnout_meter.quantity()),
+                    ("days", work_days(hours, self.settings.hours_per_day())),
+                    ("dominant_project", dominant_project),
+                    ("languages", languages),
+                    (
+                        "caption",
+                        meter::caption(self.burnout_meter.band(), self.settings.captions())
+                            .to_string(),
+                    ),
+                ],
+            ),
+        };
+
+        if let (true, Some((top_language, _))) =
+            (self.settings.show_top_language(), language_hours.first())
+        {
+            location += &format!(" · {top_language}");
+        }
+
+        let band = self.burnout_meter.band();
+        let week = week_id_starting_offset(now, offset, self.settings.week_start());
 
-        let location = format!(
-            "{} {}/{} hours",
-            self.burnout_meter,
-            hours_rounded,
-            self.burnout_meter.max()
+        let celebrate = should_celebrate(
+            self.settings.goal_hours(),
+            hours,
+            *self.burnout_meter.max(),
+            state.celebrated_week.as_deref(),
+            &week,
         );
-        let profile = self.twitter.update_location(location).await?;
 
-        match profile.location {
-            Some(location) => println!("Location updated to {location}"),
-            None => panic!("Location not updated"),
+        if celebrate {
+            location += " 🎉";
+        }
+
+        let target_field = self.settings.twitter_target_field();
+
+        if self.settings.truncate_location() {
+            location =
+                twitter::truncate_to_fit(&location, &report.meter, target_field.max_length());
+        }
+
+        let length = twitter::weighted_length(&location);
+
+        if length > target_field.max_length() {
+            log::warn!(
+                "Location is {length} characters, over Twitter's {target_field} limit of {}: {location}",
+                target_field.max_length()
+            );
+        }
+
+        let rendered = Instant::now();
+
+        state.record(HistoryEntry {
+            timestamp: now.unix_timestamp(),
+            hours,
+            limit: *self.burnout_meter.max(),
+        });
+
+        let step = match self.settings.break_after_days() {
+            Some(days) => hardstop::step(
+                &mut state,
+                band,
+                now,
+                offset,
+                days,
+                self.settings.break_sentinel().exists(),
+            ),
+            None => Step::Update,
+        };
+
+        let dry_run = self.settings.dry_run();
+
+        if step == Step::Stop {
+            location = self.settings.break_message();
+
+            // a dry run shows the break without starting it
+            if !dry_run {
+                fs::write(
+                    self.settings.break_sentinel(),
+                    "Delete this file to resume updates\n",
+                )?;
+            }
+        }
+
+        // a new week always gets posted, even if the meter looks the same
+        let new_week = self.settings.new_week_update()
+            && is_new_week(state.last_update_week.as_deref(), &week);
+
+        let update = match step {
+            Step::Update => {
+                self.settings
+                    .update_on()
+                    .should_update(state.last_band, band)
+                    || celebrate
+                    || new_week
+            }
+            Step::Stop => true,
+            Step::Stopped => false,
         };
 
+        // posting the same location again would only use up the rate limit,
+        // except for the post that starts a new week
+        let target = self.settings.posted_target();
+        let unchanged = !self.settings.force_update()
+            && !new_week
+            && state.load_last_posted(&target) == Some(location.as_str());
+
+        // a dry run shows what would be posted whether or not it would be
+        if (update && !unchanged) || dry_run {
+            let payload = self.payload(
+                location,
+                self.burnout_meter.to_string(),
+                hours,
+                *self.burnout_meter.max(),
+                band,
+            );
+
+            let allowed = match self.settings.pre_update_hook() {
+                Some(hook) if !dry_run => run_hook(hook, &payload)?,
+                _ => true,
+            };
+
+            let posted_location = payload.location.clone();
+
+            if allowed && self.update_profile(payload).await? {
+                state.last_band = Some(band);
+                state.last_update_week = Some(week.clone());
+                state.save_last_posted(&target, &posted_location);
+
+                if celebrate {
+                    state.celebrated_week = Some(week);
+                }
+            }
+        } else if step == Step::Stopped {
+            log::info!(
+                "Taking a break, delete {} to resume updates",
+                self.settings.break_sentinel().display()
+            );
+        } else if update {
+            log::info!("Location unchanged, skipping update");
+        } else {
+            log::info!("Still in the {band} band, skipping update");
+        }
+
+        if self.settings.also_post() && step == Step::Update && !dry_run {
+            let today = day_id_offset(now, offset);
+            post_daily_log(
+                &self.twitter,
+                &mut state,
+                &daily_log_text(&self.burnout_meter, hours),
+                &today,
+            )
+            .await?;
+        }
+
+        if let (Some(notify_on), Step::Update, false) = (self.settings.notify_on(), step, dry_run) {
+            notify_crossing(&self.twitter, state.run_band, band, notify_on).await?;
+        }
+
+        let posted = Instant::now();
+
+        // nothing is kept from a dry run, so the next real run acts as if it
+        // never happened
+        if !dry_run {
+            state.run_band = Some(band);
+            self.state = state;
+            self.save_state()?;
+        }
+
         let hours_til_burnout = ((self.burnout_meter.max() - hours) * 100f64).round() / 100f64;
 
-        println!("{hours} hours in the last 30 days");
-        println!("Hours til burnout: {hours_til_burnout}",);
-        println!("Generated Meter: {}", self.burnout_meter);
+        log::info!("{hours} hours in the last 30 days");
+        log::info!("Hours til burnout: {hours_til_burnout}",);
+        log::info!("Generated Meter: {}", self.burnout_meter);
+        log::info!("Band: {}", self.burnout_meter.band());
+
+        if self.settings.summary() {
+            log::info!("{}", summarize(&report));
+        }
+
+        report.twitter_rate_limit = self.twitter.rate_limit();
+        report.timings = Timings {
+            fetch: fetched - started,
+            render: rendered - fetched,
+            post: posted - rendered,
+            total: started.elapsed(),
+        };
+
+        if let Some(warning) = self
+            .settings
+            .run_budget()
+            .and_then(|budget| report.timings.budget_warning(budget))
+        {
+            log::warn!("{warning}");
+        }
+
+        let rate_limits = [
+            ("WakaTime", report.wakatime_rate_limit),
+            ("Twitter", report.twitter_rate_limit),
+        ];
+
+        for (api, rate_limit) in rate_limits {
+            if let Some(warning) = rate_limit
+                .as_ref()
+                .and_then(|limit| ratelimit::warning(api, limit))
+            {
+                log::warn!("{warning}");
+            }
+        }
+
+        self.print_output(&report)?;
+
+        Ok(report)
+    }
+
+    /// Print `report` as a [`MeterOutput`] if `OUTPUT_FORMAT` is JSON,
+    /// rounded to `JSON_PRECISION` if it's set.
+    fn print_output(&self, report: &RunReport) -> Result<(), Box<dyn Error>> {
+        if self.settings.output_format() == OutputFormat::Json {
+            let output = MeterOutput::from(self.payload(
+                String::new(),
+                report.meter.clone(),
+                report.hours,
+                report.limit,
+                report.band,
+            ));
+            println!("{}", serde_json::to_string(&output)?);
+        }
 
         Ok(())
     }
-}
 
-pub struct AppSettings {
-    wakatime_api_key: String,
-    twitter_credentials: twitter::Credentials,
-    burnout_limit: f64,
-    timezone_offset: i8,
-    meter_length: u8,
-}
+    /// The payload for an update, rounded to `JSON_PRECISION` if it's set.
+    fn payload(
+        &self,
+        location: String,
+        meter: String,
+        hours: f64,
+        max: f64,
+        band: Band,
+    ) -> Payload {
+        let payload = Payload::new(location, meter, hours, max, band);
 
-impl AppSettings {
-    fn wakatime_api_key(&self) -> &str {
-        self.wakatime_api_key.as_ref()
+        match self.settings.json_precision() {
+            Some(decimals) => payload.with_precision(decimals),
+            None => payload,
+        }
     }
 
-    fn twitter_credentials(&self) -> twitter::Credentials {
-        self.twitter_credentials.clone()
+    /// Save freshly fetched `hours` to `WAKATIME_CACHE_PATH`, if it's set.
+    ///
+    /// A cache that can't be written shouldn't stop the update, so the error
+    /// is only printed.
+    fn save_disk_cache(&self, hours: f64, now: OffsetDateTime) {
+        let Some(path) = self.settings.wakatime_cache_path() else {
+            return;
+        };
+
+        let cached = CachedHours {
+            hours,
+            timestamp: now.unix_timestamp(),
+        };
+
+        if let Err(e) = cache::save_cache(path, &cached) {
+            log::warn!("Failed to save the WakaTime cache: {e}");
+        }
     }
 
-    fn burnout_limit(&self) -> f64 {
-        self.burnout_limit
+    /// The hours cached at `WAKATIME_CACHE_PATH`, if it's set and they're
+    /// fresh enough to stand in for a failed fetch.
+    fn disk_cached_hours(&self, now: OffsetDateTime) -> Option<f64> {
+        let path = self.settings.wakatime_cache_path()?;
+
+        match cache::load_cache(path) {
+            Ok(cached) => cached?.fresh_hours(now),
+            Err(e) => {
+                log::warn!("Failed to load the WakaTime cache: {e}");
+                None
+            }
+        }
+    }
+
+    /// Post a row of `NO_DATA_GLYPH` in place of the meter, for when the
+    /// source has no hours, there's nothing cached to fall back on, and
+    /// `TREAT_EMPTY_AS_ZERO` is off.
+    ///
+    /// The last band is forgotten, so the next run with hours posts the
+    /// meter again.
+    async fn post_no_data(&mut self, days_since_start: i64) -> Result<RunReport, Box<dyn Error>> {
+        let meter =
+            meter::render_no_data(self.settings.meter_length(), self.settings.no_data_glyph());
+        let limit = self.settings.burnout_limit();
+
+        log::info!("No hours found, posting {meter}");
+
+        let payload = self.payload(meter.clone(), meter.clone(), 0f64, limit, Band::Fresh);
+        let target = self.settings.posted_target();
+
+        if !self.settings.force_update()
+            && self.state.load_last_posted(&target) == Some(meter.as_str())
+        {
+            log::info!("Location unchanged, skipping update");
+        } else if self.update_profile(payload).await? {
+            self.state.last_band = None;
+            self.state.save_last_posted(&target, &meter);
+            self.save_state()?;
+        }
+
+        let report = RunReport {
+            hours: 0f64,
+            limit,
+            days: days_since_start as u32 + 1,
+            active_days: 0,
+            band: Band::Fresh,
+            meter,
+            wakatime_rate_limit: self.wakatime.rate_limit(),
+            twitter_rate_limit: self.twitter.rate_limit(),
+            timings: Timings::default(),
+        };
+
+        self.print_output(&report)?;
+
+        Ok(report)
     }
 
-    fn timezone_offset(&self) -> i8 {
-        self.timezone_offset
+    /// Print a suggested `BURNOUT_LIMIT` based on the last few weeks of
+    /// WakaTime data, without updating anything.
+    pub async fn suggest_limit(&self) -> Result<(), Box<dyn Error>> {
+        let weekly = self
+            .wakatime
+            .get_weekly_time_last_n_weeks(SUGGEST_LIMIT_WEEKS)
+            .await?;
+
+        let limit = match suggest_limit(&weekly) {
+            Some(limit) => limit,
+            None => return Err("Not enough WakaTime data to suggest a limit".into()),
+        };
+
+        let mean = weekly.iter().sum::<f64>() / weekly.len() as f64;
+
+        println!(
+            "Over the last {} weeks you coded {:.1} hours a week on average.",
+            weekly.len(),
+            mean
+        );
+        println!("90% of those weeks were at or under {limit} hours.");
+        println!("Suggested limit: BURNOUT_LIMIT={limit}");
+
+        Ok(())
     }
 
-    fn meter_length(&self) -> u8 {
-        self.meter_length
+    /// Print the meter and hours sized to fill the terminal, without updating
+    /// anything.
+    ///
+    /// When the output isn't a terminal the meter is `METER_LENGTH` long, and
+    /// with `OUTPUT_FORMAT=json` it's printed as a [`MeterOutput`] instead.
+    pub async fn print_meter(&mut self) -> Result<(), Box<dyn Error>> {
+        self.load_meter().await?;
+
+        if self.settings.output_format() == OutputFormat::Json {
+            self.burnout_meter.build()?;
+
+            let output = MeterOutput::from(self.payload(
+                String::new(),
+                self.burnout_meter.to_string(),
+                self.burnout_meter.current().unwrap_or(0f64),
+                *self.burnout_meter.max(),
+                self.burnout_meter.band(),
+            ));
+            println!("{}", serde_json::to_string(&output)?);
+
+            return Ok(());
+        }
+
+        let style = self.settings.meter_style();
+        let suffix = format!(" {}", self.burnout_meter.quantity());
+        let columns = terminal_size().map(|(Width(width), _)| width as usize);
+
+        self.burnout_meter
+            .set_length(meter::fit_length(
+                columns,
+                suffix.chars().count(),
+                style.cell_width(),
+                self.settings.meter_length(),
+            ))
+            .build()?;
+
+        println!("{}{suffix}", self.burnout_meter);
+
+        Ok(())
     }
-}
 
-impl Default for AppSettings {
-    fn default() -> Self {
-        Self {
-            wakatime_api_key: get_env_var("WAKATIME_API_KEY").unwrap(),
-            twitter_credentials: twitter::Credentials {
-                consumer_key: get_env_var("TWITTER_CONSUMER_KEY").unwrap(),
-                consumer_secret: get_env_var("TWITTER_CONSUMER_SECRET").unwrap(),
-                access_token: get_env_var("TWITTER_ACCESS_TOKEN").unwrap(),
-                access_token_secret: get_env_var("TWITTER_ACCESS_TOKEN_SECRET").unwrap(),
-            },
-            burnout_limit: get_env_var("BURNOUT_LIMIT").unwrap_or(40.0),
-            timezone_offset: get_env_var("TIMEZONE_OFFSET").unwrap_or(0),
-            meter_length: get_env_var("METER_LENGTH").unwrap_or(8),
+    /// Print the shields.io endpoint badge JSON for the hours this week,
+    /// without updating anything.
+    pub async fn print_shields(&mut self) -> Result<(), Box<dyn Error>> {
+        self.load_meter().await?;
+        self.burnout_meter.build()?;
+
+        let endpoint =
+            shields::Endpoint::new(&self.burnout_meter.quantity(), self.burnout_meter.band());
+
+        println!("{}", serde_json::to_string(&endpoint)?);
+
+        Ok(())
+    }
+
+    /// Print the meter for the hours this week as an SVG image, with the
+    /// caption for its band from `CAPTIONS` under it, without updating
+    /// anything.
+    pub async fn print_svg(&mut self) -> Result<(), Box<dyn Error>> {
+        self.load_meter().await?;
+        self.burnout_meter.build()?;
+
+        let caption = meter::caption(self.burnout_meter.band(), self.settings.captions());
+
+        println!("{}", svg::render(&self.burnout_meter, caption));
+
+        Ok(())
+    }
+
+    /// Print the hours this week, without updating anything.
+    pub async fn print_hours(&self) -> Result<(), Box<dyn Error>> {
+        println!("{}", self.week_hours().await?);
+
+        Ok(())
+    }
+
+    /// The hours this week for the commands that only print them.
+    async fn week_hours(&self) -> Result<f64, Box<dyn Error>> {
+        let now = OffsetDateTime::now_utc();
+        let lookback_days = self.settings.lookback_mode().days(
+            now,
+            self.settings.utc_offset(),
+            self.settings.week_start(),
+        )?;
+
+        match self.get_hours(lookback_days).await? {
+            Some(hours) => Ok(hours),
+            None if self.settings.treat_empty_as_zero() => Ok(0f64),
+            None => Err(BurnoutError::WakaTimeEmpty.into()),
+        }
+    }
+
+    /// Set the meter up with the hours this week for the commands that only
+    /// print it, leaving it to the caller to build.
+    async fn load_meter(&mut self) -> Result<(), Box<dyn Error>> {
+        let hours = self.week_hours().await?;
+
+        let unit = self.settings.meter_unit().unwrap_or(self.source().unit());
+
+        self.burnout_meter
+            .set_max(self.settings.burnout_limit())
+            .set_length(self.settings.meter_length())
+            .set_style(self.settings.meter_style())
+            .set_hour_thresholds(self.settings.thresholds())
+            .set_unit(unit)
+            .set_current(hours);
+
+        Ok(())
+    }
+
+    /// Print the run history as Grafana JSON time series.
+    pub fn print_grafana(&self) -> Result<(), Box<dyn Error>> {
+        println!(
+            "{}",
+            serde_json::to_string(&grafana::to_series(&self.state.history))?
+        );
+
+        Ok(())
+    }
+
+    /// The meter from the last run as Prometheus metrics, see
+    /// [`metrics::render`].
+    ///
+    /// Before a run, the hours are zero.
+    pub fn metrics_text(&self) -> String {
+        metrics::render(&self.burnout_meter)
+    }
+
+    /// Write the state, including the circuit breakers, to the store.
+    fn save_state(&mut self) -> Result<(), Box<dyn Error>> {
+        self.state.circuits = self.circuits.snapshot();
+
+        self.state.save_to(self.store.as_mut())
+    }
+
+    /// Hand the update to the exec command if `PROFILE_BACKEND=exec`, and
+    /// otherwise update every Mastodon instance if the backend includes
+    /// Mastodon and the Twitter location if it includes Twitter. Any backend
+    /// that has failed too many times in a row recently is skipped.
+    ///
+    /// The location is also sent to Discord if `DISCORD_WEBHOOK_URL` is set,
+    /// to Telegram if `TELEGRAM_BOT_TOKEN` and `TELEGRAM_CHAT_ID` are, and to
+    /// Slack if `SLACK_WEBHOOK_URL` or `SLACK_BOT_TOKEN` and
+    /// `SLACK_CHANNEL` are, whatever the backend. It's set as the Slack status
+    /// too if `SLACK_USER_TOKEN` is, the GitHub status if `GITHUB_STATUS` is,
+    /// and the Bluesky description if `BLUESKY_HANDLE` and
+    /// `BLUESKY_APP_PASSWORD` are.
+    ///
+    /// Returns whether the location was updated. For Mastodon alone that's
+    /// whether any instance was, and with Twitter too it's whether Twitter
+    /// was, with Mastodon, Discord, Telegram, Slack, GitHub, and Bluesky
+    /// failures only reported.
+    async fn update_profile(&mut self, payload: Payload) -> Result<bool, Box<dyn Error>> {
+        if self.settings.dry_run() {
+            log::info!("Dry run, would update the location to {}", payload.location);
+            return Ok(false);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let circuits = &mut self.circuits;
+
+        if let Some(discord) = &self.discord {
+            match circuits
+                .call("discord", now, discord.send_message(&payload.location))
+                .await
+            {
+                Ok(()) => log::info!("Sent to Discord"),
+                Err(e) => log::warn!("Failed to send to Discord: {e}"),
+            }
+        }
+
+        if let Some(telegram) = &self.telegram {
+            match circuits
+                .call("telegram", now, telegram.send_message(&payload.location))
+                .await
+            {
+                Ok(()) => log::info!("Sent to Telegram"),
+                Err(e) => log::warn!("Failed to send to Telegram: {e}"),
+            }
+        }
+
+        if let Some(slack) = &self.slack {
+            match circuits
+                .call("slack", now, slack.post_message(&payload.location))
+                .await
+            {
+                Ok(()) => log::info!("Posted to Slack"),
+                Err(e) => log::warn!("Failed to post to Slack: {e}"),
+            }
+        }
+
+        if let Some(slack) = &self.slack_status {
+            let emoji = shortcode(payload.band.glyph());
+            let expiration = self
+                .settings
+                .slack_status_minutes()
+                .map(|minutes| now.unix_timestamp() + minutes * 60);
+            let status = slack.set_status(&payload.location, emoji, expiration);
+
+            match circuits.call("slack_status", now, status).await {
+                Ok(()) => log::info!("Slack status set to {emoji} {}", payload.location),
+                Err(e) => log::warn!("Failed to set the Slack status: {e}"),
+            }
+        }
+
+        if let Some(github) = &self.github_status {
+            let emoji = github::status_emoji(payload.band);
+            let expires_at = self
+                .settings
+                .github_status_minutes()
+                .map(|minutes| now + time::Duration::minutes(minutes));
+            let status = github.set_status(&payload.location, emoji, expires_at);
+
+            match circuits.call("github_status", now, status).await {
+                Ok(()) => log::info!("GitHub status set to {emoji} {}", payload.location),
+                Err(e) => log::warn!("Failed to set the GitHub status: {e}"),
+            }
+        }
+
+        if let Some(bluesky) = &self.bluesky {
+            match circuits
+                .call(
+                    "bluesky",
+                    now,
+                    bluesky.update_description(&payload.location),
+                )
+                .await
+            {
+                Ok(()) => log::info!("Bluesky description updated to {}", payload.location),
+                Err(e) => log::warn!("Failed to update Bluesky: {e}"),
+            }
+        }
+
+        if let Some(exec) = &self.exec {
+            return match circuits
+                .call("exec", now, async { exec.update(&payload) })
+                .await
+            {
+                Ok(output) => {
+                    print!("{output}");
+                    Ok(true)
+                }
+                Err(e) => skipped_if_open(e),
+            };
+        }
+
+        if !self.mastodon.is_empty() {
+            let update = update_mastodon(
+                &self.mastodon,
+                self.settings.mastodon_field(),
+                &payload.location,
+            );
+            let updated = circuits.call("mastodon", now, update).await;
+
+            if self.settings.backend() == Backend::Mastodon {
+                return updated.or_else(skipped_if_open);
+            }
+
+            if let Err(e) = updated {
+                log::warn!("{e}");
+            }
         }
+
+        let field = self.settings.twitter_target_field();
+        let update = self.twitter.update_profile(field, payload.location);
+
+        let profile = match circuits.call("twitter", now, update).await {
+            Ok(profile) => profile,
+            Err(e) => return skipped_if_open(e.into()),
+        };
+
+        let updated = match field {
+            TargetField::Location => profile.location,
+            TargetField::Description => profile.description,
+        };
+
+        match updated {
+            Some(value) => log::info!("Twitter {field} updated to {value}"),
+            None => return Err(BurnoutError::ProfileNotUpdated(field.to_string()).into()),
+        };
+
+        Ok(true)
+    }
+
+    /// Build the location with `location_fn` instead of `LOCATION_TEMPLATE`.
+    pub fn set_location_fn(&mut self, location_fn: LocationFn) -> &mut Self {
+        self.location_fn = Some(location_fn);
+
+        self
+    }
+
+    /// Run until interrupted, updating every `interval`.
+    ///
+    /// Errors from a single run are printed and the next run carries on, while
+    /// a backend that keeps failing is skipped for a cooldown period. The
+    /// state is flushed after each run and on Ctrl-C, so a restart doesn't
+    /// post again or forget a failing backend.
+    pub async fn run_daemon(&mut self, interval: Duration) -> Result<(), Box<dyn Error>> {
+        loop {
+            if let Err(e) = self.run().await {
+                log::error!("Update failed: {e}");
+            }
+
+            // keep the circuit breaker even when the run failed
+            self.save_state()?;
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = tokio::signal::ctrl_c() => {
+                    log::info!("Shutting down");
+                    return self.save_state();
+                }
+            }
+        }
+    }
+
+    /// Get the hours from WakaTime, capping long sessions if `MAX_SESSION_HOURS`
+    /// is set and blending in GitHub activity if `GITHUB_TOKEN` is set.
+    ///
+    /// With `FORCE_HOURS` set, WakaTime isn't called at all and those hours
+    /// are used instead.
+    async fn get_hours(&self, days: i64) -> Result<Option<f64>, Box<dyn Error>> {
+        if let Some(hours) = self.settings.force_hours() {
+            log::warn!("FORCE_HOURS is set, using {hours} fake hours instead of WakaTime");
+            return Ok(Some(hours));
+        }
+
+        match self.settings.hours_window() {
+            HoursWindow::Week => self.source().get_time_last_n_days(days).await,
+            HoursWindow::Rolling => {
+                let rolling_days = self.settings.rolling_days();
+                let daily = self
+                    .wakatime
+                    .get_daily_time_last_n_days(rolling_days as i64 - 1)
+                    .await?;
+
+                Ok(Some(rolling_hours(&daily, rolling_days)))
+            }
+        }
+    }
+
+    /// The data source the meter fills from.
+    fn source(&self) -> BlendedSource<'_> {
+        let mut source = BlendedSource::new(
+            &self.wakatime,
+            self.github.as_ref(),
+            self.settings.blend_weights(),
+        );
+        source
+            .set_extra_accounts(
+                &self.extra_wakatime,
+                self.settings.wakatime_account_failure(),
+            )
+            .set_max_session_hours(self.settings.max_session_hours())
+            .set_work_hours(self.settings.work_hours())
+            .set_project(self.settings.wakatime_project());
+
+        source
+    }
+}
+
+/// When to update the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateOn {
+    /// Every run.
+    #[default]
+    Always,
+    /// Only when the meter moves into a different band.
+    Band,
+}
+
+impl UpdateOn {
+    /// Whether to update, given the band at the last update and the band now.
+    fn should_update(&self, last_band: Option<Band>, band: Band) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Band => last_band != Some(band),
+        }
+    }
+}
+
+impl FromStr for UpdateOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "always" => Ok(Self::Always),
+            "band" => Ok(Self::Band),
+            _ => Err(format!("Unknown UPDATE_ON value: {s}")),
+        }
+    }
+}
+
+/// What the meter is printed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Readable lines, and nothing at all for an update.
+    #[default]
+    Text,
+    /// A single [`MeterOutput`] JSON object, for scripts.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Unknown OUTPUT_FORMAT value: {s}")),
+        }
+    }
+}
+
+/// Which band crossings to post a notification for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyOn {
+    /// Only moving up into a heavier band.
+    Up,
+    /// Moving up or back down.
+    Both,
+}
+
+impl FromStr for NotifyOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "up" => Ok(Self::Up),
+            "both" => Ok(Self::Both),
+            _ => Err(format!("Unknown NOTIFY_ON value: {s}")),
+        }
+    }
+}
+
+/// Where the profile update goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// The Twitter profile location.
+    #[default]
+    Twitter,
+    /// An external command from `EXEC_COMMAND`, given the update as JSON on
+    /// stdin.
+    Exec,
+    /// A profile field on each of the `MASTODON_INSTANCES`.
+    Mastodon,
+    /// Both the Twitter location and the Mastodon field.
+    TwitterAndMastodon,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Twitter => write!(f, "twitter"),
+            Self::Exec => write!(f, "exec"),
+            Self::Mastodon => write!(f, "mastodon"),
+            Self::TwitterAndMastodon => write!(f, "twitter+mastodon"),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "twitter" => Ok(Self::Twitter),
+            "exec" => Ok(Self::Exec),
+            "mastodon" => Ok(Self::Mastodon),
+            "twitter+mastodon" => Ok(Self::TwitterAndMastodon),
+            _ => Err(format!("Unknown PROFILE_BACKEND value: {s}")),
+        }
+    }
+}
+
+/// Which days the hours are counted over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HoursWindow {
+    /// From the start of the week to today.
+    #[default]
+    Week,
+    /// The average day over the last `ROLLING_DAYS`, scaled up to a week.
+    Rolling,
+}
+
+impl FromStr for HoursWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "week" => Ok(Self::Week),
+            "rolling" => Ok(Self::Rolling),
+            _ => Err(format!("Unknown HOURS_WINDOW value: {s}")),
+        }
+    }
+}
+
+/// How far back the hours are looked up from today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LookbackMode {
+    /// Since the start of the week.
+    #[default]
+    CalendarWeek,
+    /// The last this many days, including today.
+    RollingDays(i64),
+}
+
+impl LookbackMode {
+    /// The days before today to look up along with it, for `now` at
+    /// `offset` in weeks beginning on `week_start`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a rolling window of no days, which would have
+    /// nothing in it.
+    pub fn days(
+        &self,
+        now: OffsetDateTime,
+        offset: UtcOffset,
+        week_start: Weekday,
+    ) -> Result<i64, String> {
+        match self {
+            Self::CalendarWeek => Ok(days_since_week_start_offset(now, offset, week_start)),
+            Self::RollingDays(days) if *days < 1 => Err(format!(
+                "LOOKBACK_MODE must look back at least 1 day, got {days}"
+            )),
+            Self::RollingDays(days) => Ok(days - 1),
+        }
+    }
+}
+
+impl FromStr for LookbackMode {
+    type Err = String;
+
+    /// Parse `week`, or `rolling:N` for the last `N` days.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+
+        match s.split_once(':') {
+            None if s == "week" => Ok(Self::CalendarWeek),
+            Some(("rolling", days)) => days
+                .trim()
+                .parse()
+                .map(Self::RollingDays)
+                .map_err(|_| format!("Failed to parse LOOKBACK_MODE days: {days}")),
+            _ => Err(format!("Unknown LOOKBACK_MODE value: {s}")),
+        }
+    }
+}
+
+pub struct AppSettings {
+    wakatime_api_key: String,
+    wakatime_extra_keys: Vec<String>,
+    wakatime_account_failure: AccountFailure,
+    twitter_credentials: twitter::Credentials,
+    burnout_limit: f64,
+    timezone_offset: i8,
+    timezone_offset_minutes: Option<i16>,
+    meter_length: u8,
+    max_session_hours: Option<f64>,
+    wakatime_project: Option<String>,
+    daemon_interval_minutes: Option<u64>,
+    meter_style: MeterStyle,
+    daily_limit: Option<f64>,
+    dump_target: Option<DumpTarget>,
+    thresholds: Option<[f64; 3]>,
+    update_on: UpdateOn,
+    output_format: OutputFormat,
+    state_path: PathBuf,
+    location_template: String,
+    hours_label: Option<String>,
+    centered: bool,
+    reversed: bool,
+    orientation: Orientation,
+    goal_hours: Option<f64>,
+    sanity_max_hours: f64,
+    force_hours: Option<f64>,
+    hours_per_day: f64,
+    hours_window: HoursWindow,
+    twitter_retry_on: RetryOn,
+    twitter_rate_limit_max_wait_secs: Option<u64>,
+    dominant_share: f64,
+    break_after_days: Option<u32>,
+    break_message: String,
+    break_sentinel: PathBuf,
+    rolling_days: usize,
+    captions: HashMap<Band, String>,
+    wakatime_base_url: Option<String>,
+    twitter_base_url: Option<String>,
+    also_post: bool,
+    notify_on: Option<NotifyOn>,
+    summary: bool,
+    backend: Backend,
+    exec_command: Option<String>,
+    mastodon_instances: Vec<(String, String)>,
+    mastodon_field: String,
+    discord_webhook_url: Option<String>,
+    telegram_bot_token: Option<String>,
+    telegram_chat_id: Option<String>,
+    telegram_base_url: Option<String>,
+    slack_webhook_url: Option<String>,
+    slack_bot_token: Option<String>,
+    slack_channel: Option<String>,
+    slack_user_token: Option<String>,
+    slack_status_minutes: Option<i64>,
+    bluesky_handle: Option<String>,
+    bluesky_app_password: Option<String>,
+    bluesky_base_url: Option<String>,
+    no_data_glyph: String,
+    run_budget_ms: Option<u64>,
+    pre_update_hook: Option<String>,
+    provisional_margin: Option<f64>,
+    meter_unit: Option<Unit>,
+    away_days: Option<usize>,
+    new_week_update: bool,
+    clamp_display: bool,
+    work_hours: Option<WorkHours>,
+    rate_limit_fallback: bool,
+    treat_empty_as_zero: bool,
+    wakatime_cache_path: Option<PathBuf>,
+    json_precision: Option<u32>,
+    top_languages: usize,
+    show_top_language: bool,
+    wakatime_min_interval_secs: Option<u64>,
+    dry_run: bool,
+    force_update: bool,
+    twitter_target_field: TargetField,
+    truncate_location: bool,
+    lookback_mode: LookbackMode,
+    week_start: Weekday,
+    github_token: Option<String>,
+    github_base_url: Option<String>,
+    github_user: Option<String>,
+    github_status: bool,
+    github_status_minutes: Option<i64>,
+    blend_weights: BlendWeights,
+}
+
+impl AppSettings {
+    fn wakatime_api_key(&self) -> &str {
+        self.wakatime_api_key.as_ref()
+    }
+
+    /// The keys from `WAKATIME_API_KEYS` other than `WAKATIME_API_KEY`.
+    fn wakatime_extra_keys(&self) -> &[String] {
+        &self.wakatime_extra_keys
+    }
+
+    fn wakatime_account_failure(&self) -> AccountFailure {
+        self.wakatime_account_failure
+    }
+
+    fn twitter_credentials(&self) -> twitter::Credentials {
+        self.twitter_credentials.clone()
+    }
+
+    fn burnout_limit(&self) -> f64 {
+        self.burnout_limit
+    }
+
+    /// The offset from `TIMEZONE_OFFSET_MINUTES` if it's set, or else from
+    /// `TIMEZONE_OFFSET`.
+    fn utc_offset(&self) -> UtcOffset {
+        match self.timezone_offset_minutes {
+            Some(minutes) => offset_from_minutes(minutes),
+            None => UtcOffset::from_hms(self.timezone_offset, 0, 0).unwrap_or(UtcOffset::UTC),
+        }
+    }
+
+    fn meter_length(&self) -> u8 {
+        self.meter_length
+    }
+
+    fn max_session_hours(&self) -> Option<f64> {
+        self.max_session_hours
+    }
+
+    fn wakatime_project(&self) -> Option<String> {
+        self.wakatime_project.clone()
+    }
+
+    fn meter_style(&self) -> MeterStyle {
+        self.meter_style
+    }
+
+    fn daily_limit(&self) -> Option<f64> {
+        self.daily_limit
+    }
+
+    fn dump_target(&self) -> Option<DumpTarget> {
+        self.dump_target.clone()
+    }
+
+    fn thresholds(&self) -> Option<[f64; 3]> {
+        self.thresholds
+    }
+
+    fn update_on(&self) -> UpdateOn {
+        self.update_on
+    }
+
+    /// What the meter is printed as, from `--format` or `OUTPUT_FORMAT`.
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.state_path.clone()
+    }
+
+    fn location_template(&self) -> String {
+        self.location_template.clone()
+    }
+
+    /// The `HOURS_LABEL`, or the label of the meter's `unit` if unset.
+    fn hours_label(&self, unit: Unit) -> String {
+        self.hours_label
+            .clone()
+            .unwrap_or_else(|| unit.label().to_string())
+    }
+
+    fn centered(&self) -> bool {
+        self.centered
+    }
+
+    fn reversed(&self) -> bool {
+        self.reversed
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    fn goal_hours(&self) -> Option<f64> {
+        self.goal_hours
+    }
+
+    fn sanity_max_hours(&self) -> f64 {
+        self.sanity_max_hours
+    }
+
+    fn force_hours(&self) -> Option<f64> {
+        self.force_hours
+    }
+
+    fn hours_per_day(&self) -> f64 {
+        self.hours_per_day
+    }
+
+    fn break_after_days(&self) -> Option<u32> {
+        self.break_after_days
+    }
+
+    fn break_message(&self) -> String {
+        self.break_message.clone()
+    }
+
+    fn break_sentinel(&self) -> &PathBuf {
+        &self.break_sentinel
+    }
+
+    fn dominant_share(&self) -> f64 {
+        self.dominant_share
+    }
+
+    fn twitter_retry_on(&self) -> RetryOn {
+        self.twitter_retry_on
+    }
+
+    fn twitter_rate_limit_max_wait(&self) -> Option<Duration> {
+        self.twitter_rate_limit_max_wait_secs
+            .map(Duration::from_secs)
+    }
+
+    fn hours_window(&self) -> HoursWindow {
+        self.hours_window
+    }
+
+    fn rolling_days(&self) -> usize {
+        self.rolling_days.max(1)
+    }
+
+    fn captions(&self) -> &HashMap<Band, String> {
+        &self.captions
+    }
+
+    fn wakatime_base_url(&self) -> Option<&str> {
+        self.wakatime_base_url.as_deref()
+    }
+
+    fn twitter_base_url(&self) -> Option<&str> {
+        self.twitter_base_url.as_deref()
+    }
+
+    fn also_post(&self) -> bool {
+        self.also_post
+    }
+
+    fn notify_on(&self) -> Option<NotifyOn> {
+        self.notify_on
+    }
+
+    fn summary(&self) -> bool {
+        self.summary
+    }
+
+    fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn exec_command(&self) -> Option<&str> {
+        self.exec_command.as_deref()
+    }
+
+    fn mastodon_instances(&self) -> &[(String, String)] {
+        &self.mastodon_instances
+    }
+
+    fn mastodon_field(&self) -> &str {
+        &self.mastodon_field
+    }
+
+    fn discord_webhook_url(&self) -> Option<&str> {
+        self.discord_webhook_url.as_deref()
+    }
+
+    fn telegram_credentials(&self) -> Result<Option<(&str, &str)>, String> {
+        match (&self.telegram_bot_token, &self.telegram_chat_id) {
+            (Some(bot_token), Some(chat_id)) => Ok(Some((bot_token, chat_id))),
+            (None, None) => Ok(None),
+            _ => Err("TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID must be set together".to_string()),
+        }
+    }
+
+    fn telegram_base_url(&self) -> Option<&str> {
+        self.telegram_base_url.as_deref()
+    }
+
+    fn slack_user_token(&self) -> Option<&str> {
+        self.slack_user_token.as_deref()
+    }
+
+    fn slack_status_minutes(&self) -> Option<i64> {
+        self.slack_status_minutes
+    }
+
+    /// Where to post in Slack, preferring `SLACK_WEBHOOK_URL` over a bot.
+    fn slack_destination(&self) -> Result<Option<Destination>, String> {
+        if let Some(webhook_url) = &self.slack_webhook_url {
+            return Ok(Some(Destination::Webhook(webhook_url.clone())));
+        }
+
+        match (&self.slack_bot_token, &self.slack_channel) {
+            (Some(token), Some(channel)) => Ok(Some(Destination::Channel {
+                token: token.clone(),
+                channel: channel.clone(),
+            })),
+            (None, None) => Ok(None),
+            _ => Err("SLACK_BOT_TOKEN and SLACK_CHANNEL must be set together".to_string()),
+        }
+    }
+
+    fn bluesky_credentials(&self) -> Result<Option<(&str, &str)>, String> {
+        match (&self.bluesky_handle, &self.bluesky_app_password) {
+            (Some(handle), Some(app_password)) => Ok(Some((handle, app_password))),
+            (None, None) => Ok(None),
+            _ => Err("BLUESKY_HANDLE and BLUESKY_APP_PASSWORD must be set together".to_string()),
+        }
+    }
+
+    fn bluesky_base_url(&self) -> Option<&str> {
+        self.bluesky_base_url.as_deref()
+    }
+
+    fn no_data_glyph(&self) -> &str {
+        &self.no_data_glyph
+    }
+
+    fn run_budget(&self) -> Option<Duration> {
+        self.run_budget_ms.map(Duration::from_millis)
+    }
+
+    fn pre_update_hook(&self) -> Option<&str> {
+        self.pre_update_hook.as_deref()
+    }
+
+    fn provisional_margin(&self) -> Option<f64> {
+        self.provisional_margin
+    }
+
+    fn meter_unit(&self) -> Option<Unit> {
+        self.meter_unit
+    }
+
+    fn away_days(&self) -> Option<usize> {
+        self.away_days
+    }
+
+    fn new_week_update(&self) -> bool {
+        self.new_week_update
+    }
+
+    fn clamp_display(&self) -> bool {
+        self.clamp_display
+    }
+
+    /// The `WORK_HOURS` window, in the timezone from [`Self::utc_offset`].
+    fn work_hours(&self) -> Option<WorkHours> {
+        self.work_hours.map(|work_hours| WorkHours {
+            offset: self.utc_offset(),
+            ..work_hours
+        })
+    }
+
+    fn rate_limit_fallback(&self) -> bool {
+        self.rate_limit_fallback
+    }
+
+    fn treat_empty_as_zero(&self) -> bool {
+        self.treat_empty_as_zero
+    }
+
+    fn wakatime_cache_path(&self) -> Option<&PathBuf> {
+        self.wakatime_cache_path.as_ref()
+    }
+
+    fn json_precision(&self) -> Option<u32> {
+        self.json_precision
+    }
+
+    fn top_languages(&self) -> usize {
+        self.top_languages
+    }
+
+    fn show_top_language(&self) -> bool {
+        self.show_top_language
+    }
+
+    fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    fn force_update(&self) -> bool {
+        self.force_update
+    }
+
+    /// What the last posted location is kept under, so switching the backend
+    /// or the Twitter field posts again.
+    fn posted_target(&self) -> String {
+        format!("{}:{}", self.backend, self.twitter_target_field)
+    }
+
+    fn twitter_target_field(&self) -> TargetField {
+        self.twitter_target_field
+    }
+
+    fn truncate_location(&self) -> bool {
+        self.truncate_location
+    }
+
+    fn lookback_mode(&self) -> LookbackMode {
+        self.lookback_mode
+    }
+
+    fn week_start(&self) -> Weekday {
+        self.week_start
+    }
+
+    fn wakatime_min_interval(&self) -> Option<Duration> {
+        self.wakatime_min_interval_secs.map(Duration::from_secs)
+    }
+
+    /// The directory holding `STATE_PATH`, where what's shared between
+    /// processes is kept.
+    fn shared_dir(&self) -> PathBuf {
+        match self.state_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        }
+    }
+
+    fn github_base_url(&self) -> Option<&str> {
+        self.github_base_url.as_deref()
+    }
+
+    fn github_token(&self) -> Option<&str> {
+        self.github_token.as_deref()
+    }
+
+    fn github_status(&self) -> bool {
+        self.github_status
+    }
+
+    fn github_status_minutes(&self) -> Option<i64> {
+        self.github_status_minutes
+    }
+
+    fn github_credentials(&self) -> Option<(String, String)> {
+        match (&self.github_token, &self.github_user) {
+            (Some(token), Some(user)) => Some((token.clone(), user.clone())),
+            _ => None,
+        }
+    }
+
+    fn blend_weights(&self) -> BlendWeights {
+        self.blend_weights
+    }
+
+    /// How often to update when running as a daemon, or `None` to run once.
+    pub fn daemon_interval(&self) -> Option<Duration> {
+        self.daemon_interval_minutes
+            .map(|minutes| Duration::from_secs(minutes * 60))
+    }
+}
+
+impl AppSettings {
+    /// Load the settings from the env, or from the config file given by
+    /// `--config` or `BURNOUT_CONFIG`, with `--profile` picking the
+    /// credentials from one of its profiles. The rest of `args` override the
+    /// env.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file or profile can't be loaded, or a
+    /// credential isn't set.
+    pub fn load(args: &Args) -> Result<Self, Box<dyn Error>> {
+        let mut settings = Self::load_credentials(args)?;
+
+        if let Some(format) = args.format {
+            settings.output_format = format;
+        }
+        settings.force_update |= args.force;
+        if args.dump_wakatime && settings.dump_target.is_none() {
+            settings.dump_target = Some(DumpTarget::Stderr);
+        }
+
+        Ok(settings)
+    }
+
+    /// Load the settings for [`Self::load`], before the rest of `args`.
+    fn load_credentials(args: &Args) -> Result<Self, Box<dyn Error>> {
+        let config_path = args.config.clone().or_else(|| {
+            get_env_var::<String>("BURNOUT_CONFIG")
+                .ok()
+                .map(PathBuf::from)
+        });
+
+        if let (None, Some(path)) = (&args.profile, &config_path) {
+            return Self::from_file(path);
+        }
+
+        let mut config = None;
+        let (wakatime_api_key, twitter_credentials) = match &args.profile {
+            Some(profile) => {
+                let config_path = config_path.unwrap_or_else(|| "burnout.toml".into());
+                let loaded = Config::load(&config_path)?;
+                let profile = loaded.profile(profile)?.clone();
+                config = Some(loaded);
+
+                (profile.wakatime_api_key, profile.twitter)
+            }
+            None => (
+                // the first of several keys will do if there isn't just the one
+                get_env_var("WAKATIME_API_KEY")
+                    .ok()
+                    .or_else(|| wakatime_api_keys().into_iter().next())
+                    .ok_or("WAKATIME_API_KEY or WAKATIME_API_KEYS must be set")?,
+                twitter::Credentials {
+                    consumer_key: get_env_var("TWITTER_CONSUMER_KEY")?,
+                    consumer_secret: get_env_var("TWITTER_CONSUMER_SECRET")?,
+                    access_token: get_env_var("TWITTER_ACCESS_TOKEN")?,
+                    access_token_secret: get_env_var("TWITTER_ACCESS_TOKEN_SECRET")?,
+                },
+            ),
+        };
+
+        let mut settings = Self::with_credentials(wakatime_api_key, twitter_credentials)?;
+
+        if let Some(config) = config {
+            settings.apply_meter_config(&config.meter)?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Load the settings from the TOML config file at `path`, with the
+    /// credentials from its `[wakatime]` and `[twitter]` tables and the meter
+    /// from its `[meter]` table.
+    ///
+    /// The env overrides the file, and sets everything the file can't.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be loaded, or a credential is in
+    /// neither the file nor the env.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let config = Config::load(path)?;
+
+        let setting = |key: &str, file: &Option<String>| match (get_env_var(key), file) {
+            (Ok(value), _) => Ok(value),
+            (Err(_), Some(value)) => Ok(value.clone()),
+            (Err(_), None) => Err(format!("{key} not set and not in {}", path.display())),
+        };
+
+        let twitter = &config.twitter;
+        let twitter_credentials = twitter::Credentials {
+            consumer_key: setting("TWITTER_CONSUMER_KEY", &twitter.consumer_key)?,
+            consumer_secret: setting("TWITTER_CONSUMER_SECRET", &twitter.consumer_secret)?,
+            access_token: setting("TWITTER_ACCESS_TOKEN", &twitter.access_token)?,
+            access_token_secret: setting(
+                "TWITTER_ACCESS_TOKEN_SECRET",
+                &twitter.access_token_secret,
+            )?,
+        };
+
+        let mut settings = Self::with_credentials(
+            setting("WAKATIME_API_KEY", &config.wakatime.api_key)?,
+            twitter_credentials,
+        )?;
+        settings.apply_meter_config(&config.meter)?;
+
+        Ok(settings)
+    }
+
+    /// Use the `[meter]` table of the config for anything the env doesn't set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thresholds can't be parsed, as
+    /// `--validate-config` would report.
+    fn apply_meter_config(&mut self, meter: &MeterConfig) -> Result<(), Box<dyn Error>> {
+        if let (Err(_), Some(limit)) = (get_env_var::<f64>("BURNOUT_LIMIT"), meter.limit) {
+            self.burnout_limit = limit;
+        }
+
+        if let (Err(_), Some(length)) = (get_env_var::<u8>("METER_LENGTH"), meter.length) {
+            self.meter_length = length;
+        }
+
+        if let (Err(_), Some(thresholds)) = (get_env_var::<String>("THRESHOLDS"), &meter.thresholds)
+        {
+            self.thresholds =
+                parse_thresholds(thresholds).map_err(|e| format!("meter.thresholds: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// The settings for the given credentials, with everything else read from
+    /// the env.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `THRESHOLDS`, `CAPTIONS`, `MASTODON_INSTANCES`,
+    /// `WEEK_START` or the output format can't be parsed.
+    fn with_credentials(
+        wakatime_api_key: String,
+        twitter_credentials: twitter::Credentials,
+    ) -> Result<Self, Box<dyn Error>> {
+        let wakatime_extra_keys = wakatime_api_keys()
+            .into_iter()
+            .filter(|api_key| *api_key != wakatime_api_key)
+            .collect();
+        let meter_unit: Option<Unit> = get_env_var::<String>("METER_UNIT")
+            .ok()
+            .map(|unit| unit.parse())
+            .transpose()?;
+
+        Ok(Self {
+            wakatime_api_key,
+            wakatime_extra_keys,
+            wakatime_account_failure: get_env_var::<String>("WAKATIME_ACCOUNT_FAILURE")
+                .map_or(Ok(AccountFailure::default()), |value| value.parse())?,
+            twitter_credentials,
+            burnout_limit: resolve_limit(
+                get_env_var("BURNOUT_LIMIT").ok(),
+                get_env_var("WEEKLY_GOAL").ok(),
+                get_env_var("BURNOUT_MULTIPLIER").ok(),
+            ),
+            timezone_offset: get_env_var("TIMEZONE_OFFSET").unwrap_or(0),
+            timezone_offset_minutes: get_env_var("TIMEZONE_OFFSET_MINUTES").ok(),
+            meter_length: get_env_var("METER_LENGTH").unwrap_or(8),
+            max_session_hours: get_env_var("MAX_SESSION_HOURS").ok(),
+            wakatime_project: get_env_var("WAKATIME_PROJECT").ok(),
+            daemon_interval_minutes: get_env_var("DAEMON_INTERVAL_MINUTES").ok(),
+            meter_style: get_env_var::<String>("METER_STYLE")
+                .map_or(Ok(MeterStyle::default()), |value| value.parse())?,
+            daily_limit: get_env_var("DAILY_LIMIT").ok(),
+            dump_target: match get_env_var::<String>("DUMP_RESPONSES_PATH") {
+                Ok(path) => Some(DumpTarget::File(path.into())),
+                Err(_) if get_env_var("DUMP_RESPONSES").unwrap_or(false) => {
+                    Some(DumpTarget::Stderr)
+                }
+                Err(_) => None,
+            },
+            thresholds: parse_thresholds(&get_env_var::<String>("THRESHOLDS").unwrap_or_default())?,
+            update_on: get_env_var::<String>("UPDATE_ON")
+                .map_or(Ok(UpdateOn::default()), |value| value.parse())?,
+            output_format: get_env_var::<String>("OUTPUT_FORMAT")
+                .map_or(Ok(OutputFormat::default()), |format| format.parse())?,
+            state_path: get_env_var("STATE_PATH").unwrap_or_else(|_| ".burnout-state.json".into()),
+            location_template: get_env_var("LOCATION_TEMPLATE").unwrap_or_else(|_| {
+                template::default_template(meter_unit.unwrap_or_default()).to_string()
+            }),
+            hours_label: get_env_var("HOURS_LABEL").ok(),
+            centered: get_env_var("METER_CENTERED").unwrap_or(false),
+            reversed: get_env_var("METER_REVERSED").unwrap_or(false),
+            orientation: get_env_var::<String>("METER_ORIENTATION")
+                .map_or(Ok(Orientation::default()), |value| value.parse())?,
+            goal_hours: get_env_var("GOAL_HOURS").ok(),
+            sanity_max_hours: get_env_var("SANITY_MAX_HOURS").unwrap_or(168.0),
+            force_hours: get_env_var("FORCE_HOURS").ok(),
+            hours_per_day: get_env_var("HOURS_PER_DAY").unwrap_or(8.0),
+            hours_window: get_env_var::<String>("HOURS_WINDOW")
+                .map_or(Ok(HoursWindow::default()), |value| value.parse())?,
+            twitter_retry_on: get_env_var::<String>("TWITTER_RETRY_ON")
+                .map_or(Ok(RetryOn::default()), |value| value.parse())?,
+            twitter_rate_limit_max_wait_secs: get_env_var("TWITTER_RATE_LIMIT_MAX_WAIT_SECS").ok(),
+            dominant_share: get_env_var("DOMINANT_SHARE").unwrap_or(0.8),
+            break_after_days: get_env_var("BREAK_AFTER_DAYS").ok(),
+            break_message: get_env_var("BREAK_MESSAGE")
+                .unwrap_or_else(|_| hardstop::DEFAULT_BREAK_MESSAGE.to_string()),
+            break_sentinel: get_env_var("BREAK_SENTINEL")
+                .unwrap_or_else(|_| ".burnout-break".into()),
+            rolling_days: get_env_var("ROLLING_DAYS").unwrap_or(7),
+            captions: parse_captions(&get_env_var::<String>("CAPTIONS").unwrap_or_default())?,
+            wakatime_base_url: get_env_var("WAKATIME_BASE_URL").ok(),
+            twitter_base_url: get_env_var("TWITTER_API_URL").ok(),
+            also_post: get_env_var("ALSO_POST").unwrap_or(false),
+            notify_on: get_env_var::<String>("NOTIFY_ON")
+                .ok()
+                .map(|notify_on| notify_on.parse())
+                .transpose()?,
+            summary: get_env_var("SUMMARY").unwrap_or(false),
+            backend: get_env_var::<String>("PROFILE_BACKEND")
+                .map_or(Ok(Backend::default()), |value| value.parse())?,
+            exec_command: get_env_var("EXEC_COMMAND").ok(),
+            mastodon_instances: mastodon::parse_instances(
+                &get_env_var::<String>("MASTODON_INSTANCES").unwrap_or_default(),
+            )?,
+            mastodon_field: get_env_var("MASTODON_FIELD")
+                .unwrap_or_else(|_| mastodon::DEFAULT_FIELD.to_string()),
+            discord_webhook_url: get_env_var("DISCORD_WEBHOOK_URL").ok(),
+            telegram_bot_token: get_env_var("TELEGRAM_BOT_TOKEN").ok(),
+            telegram_chat_id: get_env_var("TELEGRAM_CHAT_ID").ok(),
+            telegram_base_url: get_env_var("TELEGRAM_API_URL").ok(),
+            slack_webhook_url: get_env_var("SLACK_WEBHOOK_URL").ok(),
+            slack_bot_token: get_env_var("SLACK_BOT_TOKEN").ok(),
+            slack_channel: get_env_var("SLACK_CHANNEL").ok(),
+            slack_user_token: get_env_var("SLACK_USER_TOKEN").ok(),
+            slack_status_minutes: get_env_var("SLACK_STATUS_MINUTES").ok(),
+            bluesky_handle: get_env_var("BLUESKY_HANDLE").ok(),
+            bluesky_app_password: get_env_var("BLUESKY_APP_PASSWORD").ok(),
+            bluesky_base_url: get_env_var("BLUESKY_PDS_URL").ok(),
+            no_data_glyph: get_env_var("NO_DATA_GLYPH")
+                .unwrap_or_else(|_| meter::DEFAULT_NO_DATA.to_string()),
+            run_budget_ms: get_env_var("RUN_BUDGET_MS").ok(),
+            pre_update_hook: get_env_var("PRE_UPDATE_HOOK").ok(),
+            provisional_margin: get_env_var("PROVISIONAL_MARGIN").ok(),
+            meter_unit,
+            away_days: get_env_var("AWAY_DAYS").ok(),
+            new_week_update: get_env_var("NEW_WEEK_UPDATE").unwrap_or(true),
+            clamp_display: get_env_var("CLAMP_DISPLAY").unwrap_or(false),
+            work_hours: get_env_var::<String>("WORK_HOURS")
+                .ok()
+                .map(|work_hours| work_hours.parse::<WorkHours>())
+                .transpose()?
+                .map(|work_hours| WorkHours {
+                    off_hours_multiplier: get_env_var("OFF_HOURS_MULTIPLIER")
+                        .unwrap_or(DEFAULT_OFF_HOURS_MULTIPLIER),
+                    ..work_hours
+                }),
+            rate_limit_fallback: get_env_var("RATE_LIMIT_FALLBACK").unwrap_or(false),
+            treat_empty_as_zero: get_env_var("TREAT_EMPTY_AS_ZERO").unwrap_or(true),
+            wakatime_cache_path: get_env_var("WAKATIME_CACHE_PATH").ok(),
+            json_precision: get_env_var("JSON_PRECISION").ok(),
+            top_languages: get_env_var("TOP_LANGUAGES").unwrap_or(3),
+            show_top_language: get_env_var("SHOW_TOP_LANGUAGE").unwrap_or(false),
+            wakatime_min_interval_secs: get_env_var("WAKATIME_MIN_INTERVAL_SECS").ok(),
+            dry_run: get_env_var("DRY_RUN").unwrap_or(false),
+            force_update: get_env_var("FORCE_UPDATE").unwrap_or(false),
+            twitter_target_field: get_env_var::<String>("TWITTER_TARGET_FIELD")
+                .map_or(Ok(TargetField::default()), |value| value.parse())?,
+            truncate_location: get_env_var("TRUNCATE_LOCATION").unwrap_or(false),
+            lookback_mode: get_env_var::<String>("LOOKBACK_MODE")
+                .map_or(Ok(LookbackMode::default()), |value| value.parse())?,
+            week_start: get_env_var::<String>("WEEK_START")
+                .map_or(Ok(Weekday::Monday), |day| parse_weekday(&day))?,
+            github_token: get_env_var("GITHUB_TOKEN").ok(),
+            github_base_url: get_env_var("GITHUB_API_URL").ok(),
+            github_user: get_env_var("GITHUB_USER").ok(),
+            github_status: get_env_var("GITHUB_STATUS").unwrap_or(false),
+            github_status_minutes: get_env_var("GITHUB_STATUS_MINUTES").ok(),
+            blend_weights: BlendWeights {
+                pull_request: get_env_var("GITHUB_PR_WEIGHT")
+                    .unwrap_or(BlendWeights::default().pull_request),
+                review: get_env_var("GITHUB_REVIEW_WEIGHT")
+                    .unwrap_or(BlendWeights::default().review),
+            },
+        })
+    }
+}
+
+/// Whether `week` is a different week to the last update's, so the update
+/// should go out even if nothing else changed.
+///
+/// Before the first update there's no last week to compare with.
+fn is_new_week(last_update_week: Option<&str>, week: &str) -> bool {
+    last_update_week.is_some_and(|last_update_week| last_update_week != week)
+}
+
+/// Post a notification if the meter crossed into a different band since the
+/// last run, only going up unless `notify_on` is [`NotifyOn::Both`].
+///
+/// Returns whether a notification was posted.
+async fn notify_crossing(
+    twitter: &Twitter,
+    last_band: Option<Band>,
+    band: Band,
+    notify_on: NotifyOn,
+) -> Result<bool, Box<dyn Error>> {
+    let last_band = match last_band {
+        Some(last_band) => last_band,
+        None => return Ok(false),
+    };
+
+    let text = if band > last_band {
+        format!("You just entered {} {band} territory", band.glyph())
+    } else if band < last_band && notify_on == NotifyOn::Both {
+        format!("You just dropped back to {} {band} territory", band.glyph())
+    } else {
+        return Ok(false);
+    };
+
+    let tweet = twitter.post_update(text).await?;
+    log::info!("Posted notification: {}", tweet.text);
+
+    Ok(true)
+}
+
+/// Reuse the hours from the last run if `error` is WakaTime rate limiting us,
+/// otherwise pass the error on.
+///
+/// The last run only stands in if it was in the same week as `now`, at
+/// `offset` in weeks beginning on `week_start`, and no older than
+/// [`cache::MAX_AGE`], like the hours cached on disk.
+fn fall_back_to_cache(
+    error: Box<dyn Error>,
+    state: &State,
+    now: OffsetDateTime,
+    offset: UtcOffset,
+    week_start: Weekday,
+) -> Result<f64, Box<dyn Error>> {
+    if !matches!(
+        error.downcast_ref::<BurnoutError>(),
+        Some(BurnoutError::RateLimited)
+    ) {
+        return Err(error);
+    }
+
+    let week = week_id_starting_offset(now, offset, week_start);
+    let hours = state.history.last().and_then(|entry| {
+        let recorded = OffsetDateTime::from_unix_timestamp(entry.timestamp).ok()?;
+
+        if week_id_starting_offset(recorded, offset, week_start) != week {
+            return None;
+        }
+
+        CachedHours {
+            hours: entry.hours,
+            timestamp: entry.timestamp,
+        }
+        .fresh_hours(now)
+    });
+
+    match hours {
+        Some(hours) => {
+            log::warn!("{error}, using the cached {hours} hours");
+            Ok(hours)
+        }
+        None => Err(format!("{error} and there are no recent hours to fall back on").into()),
+    }
+}
+
+/// The average of the last `days` of `daily` hours, scaled up to a week, so
+/// one long day is spread over the whole window rather than counted in full.
+fn rolling_hours(daily: &[f64], days: usize) -> f64 {
+    let window = &daily[daily.len().saturating_sub(days)..];
+
+    match window.len() {
+        0 => 0f64,
+        len => window.iter().sum::<f64>() / len as f64 * 7f64,
+    }
+}
+
+/// The project with at least `share` of the hours in `projects`, if one
+/// dominates the week.
+fn dominant_project(projects: &[(String, f64)], share: f64) -> Option<&str> {
+    let total: f64 = projects.iter().map(|(_, hours)| hours).sum();
+    let (name, hours) = projects.iter().max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+    (total > 0f64 && hours / total >= share).then_some(name.as_str())
+}
+
+/// The hours as work days of `hours_per_day`, to two decimal places, e.g.
+/// `2.75` for 22 hours of 8 hour days.
+fn work_days(hours: f64, hours_per_day: f64) -> String {
+    ((hours / hours_per_day * 100f64).round() / 100f64).to_string()
+}
+
+/// The text of the daily log post.
+fn daily_log_text(meter: &meter::Builder, hours: f64) -> String {
+    format!(
+        "{meter} {:.1}/{} hours of coding this week",
+        hours,
+        meter.max()
+    )
+}
+
+/// Set the `field` on every Mastodon instance to `location`.
+///
+/// Returns an error if no instance was updated.
+async fn update_mastodon(
+    mastodon: &[Mastodon],
+    field: &str,
+    location: &str,
+) -> Result<bool, Box<dyn Error>> {
+    let updates = mastodon::update_all(mastodon, field, location).await;
+
+    log::info!("{updates}");
+    for (base_url, e) in &updates.failed {
+        log::warn!("Failed to update {base_url}: {e}");
+    }
+
+    match updates.updated.is_empty() {
+        true => Err(updates.to_string().into()),
+        false => Ok(true),
+    }
+}
+
+/// Not updated rather than failed if the backend was skipped for an open
+/// circuit, and the error `e` otherwise.
+fn skipped_if_open(e: Box<dyn Error>) -> Result<bool, Box<dyn Error>> {
+    match e.downcast_ref::<BurnoutError>() {
+        Some(BurnoutError::CircuitOpen(_)) => {
+            log::warn!("{e}");
+            Ok(false)
+        }
+        _ => Err(e),
+    }
+}
+
+/// Post `text` as a status, unless the daily log was already posted `today`.
+///
+/// Returns whether it was posted.
+async fn post_daily_log(
+    twitter: &Twitter,
+    state: &mut State,
+    text: &str,
+    today: &str,
+) -> Result<bool, Box<dyn Error>> {
+    if state.last_post_day.as_deref() == Some(today) {
+        return Ok(false);
+    }
+
+    let tweet = twitter.post_update(text).await?;
+    log::info!("Posted daily log: {}", tweet.text);
+
+    state.last_post_day = Some(today.to_string());
+
+    Ok(true)
+}
+
+/// Check the hours are plausible, between zero and `max_hours`.
+///
+/// # Errors
+///
+/// Returns an error describing the problem if they aren't.
+fn check_sanity(hours: f64, max_hours: f64) -> Result<(), Box<dyn Error>> {
+    if !hours.is_finite() || hours < 0f64 {
+        return Err(format!("Got {hours} hours, which can't be right, skipping update").into());
+    }
+
+    if hours > max_hours {
+        return Err(format!(
+            "Got {hours} hours, more than the SANITY_MAX_HOURS of {max_hours}, skipping update"
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Whether to celebrate reaching the weekly goal, which happens once a week
+/// when the hours reach `goal` without going over `limit`.
+fn should_celebrate(
+    goal: Option<f64>,
+    hours: f64,
+    limit: f64,
+    celebrated_week: Option<&str>,
+    week: &str,
+) -> bool {
+    let reached = match goal {
+        Some(goal) => hours >= goal && hours <= limit,
+        None => false,
+    };
+
+    reached && celebrated_week != Some(week)
+}
+
+/// Suggest a burnout limit from weekly totals, using the 90th percentile
+/// (nearest rank) rounded up to a whole hour.
+///
+/// Returns `None` if there are no weeks to go on.
+fn suggest_limit(weekly: &[f64]) -> Option<f64> {
+    if weekly.is_empty() {
+        return None;
+    }
+
+    let mut sorted = weekly.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let rank = (0.9 * sorted.len() as f64).ceil() as usize;
+
+    Some(sorted[rank.max(1) - 1].ceil())
+}
+
+/// The burnout limit: an explicit `limit` if there is one, otherwise `goal`
+/// times `multiplier` if both are set, otherwise 40 hours.
+fn resolve_limit(limit: Option<f64>, goal: Option<f64>, multiplier: Option<f64>) -> f64 {
+    match (limit, goal, multiplier) {
+        (Some(limit), _, _) => limit,
+        (None, Some(goal), Some(multiplier)) => goal * multiplier,
+        _ => 40.0,
+    }
+}
+
+/// The comma separated keys in `WAKATIME_API_KEYS`, one for each account.
+fn wakatime_api_keys() -> Vec<String> {
+    parse_api_keys(&get_env_var::<String>("WAKATIME_API_KEYS").unwrap_or_default())
+}
+
+/// Parse a comma separated list of API keys, skipping blanks, e.g.
+/// `waka_1, waka_2`.
+fn parse_api_keys(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|api_key| !api_key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse a `;` separated list of captions for each band, e.g.
+/// `fresh=Cruising;burnout=Take a break!`.
+///
+/// # Errors
+///
+/// Returns an error if an entry isn't `band=caption` or names an unknown band.
+fn parse_captions(value: &str) -> Result<HashMap<Band, String>, Box<dyn Error>> {
+    value
+        .split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((band, caption)) => Ok((
+                band.trim()
+                    .parse()
+                    .map_err(|e| format!("Failed to parse CAPTIONS entry: {e}"))?,
+                caption.trim().to_string(),
+            )),
+            None => Err(format!("Failed to parse CAPTIONS entry: {entry}").into()),
+        })
+        .collect()
+}
+
+/// Parse a comma separated list of the weekly hours where the meter turns
+/// yellow, orange, and red, e.g. `20,30,38`.
+///
+/// Returns `None` for an empty value, so the percentage defaults are used.
+///
+/// # Errors
+///
+/// Returns an error if there aren't exactly three numbers or they aren't ascending.
+pub(crate) fn parse_thresholds(value: &str) -> Result<Option<[f64; 3]>, Box<dyn Error>> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let thresholds = value
+        .split(',')
+        .map(|threshold| threshold.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Failed to parse THRESHOLDS: {value}"))?;
+
+    let thresholds: [f64; 3] = thresholds
+        .try_into()
+        .map_err(|_| format!("THRESHOLDS needs exactly three values: {value}"))?;
+
+    if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(format!("THRESHOLDS must be ascending: {value}").into());
+    }
+
+    Ok(Some(thresholds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_sanity, dominant_project, fall_back_to_cache, is_new_week, notify_crossing,
+        parse_api_keys, parse_captions, parse_thresholds, post_daily_log, resolve_limit,
+        rolling_hours, should_celebrate, suggest_limit, work_days, App, AppSettings, Backend,
+        HoursWindow, LookbackMode, NotifyOn, OutputFormat, UpdateOn,
+    };
+    use crate::{
+        cache::{self, CachedHours},
+        cli::Args,
+        error::BurnoutError,
+        meter::{Band, Builder, Unit},
+        ratelimit,
+        state::{HistoryEntry, State},
+        store::InMemoryStore,
+        template,
+        twitter::{Credentials, TargetField, Twitter},
+        util::week_id,
+        wakatime::{DumpTarget, WakaTime},
+    };
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use time::macros::{datetime, offset};
+    use time::{OffsetDateTime, UtcOffset, Weekday};
+
+    #[test]
+    fn test_parse_thresholds() {
+        assert_eq!(
+            parse_thresholds("20,30,38").unwrap(),
+            Some([20.0, 30.0, 38.0])
+        );
+        assert_eq!(
+            parse_thresholds(" 20.5, 30 ,38 ").unwrap(),
+            Some([20.5, 30.0, 38.0])
+        );
+    }
+
+    #[test]
+    fn test_parse_thresholds_not_ascending() {
+        let err = parse_thresholds("20,38,30").unwrap_err();
+        assert!(err.to_string().contains("ascending"), "{err}");
+
+        assert!(parse_thresholds("20,20,38").is_err());
+        assert!(parse_thresholds("20,30").is_err());
+        assert!(parse_thresholds("20,thirty,38").is_err());
+    }
+
+    #[test]
+    fn test_resolve_limit() {
+        assert_eq!(resolve_limit(None, Some(30.0), Some(1.5)), 45.0);
+
+        // an explicit limit wins over the goal
+        assert_eq!(resolve_limit(Some(50.0), Some(30.0), Some(1.5)), 50.0);
+
+        // the goal alone isn't enough to go on
+        assert_eq!(resolve_limit(None, Some(30.0), None), 40.0);
+        assert_eq!(resolve_limit(None, None, Some(1.5)), 40.0);
+        assert_eq!(resolve_limit(None, None, None), 40.0);
+    }
+
+    #[test]
+    fn test_parse_api_keys() {
+        assert_eq!(parse_api_keys("waka_1, waka_2,,"), ["waka_1", "waka_2"]);
+        assert!(parse_api_keys("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_captions() -> Result<(), Box<dyn Error>> {
+        let captions = parse_captions("fresh=Cruising; burnout = Take a break!;")?;
+
+        assert_eq!(captions.len(), 2);
+        assert_eq!(captions[&Band::Fresh], "Cruising");
+        assert_eq!(captions[&Band::Burnout], "Take a break!");
+
+        assert!(parse_captions("")?.is_empty());
+        assert!(parse_captions("fresh").is_err());
+        assert!(parse_captions("sleepy=zzz").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_template_by_unit() {
+        let mut meter = Builder::new();
+        meter.set_max(40).set_length(4).set_current(22);
+
+        let mut location = |unit| {
+            meter.set_unit(unit);
+
+            template::render(
+                template::default_template(unit),
+                &[
+                    ("meter", "🟨🟨⬜️⬜️".to_string()),
+                    ("hours", "22".to_string()),
+                    ("max", "40".to_string()),
+                    ("hours_label", unit.label().to_string()),
+                    ("quantity", meter.quantity()),
+                ],
+            )
+        };
+
+        assert_eq!(location(Unit::Hours), "🟨🟨⬜️⬜️ 22/40 hours");
+        // not 22/40 %
+        assert_eq!(location(Unit::Percent), "🟨🟨⬜️⬜️ 55%");
+    }
+
+    #[test]
+    fn test_parse_thresholds_empty() {
+        assert_eq!(parse_thresholds("").unwrap(), None);
+        assert_eq!(parse_thresholds("  ").unwrap(), None);
+    }
+
+    #[test]
+    fn test_update_on_band() {
+        let update_on = UpdateOn::Band;
+
+        // the first run always updates
+        assert!(update_on.should_update(None, Band::Fresh));
+
+        // runs that stay in the same band don't
+        let mut last_band = Some(Band::Fresh);
+        assert!(!update_on.should_update(last_band, Band::Fresh));
+        assert!(!update_on.should_update(last_band, Band::Fresh));
+
+        // crossing into a new band does
+        assert!(update_on.should_update(last_band, Band::Busy));
+        last_band = Some(Band::Busy);
+        assert!(!update_on.should_update(last_band, Band::Busy));
+
+        // and so does dropping back down
+        assert!(update_on.should_update(last_band, Band::Fresh));
+    }
+
+    #[test]
+    fn test_update_on_always() {
+        assert!(UpdateOn::Always.should_update(Some(Band::Fresh), Band::Fresh));
+        assert_eq!("band".parse(), Ok(UpdateOn::Band));
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!("JSON".parse(), Ok(OutputFormat::Json));
+        assert_eq!("text".parse(), Ok(OutputFormat::Text));
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_suggest_limit() {
+        let weekly = [10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0];
+        assert_eq!(suggest_limit(&weekly), Some(90.0));
+
+        // order doesn't matter, and the result is rounded up to a whole hour
+        let weekly = [41.2, 30.5, 38.0, 49.3, 35.0, 32.1, 44.7, 40.0];
+        assert_eq!(suggest_limit(&weekly), Some(50.0));
+
+        assert_eq!(suggest_limit(&[12.5]), Some(13.0));
+        assert_eq!(suggest_limit(&[]), None);
+    }
+
+    #[test]
+    fn test_should_celebrate_once_a_week() {
+        let (goal, limit) = (Some(30.0), 40.0);
+        let mut celebrated_week = None;
+
+        let mut run = |now, hours| {
+            let week = week_id(now, 0);
+            let celebrate = should_celebrate(goal, hours, limit, celebrated_week.as_deref(), &week);
+
+            if celebrate {
+                celebrated_week = Some(week);
+            }
+
+            celebrate
+        };
+
+        // not there yet
+        assert!(!run(datetime!(2023-01-17 12:00 UTC), 25.0));
+        // crossing the goal celebrates
+        assert!(run(datetime!(2023-01-18 12:00 UTC), 31.0));
+        // but only once that week
+        assert!(!run(datetime!(2023-01-19 12:00 UTC), 33.0));
+        assert!(!run(datetime!(2023-01-22 23:00 UTC), 35.0));
+        // a new week can celebrate again
+        assert!(run(datetime!(2023-01-27 12:00 UTC), 30.0));
+    }
+
+    #[test]
+    fn test_should_celebrate_within_limit() {
+        assert!(!should_celebrate(Some(30.0), 41.0, 40.0, None, "2023-W03"));
+        assert!(!should_celebrate(None, 35.0, 40.0, None, "2023-W03"));
+    }
+
+    #[test]
+    fn test_check_sanity() {
+        assert!(check_sanity(0.0, 168.0).is_ok());
+        assert!(check_sanity(42.5, 168.0).is_ok());
+        assert!(check_sanity(168.0, 168.0).is_ok());
+
+        let err = check_sanity(9999.0, 168.0).unwrap_err();
+        assert!(err.to_string().contains("SANITY_MAX_HOURS"), "{err}");
+
+        assert!(check_sanity(-3.0, 168.0).is_err());
+        assert!(check_sanity(f64::NAN, 168.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_post_daily_log_once_a_day() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let text = "🟨🟨⬜️⬜️ 22.0/40 hours of coding this week";
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/statuses/update.json")
+                .x_www_form_urlencoded_tuple("status", text);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "id_str": "1", "text": text }).to_string());
+        });
+
+        let twitter = mock_twitter(&mock_server)?;
+
+        let mut state = State::default();
+
+        assert!(post_daily_log(&twitter, &mut state, text, "2023-01-18").await?);
+        assert!(!post_daily_log(&twitter, &mut state, text, "2023-01-18").await?);
+        mock.assert_hits(1);
+
+        // the next day gets its own post
+        assert!(post_daily_log(&twitter, &mut state, text, "2023-01-19").await?);
+        mock.assert_hits(2);
+
+        Ok(())
+    }
+
+    fn rate_limited_wakatime(mock_server: &MockServer) -> Result<WakaTime, Box<dyn Error>> {
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(429);
+        });
+
+        let mut wakatime = WakaTime::new("api_key")?.with_retries(0, Duration::ZERO);
+        wakatime.set_base_url(mock_server.base_url().parse()?);
+
+        Ok(wakatime)
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_with_warm_cache() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let wakatime = rate_limited_wakatime(&mock_server)?;
+
+        let mut state = State::default();
+        state.record(HistoryEntry {
+            timestamp: 1674000000,
+            hours: 22.5,
+            limit: 40.0,
+        });
+
+        let error = wakatime.get_time_last_n_days(2).await.unwrap_err();
+
+        // later the same Wednesday
+        let now = datetime!(2023-01-18 12:00 UTC);
+        assert_eq!(
+            fall_back_to_cache(error.into(), &state, now, UtcOffset::UTC, Weekday::Monday)?,
+            22.5
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limited_with_stale_cache() {
+        let rate_limited = || Box::new(BurnoutError::RateLimited) as Box<dyn Error>;
+
+        // recorded late on Sunday 22 January
+        let mut state = State::default();
+        state.record(HistoryEntry {
+            timestamp: datetime!(2023-01-22 23:00 UTC).unix_timestamp(),
+            hours: 38.0,
+            limit: 40.0,
+        });
+
+        // only an hour later, but a new week
+        let monday = datetime!(2023-01-23 00:00 UTC);
+        let error = fall_back_to_cache(
+            rate_limited(),
+            &state,
+            monday,
+            UtcOffset::UTC,
+            Weekday::Monday,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("no recent hours"), "{error}");
+
+        // the same week with weeks starting on Sunday, but too old by Tuesday
+        let tuesday = datetime!(2023-01-24 00:00 UTC);
+        assert_eq!(
+            fall_back_to_cache(
+                rate_limited(),
+                &state,
+                monday,
+                UtcOffset::UTC,
+                Weekday::Sunday
+            )
+            .ok(),
+            Some(38.0)
+        );
+        assert!(fall_back_to_cache(
+            rate_limited(),
+            &state,
+            tuesday,
+            UtcOffset::UTC,
+            Weekday::Sunday
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_with_cold_cache() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let wakatime = rate_limited_wakatime(&mock_server)?;
+
+        let error = wakatime.get_time_last_n_days(2).await.unwrap_err();
+
+        assert!(fall_back_to_cache(
+            error.into(),
+            &State::default(),
+            OffsetDateTime::now_utc(),
+            UtcOffset::UTC,
+            Weekday::Monday
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fall_back_only_when_rate_limited() {
+        let mut state = State::default();
+        state.record(HistoryEntry {
+            timestamp: 1674000000,
+            hours: 22.5,
+            limit: 40.0,
+        });
+
+        let now = datetime!(2023-01-18 12:00 UTC);
+        let error = fall_back_to_cache(
+            "connection refused".into(),
+            &state,
+            now,
+            UtcOffset::UTC,
+            Weekday::Monday,
+        )
+        .unwrap_err();
+
+        assert_eq!(error.to_string(), "connection refused");
+    }
+
+    fn mock_twitter(mock_server: &MockServer) -> Result<Twitter, Box<dyn Error>> {
+        let mut twitter = Twitter::new(Credentials {
+            consumer_key: "consumer_key".to_string(),
+            consumer_secret: "consumer_secret".to_string(),
+            access_token: "access_token".to_string(),
+            access_token_secret: "access_token_secret".to_string(),
+        })?;
+        twitter.set_base_url(mock_server.base_url().parse()?);
+
+        Ok(twitter)
+    }
+
+    #[tokio::test]
+    async fn test_notify_upward_crossing() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let text = "You just entered 🟧 heavy territory";
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/statuses/update.json")
+                .x_www_form_urlencoded_tuple("status", text);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "id_str": "1", "text": text }).to_string());
+        });
+
+        let twitter = mock_twitter(&mock_server)?;
+
+        assert!(notify_crossing(&twitter, Some(Band::Busy), Band::Heavy, NotifyOn::Up).await?);
+        mock.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_notify_downward_crossing_silent_by_default() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/statuses/update.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "id_str": "1", "text": "" }).to_string());
+        });
+
+        let twitter = mock_twitter(&mock_server)?;
+
+        assert!(!notify_crossing(&twitter, Some(Band::Heavy), Band::Busy, NotifyOn::Up).await?);
+        mock.assert_hits(0);
+
+        assert!(notify_crossing(&twitter, Some(Band::Heavy), Band::Busy, NotifyOn::Both).await?);
+        mock.assert_hits(1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_notify_no_crossing_silent() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/statuses/update.json");
+            then.status(200);
+        });
+
+        let twitter = mock_twitter(&mock_server)?;
+
+        assert!(!notify_crossing(&twitter, Some(Band::Busy), Band::Busy, NotifyOn::Both).await?);
+        // nothing to compare against on the first run
+        assert!(!notify_crossing(&twitter, None, Band::Heavy, NotifyOn::Both).await?);
+        mock.assert_hits(0);
+
+        Ok(())
+    }
+
+    fn settings(state_path: PathBuf) -> AppSettings {
+        let mut settings = AppSettings::with_credentials(
+            "api_key".to_string(),
+            Credentials {
+                consumer_key: "consumer_key".to_string(),
+                consumer_secret: "consumer_secret".to_string(),
+                access_token: "access_token".to_string(),
+                access_token_secret: "access_token_secret".to_string(),
+            },
+        )
+        .unwrap();
+        settings.state_path = state_path;
+
+        settings
+    }
+
+    /// Settings for a test called `name`, with WakaTime and Twitter both
+    /// pointed at `mock_server`.
+    fn mock_settings(mock_server: &MockServer, name: &str) -> AppSettings {
+        let mut settings = settings(temp_state_path(name));
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+
+        settings
+    }
+
+    /// A mock server with `hours` in WakaTime this week, like `"22.00"`, and
+    /// the [`mock_settings`] for it.
+    fn mock_app(name: &str, hours: &str) -> (MockServer, AppSettings) {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": hours } }).to_string());
+        });
+        let settings = mock_settings(&mock_server, name);
+
+        (mock_server, settings)
+    }
+
+    fn temp_state_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "burnout-meter-{name}-{}.json",
+            time::OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ))
+    }
+
+    #[test]
+    fn test_state_survives_restart() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("restart");
+
+        let state = State {
+            last_band: Some(Band::Heavy),
+            run_band: Some(Band::Heavy),
+            celebrated_week: Some("2023-W03".to_string()),
+            last_post_day: Some("2023-01-18".to_string()),
+            ..Default::default()
+        };
+        state.save(&path)?;
+
+        let app = App::new(settings(path.clone()));
+        std::fs::remove_file(&path)?;
+        let app = app?;
+
+        assert_eq!(app.state, state);
+        // the same band isn't posted again after the restart
+        assert!(!UpdateOn::Band.should_update(app.state.last_band, Band::Heavy));
+        assert!(!should_celebrate(
+            Some(30.0),
+            32.0,
+            40.0,
+            app.state.celebrated_week.as_deref(),
+            "2023-W03"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit_survives_shutdown() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("circuit");
+        let now = time::OffsetDateTime::now_utc();
+
+        let mut app = App::new(settings(path.clone()))?;
+        for _ in 0..super::CIRCUIT_FAILURE_THRESHOLD {
+            app.circuits.backend("twitter").record_failure(now);
+        }
+        // what the daemon does on shutdown
+        app.save_state()?;
+
+        let restarted = App::new(settings(path.clone()));
+        std::fs::remove_file(&path)?;
+
+        assert!(!restarted?.circuits.backend("twitter").is_allowed(now));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_location_fn_is_posted() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "cumulative_total": { "decimal": "22.50" },
+                        "data": [{ "grand_total": { "total_seconds": 81000.0 } }]
+                    })
+                    .to_string(),
+                );
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "22.5h, busy, 1 active day");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22.5h, busy, 1 active day" }).to_string());
+        });
+
+        let path = temp_state_path("location-fn");
+        let mut settings = settings(path.clone());
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+
+        let mut app = App::new(settings)?;
+        app.set_location_fn(Box::new(|report| {
+            format!(
+                "{}h, {}, {} active day",
+                report.hours, report.band, report.active_days
+            )
+        }));
+
+        let result = app.run().await;
+        std::fs::remove_file(&path)?;
+        result?;
+
+        location.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_in_report() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("X-RateLimit-Limit", "100")
+                .header("X-RateLimit-Remaining", "5")
+                .body(json!({ "cumulative_total": { "decimal": "22.50" } }).to_string());
+        });
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .header("x-rate-limit-limit", "900")
+                .header("x-rate-limit-remaining", "899")
+                .header("x-rate-limit-reset", "1674000000")
+                .body(json!({ "location": "🟨🟨🟨⬜️⬜️ 23/40 hours" }).to_string());
+        });
+
+        let path = temp_state_path("rate-limits");
+        let mut settings = settings(path.clone());
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+
+        let result = App::new(settings)?.run().await;
+        std::fs::remove_file(&path)?;
+        let report = result?;
+
+        let wakatime = report.wakatime_rate_limit.unwrap();
+        assert_eq!((wakatime.limit, wakatime.remaining), (Some(100), 5));
+        assert!(ratelimit::warning("WakaTime", &wakatime).is_some());
+
+        let twitter = report.twitter_rate_limit.unwrap();
+        assert_eq!((twitter.remaining, twitter.reset), (899, Some(1674000000)));
+        assert!(ratelimit::warning("Twitter", &twitter).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_new_week_forces_update() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("new-week-forces-update", "22.00");
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "🟨🟨🟨🟨⬜️⬜️⬜️⬜️");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "🟨🟨🟨🟨⬜️⬜️⬜️⬜️" }).to_string());
+        });
+
+        settings.location_template = "{meter}".to_string();
+        settings.burnout_limit = 40.0;
+        settings.meter_length = 8;
+        settings.update_on = UpdateOn::Band;
+
+        // still busy with the same meter as last week
+        let now = OffsetDateTime::now_utc();
+        let mut state = State {
+            last_band: Some(Band::Busy),
+            last_update_week: Some(week_id(now - time::Duration::weeks(1), 0)),
+            ..State::default()
+        };
+        state.save_last_posted("twitter:location", "🟨🟨🟨🟨⬜️⬜️⬜️⬜️");
+
+        let mut store = InMemoryStore::new();
+        state.save_to(&mut store)?;
+
+        let mut app = App::with_store(settings, Box::new(store))?;
+        app.run().await?;
+        // the rest of the week stays in the band, so nothing more is posted
+        app.run().await?;
+
+        location.assert_hits(1);
+        assert_eq!(app.state.last_update_week, Some(week_id(now, 0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_new_week() {
+        let week = week_id(datetime!(2023-01-23 00:30 UTC), 0);
+
+        assert!(is_new_week(Some("2023-W03"), &week));
+        assert!(!is_new_week(Some(&week), &week));
+        assert!(!is_new_week(None, &week));
+    }
+
+    #[test]
+    fn test_work_days() {
+        assert_eq!(work_days(22.0, 8.0), "2.75");
+        assert_eq!(work_days(16.0, 8.0), "2");
+        assert_eq!(work_days(10.0, 7.5), "1.33");
+        assert_eq!(work_days(0.0, 8.0), "0");
+    }
+
+    #[test]
+    fn test_days_omitted_from_default_template() {
+        let location = template::render(
+            template::DEFAULT_TEMPLATE,
+            &[
+                ("meter", "🟨🟨⬜️⬜️".to_string()),
+                ("hours", "22".to_string()),
+                ("max", "40".to_string()),
+                ("hours_label", "hours".to_string()),
+                ("days", work_days(22.0, 8.0)),
+            ],
+        );
+
+        assert_eq!(location, "🟨🟨⬜️⬜️ 22/40 hours");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_text_after_run() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("metrics", "30.00");
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "30/40" }).to_string());
+        });
+
+        settings.location_template = "{hours}/{max}".to_string();
+        settings.burnout_limit = 40.0;
+        settings.meter_length = 4;
+
+        let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+        app.run().await?;
+
+        let text = app.metrics_text();
+        for line in [
+            "burnout_hours 30",
+            "burnout_limit 40",
+            "burnout_percentage 0.75",
+            "burnout_meter_filled 3",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line} not in {text}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_days_in_location() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "22.00" } }).to_string());
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "22h (2.75 days)");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h (2.75 days)" }).to_string());
+        });
+
+        let path = temp_state_path("days");
+        let mut settings = settings(path.clone());
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+        settings.location_template = "{hours}h ({days} days)".to_string();
+        settings.hours_per_day = 8.0;
+
+        let result = App::new(settings)?.run().await;
+        std::fs::remove_file(&path)?;
+        result?;
+
+        location.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_percent_and_remaining_in_location() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("percent", "22.00");
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "Coding: 55%, 18h left");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "Coding: 55%, 18h left" }).to_string());
+        });
+
+        settings.location_template = "Coding: {percent}%, {remaining}h left".to_string();
+        settings.burnout_limit = 40.0;
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        location.assert();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_placeholder_fails_at_startup() {
+        let mut settings = settings(temp_state_path("unknown-placeholder"));
+        settings.location_template = "{meter} {precent}%".to_string();
+
+        let error = App::with_store(settings, Box::new(InMemoryStore::new()))
+            .err()
+            .and_then(|e| e.downcast::<BurnoutError>().ok());
+
+        assert!(matches!(
+            error.as_deref(),
+            Some(BurnoutError::UnknownPlaceholder(name)) if name == "precent"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_with_in_memory_store() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "22.50" } }).to_string());
+        });
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "🟨🟨🟨⬜️⬜️ 23/40 hours" }).to_string());
+        });
+
+        // the state path is never touched
+        let path = temp_state_path("in-memory");
+        let mut settings = settings(path.clone());
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+
+        let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+        app.run().await?;
+
+        assert!(!path.exists());
+
+        let state = State::load_from(app.store.as_ref())?;
+        assert_eq!(state.history.len(), 1);
+        assert_eq!(state.history[0].hours, 22.5);
+        assert!(state.last_band.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_location_not_posted_again() -> Result<(), Box<dyn Error>> {
+        for (force_update, expected_hits) in [(false, 1), (true, 2)] {
+            let mock_server = MockServer::start();
+            mock_server.mock(|when, then| {
+                when.method(GET).path("/api/v1/users/current/summaries");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "cumulative_total": { "decimal": "22.00" } }).to_string());
+            });
+            let location = mock_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/1.1/account/update_profile.json")
+                    .x_www_form_urlencoded_tuple("location", "22/40");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "location": "22/40" }).to_string());
+            });
+
+            let mut settings = mock_settings(&mock_server, "unchanged");
+            settings.location_template = "{hours}/{max}".to_string();
+            settings.burnout_limit = 40.0;
+            settings.force_update = force_update;
+
+            let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+            app.run().await?;
+            app.run().await?;
+
+            location.assert_hits(expected_hits);
+            assert_eq!(
+                app.state.load_last_posted("twitter:location"),
+                Some("22/40")
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_location_posted_on_new_week() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("unchanged-new-week", "22.00");
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "22/40");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22/40" }).to_string());
+        });
+
+        settings.location_template = "{hours}/{max}".to_string();
+        settings.burnout_limit = 40.0;
+
+        // the same location was posted last week
+        let now = OffsetDateTime::now_utc();
+        let mut state = State {
+            last_update_week: Some(week_id(now - time::Duration::weeks(1), 0)),
+            ..State::default()
+        };
+        state.save_last_posted("twitter:location", "22/40");
+
+        let mut store = InMemoryStore::new();
+        state.save_to(&mut store)?;
+
+        let mut app = App::with_store(settings, Box::new(store))?;
+        app.run().await?;
+        app.run().await?;
+
+        // posted once to start the week, then skipped as unchanged
+        location.assert_hits(1);
+        assert_eq!(app.state.last_update_week, Some(week_id(now, 0)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_twitter_and_mastodon() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("twitter-and-mastodon", "22.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "22h");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+        let mastodon_server = MockServer::start();
+        mastodon_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/accounts/verify_credentials");
+            then.status(200).body(json!({ "fields": [] }).to_string());
+        });
+        let mastodon = mastodon_server.mock(|when, then| {
+            when.method("PATCH")
+                .path("/api/v1/accounts/update_credentials")
+                .header("authorization", "Bearer token")
+                .x_www_form_urlencoded_tuple("fields_attributes[0][name]", "Burnout")
+                .x_www_form_urlencoded_tuple("fields_attributes[0][value]", "22h");
+            then.status(200)
+                .body(json!({ "fields": [{ "name": "Burnout", "value": "22h" }] }).to_string());
+        });
+
+        settings.location_template = "{hours}h".to_string();
+        settings.backend = Backend::TwitterAndMastodon;
+        settings.mastodon_instances = vec![(mastodon_server.base_url(), "token".to_string())];
+        settings.mastodon_field = "Burnout".to_string();
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        twitter.assert();
+        mastodon.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discord_failure_does_not_fail_twitter() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("discord", "22.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "22h");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+        let discord_server = MockServer::start();
+        let discord = discord_server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/webhooks/123/token")
+                .json_body(json!({ "content": "22h" }));
+            then.status(500);
+        });
+
+        settings.location_template = "{hours}h".to_string();
+        settings.discord_webhook_url = Some(discord_server.url("/api/webhooks/123/token"));
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        discord.assert();
+        twitter.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discord_circuit_opens() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("discord-circuit", "22.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+        let discord_server = MockServer::start();
+        let discord = discord_server.mock(|when, then| {
+            when.method(POST).path("/api/webhooks/123/token");
+            then.status(500);
+        });
+
+        settings.location_template = "{hours}h".to_string();
+        settings.discord_webhook_url = Some(discord_server.url("/api/webhooks/123/token"));
+        settings.force_update = true;
+
+        let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+        for _ in 0..=super::CIRCUIT_FAILURE_THRESHOLD {
+            app.run().await?;
+        }
+
+        // Discord is skipped once its circuit opens, Twitter carries on
+        discord.assert_hits(super::CIRCUIT_FAILURE_THRESHOLD as usize);
+        twitter.assert_hits(super::CIRCUIT_FAILURE_THRESHOLD as usize + 1);
+        let state = State::load_from(app.store.as_ref())?;
+        assert!(state.circuits["discord"].opened_at.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_telegram() -> Result<(), Box<dyn Error>> {
+        // a bot needs a chat to send to
+        let mut token_only = settings(temp_state_path("telegram-token"));
+        token_only.telegram_bot_token = Some("123456:ABC-token".to_string());
+        assert!(App::with_store(token_only, Box::new(InMemoryStore::new())).is_err());
+
+        let (mock_server, mut settings) = mock_app("telegram", "22.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+        let telegram = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/bot123456:ABC-token/sendMessage")
+                .json_body(json!({ "chat_id": "987654", "text": "22h" }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "ok": true }).to_string());
+        });
+
+        settings.location_template = "{hours}h".to_string();
+        settings.telegram_bot_token = Some("123456:ABC-token".to_string());
+        settings.telegram_chat_id = Some("987654".to_string());
+        settings.telegram_base_url = Some(mock_server.base_url());
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        telegram.assert();
+        twitter.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bluesky() -> Result<(), Box<dyn Error>> {
+        // an app password is no good without the handle it's for
+        let mut password_only = settings(temp_state_path("bluesky-password"));
+        password_only.bluesky_app_password = Some("app-password".to_string());
+        assert!(App::with_store(password_only, Box::new(InMemoryStore::new())).is_err());
+
+        let (mock_server, mut settings) = mock_app("bluesky", "22.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "22h");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+        mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/xrpc/com.atproto.server.createSession");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "accessJwt": "access-jwt", "did": "did:plc:alice" }).to_string());
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/xrpc/com.atproto.repo.getRecord");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "cid": "bafyreiold",
+                        "value": { "$type": "app.bsky.actor.profile", "displayName": "Alice" },
+                    })
+                    .to_string(),
+                );
+        });
+        let bluesky = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/xrpc/com.atproto.repo.putRecord")
+                .json_body_partial(
+                    json!({
+                        "record": {
+                            "$type": "app.bsky.actor.profile",
+                            "displayName": "Alice",
+                            "description": "22h",
+                        }
+                    })
+                    .to_string(),
+                );
+            then.status(200)
+                .body(json!({ "cid": "bafyreinew" }).to_string());
+        });
+
+        settings.location_template = "{hours}h".to_string();
+        settings.bluesky_handle = Some("alice.bsky.social".to_string());
+        settings.bluesky_app_password = Some("app-password".to_string());
+        settings.bluesky_base_url = Some(mock_server.base_url());
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        bluesky.assert();
+        twitter.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slack_webhook() -> Result<(), Box<dyn Error>> {
+        // a bot needs a channel to post in
+        let mut bot = settings(temp_state_path("slack-bot"));
+        bot.slack_bot_token = Some("xoxb-token".to_string());
+        assert!(App::with_store(bot, Box::new(InMemoryStore::new())).is_err());
+
+        let (mock_server, mut settings) = mock_app("slack", "22.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+        let slack = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/services/T0/B0/secret")
+                .json_body(json!({ "text": "22h" }));
+            then.status(200).body("ok");
+        });
+
+        settings.location_template = "{hours}h".to_string();
+        settings.slack_webhook_url = Some(mock_server.url("/services/T0/B0/secret"));
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        slack.assert();
+        twitter.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slack_status() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("slack-status", "22.00");
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "🟨🟨⬜️⬜️ 22h" }).to_string());
+        });
+
+        settings.location_template = "{meter} {hours}h".to_string();
+        settings.burnout_limit = 40.0;
+        settings.meter_length = 4;
+        settings.slack_user_token = Some("xoxp-token".to_string());
+
+        let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+        app.slack_status
+            .as_mut()
+            .unwrap()
+            .set_base_url(mock_server.url("/api/").parse()?);
+
+        let status = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/users.profile.set")
+                .header("authorization", "Bearer xoxp-token")
+                .json_body(json!({
+                    "profile": {
+                        "status_text": "🟨🟨⬜️⬜️ 22h",
+                        "status_emoji": ":yellow_square:",
+                        "status_expiration": 0,
+                    }
+                }));
+            then.status(200).body(json!({ "ok": true }).to_string());
+        });
+
+        app.run().await?;
+
+        status.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_github_status_failure_does_not_fail_twitter() -> Result<(), Box<dyn Error>> {
+        // the token alone is for blending, so the status has to be asked for
+        for (github_status, expected_hits) in [(false, 0), (true, 1)] {
+            let mock_server = MockServer::start();
+            mock_server.mock(|when, then| {
+                when.method(GET).path("/api/v1/users/current/summaries");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "cumulative_total": { "decimal": "22.00" } }).to_string());
+            });
+            let twitter = mock_server.mock(|when, then| {
+                when.method(POST).path("/1.1/account/update_profile.json");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "location": "🟨🟨⬜️⬜️ 22h" }).to_string());
+            });
+            let status = mock_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/graphql")
+                    .header("authorization", "Bearer ghp-token")
+                    .json_body_partial(
+                        json!({
+                            "variables": {
+                                "input": { "message": "🟨🟨⬜️⬜️ 22h", "emoji": ":yellow_circle:" }
+                            }
+                        })
+                        .to_string(),
+                    );
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "errors": [{ "message": "Bad credentials" }] }).to_string());
+            });
+
+            let mut settings = mock_settings(&mock_server, "github-status");
+            settings.github_base_url = Some(mock_server.base_url());
+            settings.location_template = "{meter} {hours}h".to_string();
+            settings.burnout_limit = 40.0;
+            settings.meter_length = 4;
+            settings.github_token = Some("ghp-token".to_string());
+            settings.github_status = github_status;
+
+            App::with_store(settings, Box::new(InMemoryStore::new()))?
+                .run()
+                .await?;
+
+            status.assert_hits(expected_hits);
+            twitter.assert();
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_force_hours() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let summaries = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "38.00" } }).to_string());
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "🟨🟨⬜️⬜️ 20/40");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "🟨🟨⬜️⬜️ 20/40" }).to_string());
+        });
+
+        let cache_path = temp_state_path("force-hours-cache");
+        let mut settings = mock_settings(&mock_server, "force-hours");
+        settings.location_template = "{meter} {hours}/{max}".to_string();
+        settings.burnout_limit = 40.0;
+        settings.meter_length = 4;
+        settings.wakatime_cache_path = Some(cache_path.clone());
+        settings.force_hours = Some(20.0);
+
+        let report = App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        summaries.assert_hits(0);
+        location.assert();
+        assert_eq!(report.hours, 20.0);
+        // the fake hours aren't cached as if they were real
+        assert!(!cache_path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clamp_display() -> Result<(), Box<dyn Error>> {
+        for (clamp_display, expected) in [(true, "160/160"), (false, "180/160")] {
+            let mock_server = MockServer::start();
+            mock_server.mock(|when, then| {
+                when.method(GET).path("/api/v1/users/current/summaries");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "cumulative_total": { "decimal": "180.00" } }).to_string());
+            });
+            let location = mock_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/1.1/account/update_profile.json")
+                    .x_www_form_urlencoded_tuple("location", expected);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "location": expected }).to_string());
+            });
+
+            let mut settings = mock_settings(&mock_server, "clamp-display");
+            settings.location_template = "{hours}/{max}".to_string();
+            settings.burnout_limit = 160.0;
+            settings.sanity_max_hours = 200.0;
+            settings.clamp_display = clamp_display;
+
+            let report = App::with_store(settings, Box::new(InMemoryStore::new()))?
+                .run()
+                .await?;
+
+            location.assert();
+            // either way the meter shows the overflow
+            assert_eq!(report.band, Band::Overflow);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_timings() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        // a slow WakaTime
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .delay(Duration::from_millis(50))
+                .body(json!({ "cumulative_total": { "decimal": "22.00" } }).to_string());
+        });
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "22h" }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "timings");
+        settings.run_budget_ms = Some(10);
+
+        let budget = settings.run_budget().unwrap();
+        let timings = App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?
+            .timings;
+
+        assert!(timings.fetch >= Duration::from_millis(50));
+        assert!(timings.post > Duration::ZERO);
+        assert!(timings.total >= timings.fetch + timings.render + timings.post);
+        assert!(timings.budget_warning(budget).is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_errors_instead_of_panics() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": null, "data": [] }).to_string());
+        });
+        // answers without the location, so it wasn't set
+        let location = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": null }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "errors-not-panics");
+        settings.treat_empty_as_zero = false;
+
+        let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+
+        let error = app.run().await.err().unwrap();
+        location.assert();
+        assert!(matches!(
+            error.downcast_ref::<BurnoutError>(),
+            Some(BurnoutError::ProfileNotUpdated(field)) if field == "location"
+        ));
+
+        // with nothing to post instead, no hours is an error of its own
+        let error = app.print_meter().await.err().unwrap();
+        assert!(matches!(
+            error.downcast_ref::<BurnoutError>(),
+            Some(BurnoutError::WakaTimeEmpty)
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_as_zero() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        // a new account with nothing tracked yet
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": null, "data": [] }).to_string());
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️" }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "empty-as-zero");
+        settings.location_template = "{meter}".to_string();
+        settings.meter_length = 8;
+        settings.rate_limit_fallback = true;
+
+        // last week's total is no stand-in for a week with nothing yet
+        let mut store = InMemoryStore::new();
+        let mut state = State::default();
+        state.record(HistoryEntry {
+            timestamp: (OffsetDateTime::now_utc() - time::Duration::weeks(1)).unix_timestamp(),
+            hours: 30.0,
+            limit: 40.0,
+        });
+        state.save_to(&mut store)?;
+
+        let mut app = App::with_store(settings, Box::new(store))?;
+        let report = app.run().await?;
+
+        location.assert();
+        assert_eq!(report.hours, 0f64);
+        assert_eq!(report.band, Band::Fresh);
+        assert_eq!(report.meter, "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_no_data_without_cache() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        // no cumulative total at all
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "data": [] }).to_string());
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "❔❔❔❔❔❔❔❔");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "❔❔❔❔❔❔❔❔" }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "no-data");
+        settings.meter_length = 8;
+        settings.rate_limit_fallback = true;
+        settings.treat_empty_as_zero = false;
+
+        let mut store = InMemoryStore::new();
+        State {
+            last_band: Some(Band::Busy),
+            ..State::default()
+        }
+        .save_to(&mut store)?;
+
+        let mut app = App::with_store(settings, Box::new(store))?;
+        let report = app.run().await?;
+
+        location.assert();
+        assert_eq!(report.meter, "❔❔❔❔❔❔❔❔");
+        assert_ne!(report.meter, "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
+        // nothing was recorded, and the next run with hours will post
+        let state = State::load_from(app.store.as_ref())?;
+        assert!(state.history.is_empty());
+        assert_eq!(state.last_band, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_hours() {
+        // two steady weeks of 2 hours a day, with one 14 hour spike yesterday
+        let mut daily = vec![2.0; 14];
+        daily[12] = 14.0;
+
+        let raw: f64 = daily[7..].iter().sum();
+        assert_eq!(raw, 26.0);
+
+        // over a week the rolling figure is the raw sum
+        assert_eq!(rolling_hours(&daily, 7), raw);
+        // over two weeks the spike is smoothed out
+        assert_eq!(rolling_hours(&daily, 14), 20.0);
+        assert!(rolling_hours(&daily, 14) < raw);
+
+        // a short history averages what there is
+        assert_eq!(rolling_hours(&[3.0, 5.0], 7), 28.0);
+        assert_eq!(rolling_hours(&[], 7), 0.0);
+    }
+
+    #[test]
+    fn test_hours_window() {
+        assert_eq!("rolling".parse(), Ok(HoursWindow::Rolling));
+        assert_eq!("Week".parse(), Ok(HoursWindow::Week));
+        assert!("monthly".parse::<HoursWindow>().is_err());
+    }
+
+    #[test]
+    fn test_settings_from_file() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("config").with_extension("toml");
+        fs::write(
+            &path,
+            r#"
+            [wakatime]
+            api_key = "file_waka"
+
+            [twitter]
+            consumer_key = "file_consumer_key"
+            consumer_secret = "file_consumer_secret"
+            access_token = "file_access_token"
+            access_token_secret = "file_access_token_secret"
+
+            [meter]
+            limit = 30
+            length = 6
+            "#,
+        )?;
+
+        let settings = AppSettings::from_file(&path);
+
+        // the env wins over the file
+        std::env::set_var("TWITTER_ACCESS_TOKEN_SECRET", "env_access_token_secret");
+        let overridden = AppSettings::from_file(&path);
+        std::env::remove_var("TWITTER_ACCESS_TOKEN_SECRET");
+        fs::remove_file(&path)?;
+
+        let settings = settings?;
+        assert_eq!(settings.wakatime_api_key(), "file_waka");
+        assert_eq!(
+            settings.twitter_credentials.consumer_key,
+            "file_consumer_key"
+        );
+        assert_eq!(
+            settings.twitter_credentials.access_token_secret,
+            "file_access_token_secret"
+        );
+        assert_eq!(settings.burnout_limit(), 30.0);
+        assert_eq!(settings.meter_length(), 6);
+
+        let overridden = overridden?;
+        assert_eq!(
+            overridden.twitter_credentials.access_token_secret,
+            "env_access_token_secret"
+        );
+        assert_eq!(
+            overridden.twitter_credentials.access_token,
+            "file_access_token"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_from_file_missing_credential() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("partial-config").with_extension("toml");
+        fs::write(&path, "[wakatime]\napi_key = \"file_waka\"\n")?;
+
+        let result = AppSettings::from_file(&path);
+        fs::remove_file(&path)?;
+
+        let error = result.err().unwrap();
+        assert!(
+            error
+                .to_string()
+                .starts_with("TWITTER_CONSUMER_KEY not set and not in"),
+            "{error}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_settings_from_file_invalid_thresholds() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("invalid-thresholds-config").with_extension("toml");
+        fs::write(
+            &path,
+            r#"
+            [wakatime]
+            api_key = "file_waka"
+
+            [twitter]
+            consumer_key = "file_consumer_key"
+            consumer_secret = "file_consumer_secret"
+            access_token = "file_access_token"
+            access_token_secret = "file_access_token_secret"
+
+            [meter]
+            thresholds = "20,38,30"
+            "#,
+        )?;
+
+        let result = AppSettings::from_file(&path);
+        fs::remove_file(&path)?;
+
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "meter.thresholds: THRESHOLDS must be ascending: 20,38,30"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_credential() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("load-partial-config").with_extension("toml");
+        fs::write(&path, "[wakatime]\napi_key = \"file_waka\"\n")?;
+
+        // an error to report rather than a panic
+        std::env::set_var("BURNOUT_CONFIG", &path);
+        let result = AppSettings::load(&Args::default());
+        std::env::remove_var("BURNOUT_CONFIG");
+        fs::remove_file(&path)?;
+
+        let error = result.err().unwrap();
+        assert!(error.to_string().contains("not set and not in"), "{error}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_with_args() -> Result<(), Box<dyn Error>> {
+        let path = temp_state_path("load-args-config").with_extension("toml");
+        fs::write(
+            &path,
+            r#"
+            [wakatime]
+            api_key = "file_waka"
+
+            [twitter]
+            consumer_key = "file_consumer_key"
+            consumer_secret = "file_consumer_secret"
+            access_token = "file_access_token"
+            access_token_secret = "file_access_token_secret"
+            "#,
+        )?;
+
+        let args = Args {
+            config: Some(path.clone()),
+            format: Some(OutputFormat::Json),
+            force: true,
+            dump_wakatime: true,
+            ..Args::default()
+        };
+        let result = AppSettings::load(&args);
+        fs::remove_file(&path)?;
+
+        let settings = result?;
+        assert_eq!(settings.wakatime_api_key(), "file_waka");
+        assert_eq!(settings.output_format(), OutputFormat::Json);
+        assert!(settings.force_update());
+        assert!(matches!(settings.dump_target(), Some(DumpTarget::Stderr)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookback_mode() {
+        assert_eq!("week".parse(), Ok(LookbackMode::CalendarWeek));
+        assert_eq!("Rolling:7".parse(), Ok(LookbackMode::RollingDays(7)));
+        assert!("rolling".parse::<LookbackMode>().is_err());
+        assert!("rolling:a week".parse::<LookbackMode>().is_err());
+
+        // a Wednesday
+        let now = datetime!(2023-01-18 12:00 UTC);
+        assert_eq!(
+            LookbackMode::CalendarWeek.days(now, UtcOffset::UTC, Weekday::Monday),
+            Ok(2)
+        );
+        assert_eq!(
+            LookbackMode::CalendarWeek.days(now, UtcOffset::UTC, Weekday::Sunday),
+            Ok(3)
+        );
+        // late Tuesday here is already Wednesday in UTC
+        assert_eq!(
+            LookbackMode::CalendarWeek.days(
+                datetime!(2023-01-18 02:00 UTC),
+                offset!(-5),
+                Weekday::Monday
+            ),
+            Ok(1)
+        );
+
+        // the same whatever the day of the week
+        assert_eq!(
+            LookbackMode::RollingDays(7).days(now, UtcOffset::UTC, Weekday::Monday),
+            Ok(6)
+        );
+        assert_eq!(
+            LookbackMode::RollingDays(1).days(now, UtcOffset::UTC, Weekday::Monday),
+            Ok(0)
+        );
+        assert!(LookbackMode::RollingDays(0)
+            .days(now, UtcOffset::UTC, Weekday::Monday)
+            .is_err());
+        assert!(LookbackMode::RollingDays(-3)
+            .days(now, UtcOffset::UTC, Weekday::Monday)
+            .is_err());
+    }
+
+    #[test]
+    fn test_lookback_mode_with_rolling_window() {
+        // ROLLING_DAYS already sets the days for the rolling window
+        let mut settings = settings(temp_state_path("lookback-rolling-window"));
+        settings.hours_window = HoursWindow::Rolling;
+        settings.lookback_mode = LookbackMode::RollingDays(7);
+
+        let error = App::with_store(settings, Box::new(InMemoryStore::new()))
+            .err()
+            .unwrap();
+        assert!(
+            error.to_string().contains("HOURS_WINDOW=rolling"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_break_after_red_days() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "45.00" } }).to_string());
+        });
+        let mut on_break = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "taking a break 🌴");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "taking a break 🌴" }).to_string());
+        });
+
+        // red since yesterday, so today is the second day
+        let path = temp_state_path("break");
+        let sentinel = temp_state_path("break-sentinel");
+        let state = State {
+            red_since: Some(
+                (time::OffsetDateTime::now_utc() - time::Duration::days(1)).unix_timestamp(),
+            ),
+            ..Default::default()
+        };
+        state.save(&path)?;
+
+        let mut settings = settings(path.clone());
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+        settings.break_after_days = Some(2);
+        settings.break_sentinel = sentinel.clone();
+        let mut app = App::new(settings)?;
+
+        let first = app.run().await;
+        let second = app.run().await;
+        let sentinel_written = sentinel.exists();
+
+        // clearing the sentinel resumes normal updates
+        let _ = std::fs::remove_file(&sentinel);
+        // the break message went out once, not on the second run
+        on_break.assert_hits(1);
+        on_break.delete();
+        let meter = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "🟥🟥🟥🟥🟥🟥🟥🟥 45/40 hours" }).to_string());
+        });
+        let resumed = app.run().await;
+
+        std::fs::remove_file(&path)?;
+        first?;
+        second?;
+        resumed?;
+
+        assert!(sentinel_written);
+        meter.assert_hits(1);
+        assert!(!app.state.on_break);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_break() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("dry-run-break", "45.00");
+        let twitter = mock_server.mock(|when, then| {
+            when.path_contains("/1.1/");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "" }).to_string());
+        });
+
+        // red since yesterday, so today would be the second day
+        let sentinel = temp_state_path("dry-run-break-sentinel");
+        let mut store = InMemoryStore::new();
+        State {
+            red_since: Some(
+                (time::OffsetDateTime::now_utc() - time::Duration::days(1)).unix_timestamp(),
+            ),
+            ..Default::default()
+        }
+        .save_to(&mut store)?;
+
+        settings.break_after_days = Some(2);
+        settings.break_sentinel = sentinel.clone();
+        settings.dry_run = true;
+
+        let mut app = App::with_store(settings, Box::new(store))?;
+        let result = app.run().await;
+        let sentinel_written = sentinel.exists();
+        let _ = std::fs::remove_file(&sentinel);
+        result?;
+
+        twitter.assert_hits(0);
+        assert!(!sentinel_written);
+        assert!(!State::load_from(app.store.as_ref())?.on_break);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dominant_project() {
+        let projects = |hours: &[(&str, f64)]| -> Vec<(String, f64)> {
+            hours
+                .iter()
+                .map(|(name, hours)| (name.to_string(), *hours))
+                .collect()
+        };
+
+        // clearly dominant, in any order
+        let week = projects(&[("dotfiles", 2.0), ("proj-x", 26.0), ("blog", 2.0)]);
+        assert_eq!(dominant_project(&week, 0.8), Some("proj-x"));
+
+        // right on the share counts
+        let week = projects(&[("proj-x", 8.0), ("blog", 2.0)]);
+        assert_eq!(dominant_project(&week, 0.8), Some("proj-x"));
+
+        // a balanced week has none
+        let week = projects(&[("proj-x", 12.0), ("blog", 10.0), ("dotfiles", 8.0)]);
+        assert_eq!(dominant_project(&week, 0.8), None);
+        // unless the share is low enough
+        assert_eq!(dominant_project(&week, 0.4), Some("proj-x"));
+
+        assert_eq!(dominant_project(&[], 0.8), None);
+        assert_eq!(dominant_project(&projects(&[("idle", 0.0)]), 0.8), None);
+    }
+
+    #[tokio::test]
+    async fn test_dominant_project_in_location() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "cumulative_total": { "decimal": "30.00" },
+                        "data": [{
+                            "grand_total": { "total_seconds": 108000.0 },
+                            "projects": [
+                                { "name": "proj-x", "total_seconds": 97200.0 },
+                                { "name": "blog", "total_seconds": 10800.0 }
+                            ]
+                        }]
+                    })
+                    .to_string(),
+                );
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "proj-x 30/40h");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "proj-x 30/40h" }).to_string());
+        });
+
+        let path = temp_state_path("dominant-project");
+        let mut settings = settings(path.clone());
+        settings.wakatime_base_url = Some(mock_server.base_url());
+        settings.twitter_base_url = Some(mock_server.base_url());
+        settings.location_template = "{dominant_project} {hours}/{max}h".to_string();
+        settings.dominant_share = 0.8;
+
+        let result = App::new(settings)?.run().await;
+        std::fs::remove_file(&path)?;
+        result?;
+
+        location.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_twitter_target_description() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("target-description", "22.00");
+        let description = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("description", "22/40");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": null, "description": "22/40" }).to_string());
+        });
+
+        settings.location_template = "{hours}/{max}".to_string();
+        settings.burnout_limit = 40.0;
+        settings.twitter_target_field = TargetField::Description;
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        description.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_location_too_long() -> Result<(), Box<dyn Error>> {
+        for truncate_location in [false, true] {
+            let mock_server = MockServer::start();
+            mock_server.mock(|when, then| {
+                when.method(GET).path("/api/v1/users/current/summaries");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "cumulative_total": { "decimal": "22.00" } }).to_string());
+            });
+            let location = mock_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/1.1/account/update_profile.json")
+                    .x_www_form_urlencoded_tuple("location", "🟨🟨🟨🟨⬜️⬜️⬜️⬜️ 22/40 hours…");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "location": "🟨🟨🟨🟨⬜️⬜️⬜️⬜️ 22/40 hours…" }).to_string());
+            });
+
+            let mut settings = mock_settings(&mock_server, "location-too-long");
+            settings.location_template = "{meter} {hours}/{max} hours of Rust".to_string();
+            settings.burnout_limit = 40.0;
+            settings.meter_length = 8;
+            settings.truncate_location = truncate_location;
+
+            let result = App::with_store(settings, Box::new(InMemoryStore::new()))?
+                .run()
+                .await;
+
+            match truncate_location {
+                true => {
+                    result?;
+                    location.assert();
+                }
+                false => {
+                    let error = result.err().unwrap();
+                    assert!(matches!(
+                        error.downcast_ref::<BurnoutError>(),
+                        Some(BurnoutError::LocationTooLong {
+                            length: 36,
+                            max: 30
+                        })
+                    ));
+                    location.assert_hits(0);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_does_not_post() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let summaries = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "22.00" } }).to_string());
+        });
+        let twitter = mock_server.mock(|when, then| {
+            when.path_contains("/1.1/");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "" }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "dry-run");
+        settings.dry_run = true;
+
+        let mut app = App::with_store(settings, Box::new(InMemoryStore::new()))?;
+        let report = app.run().await?;
+
+        summaries.assert();
+        twitter.assert_hits(0);
+        assert_eq!(report.hours, 22.0);
+        // and the next real run isn't skipped as already posted
+        let state = State::load_from(app.store.as_ref())?;
+        assert_eq!(state.last_band, None);
+        assert!(state.history.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_languages_in_location() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "cumulative_total": { "decimal": "38.00" },
+                        "data": [{
+                            "grand_total": { "total_seconds": 136800.0 },
+                            "languages": [
+                                { "name": "Rust", "total_seconds": 108000.0 },
+                                { "name": "Python", "total_seconds": 21600.0 },
+                                { "name": "Go", "total_seconds": 7200.0 }
+                            ]
+                        }]
+                    })
+                    .to_string(),
+                );
+        });
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "rs🟧🟧🟧 py🟩");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "rs🟧🟧🟧 py🟩" }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "languages");
+        settings.location_template = "{languages}".to_string();
+        settings.burnout_limit = 40.0;
+        settings.meter_length = 4;
+        settings.top_languages = 2;
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        location.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_show_top_language() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "cumulative_total": { "decimal": "12.00" },
+                        "data": [
+                            {
+                                "grand_total": { "total_seconds": 18000.0 },
+                                "languages": [
+                                    { "name": "Python", "total_seconds": 10800.0 },
+                                    { "name": "Rust", "total_seconds": 7200.0 }
+                                ]
+                            },
+                            {
+                                "grand_total": { "total_seconds": 25200.0 },
+                                "languages": [
+                                    { "name": "Rust", "total_seconds": 21600.0 },
+                                    { "name": "Go", "total_seconds": 3600.0 }
+                                ]
+                            }
+                        ]
+                    })
+                    .to_string(),
+                );
+        });
+        // Python leads the first day, but Rust has the most across both
+        let location = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .x_www_form_urlencoded_tuple("location", "🟨🟨🟨⬜️⬜️ 12h · Rust");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "🟨🟨🟨⬜️⬜️ 12h · Rust" }).to_string());
+        });
+
+        let mut settings = mock_settings(&mock_server, "top-language");
+        settings.location_template = "{meter} {hours}h".to_string();
+        settings.burnout_limit = 20.0;
+        settings.meter_length = 5;
+        settings.show_top_language = true;
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        location.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_covers_failed_fetch() -> Result<(), Box<dyn Error>> {
+        let now = OffsetDateTime::now_utc();
+
+        for (age, posted) in [
+            (time::Duration::hours(1), true),
+            (time::Duration::hours(25), false),
+        ] {
+            let mock_server = MockServer::start();
+            // WakaTime is down
+            mock_server.mock(|when, then| {
+                when.method(GET).path("/api/v1/users/current/summaries");
+                then.status(401);
+            });
+            let location = mock_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/1.1/account/update_profile.json")
+                    .x_www_form_urlencoded_tuple("location", "22/40");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "location": "22/40" }).to_string());
+            });
+
+            let cache_path = temp_state_path("wakatime-cache");
+            cache::save_cache(
+                &cache_path,
+                &CachedHours {
+                    hours: 22.0,
+                    timestamp: (now - age).unix_timestamp(),
+                },
+            )?;
+
+            let mut settings = mock_settings(&mock_server, "disk-cache");
+            settings.location_template = "{hours}/{max}".to_string();
+            settings.burnout_limit = 40.0;
+            settings.wakatime_cache_path = Some(cache_path.clone());
+
+            let result = App::with_store(settings, Box::new(InMemoryStore::new()))?
+                .run()
+                .await;
+            fs::remove_file(&cache_path)?;
+
+            match posted {
+                true => {
+                    location.assert();
+                    assert_eq!(result?.band, Band::Busy);
+                }
+                // too old to trust, so the fetch error comes through
+                false => {
+                    location.assert_hits(0);
+                    assert!(result.is_err());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_saves_disk_cache() -> Result<(), Box<dyn Error>> {
+        let (mock_server, mut settings) = mock_app("save-disk-cache", "12.50");
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "location": "" }).to_string());
+        });
+
+        let cache_path = temp_state_path("wakatime-cache");
+        settings.wakatime_cache_path = Some(cache_path.clone());
+
+        App::with_store(settings, Box::new(InMemoryStore::new()))?
+            .run()
+            .await?;
+
+        let cached = cache::load_cache(&cache_path);
+        fs::remove_file(&cache_path)?;
+
+        assert_eq!(cached?.map(|cached| cached.hours), Some(12.5));
+
+        Ok(())
     }
 }
@@ -0,0 +1,351 @@
+use crate::{
+    github::{Activity, GitHub},
+    meter::Unit,
+    wakatime::WakaTime,
+    workhours::WorkHours,
+};
+use std::{error::Error, iter, str::FromStr};
+
+/// How many hours each piece of GitHub activity counts for in a blended score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlendWeights {
+    pub pull_request: f64,
+    pub review: f64,
+}
+
+impl Default for BlendWeights {
+    fn default() -> Self {
+        Self {
+            pull_request: 1.0,
+            review: 0.5,
+        }
+    }
+}
+
+/// What to do when one of several WakaTime accounts fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccountFailure {
+    /// Fail the whole run.
+    #[default]
+    Abort,
+    /// Log it and add up the other accounts, failing only if they all do.
+    Skip,
+}
+
+impl FromStr for AccountFailure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "abort" => Ok(Self::Abort),
+            "skip" => Ok(Self::Skip),
+            _ => Err(format!("Unknown WAKATIME_ACCOUNT_FAILURE value: {s}")),
+        }
+    }
+}
+
+/// A data source combining WakaTime hours with GitHub activity, since
+/// opening and reviewing pull requests wears you down on top of the time
+/// spent in the editor.
+///
+/// Without GitHub, or when GitHub can't be reached, it's just the hours.
+pub struct BlendedSource<'a> {
+    wakatime: &'a WakaTime,
+    /// More WakaTime accounts whose hours are added to the first's.
+    extra_accounts: &'a [WakaTime],
+    on_account_failure: AccountFailure,
+    github: Option<&'a GitHub>,
+    weights: BlendWeights,
+    max_session_hours: Option<f64>,
+    work_hours: Option<WorkHours>,
+    project: Option<String>,
+}
+
+impl<'a> BlendedSource<'a> {
+    pub fn new(wakatime: &'a WakaTime, github: Option<&'a GitHub>, weights: BlendWeights) -> Self {
+        Self {
+            wakatime,
+            extra_accounts: &[],
+            on_account_failure: AccountFailure::default(),
+            github,
+            weights,
+            max_session_hours: None,
+            work_hours: None,
+            project: None,
+        }
+    }
+
+    /// Add the hours from `accounts` to the first account's, e.g. for
+    /// machines on separate WakaTime accounts, with `on_failure` deciding
+    /// what happens when one of them fails.
+    pub fn set_extra_accounts(
+        &mut self,
+        accounts: &'a [WakaTime],
+        on_failure: AccountFailure,
+    ) -> &mut Self {
+        self.extra_accounts = accounts;
+        self.on_account_failure = on_failure;
+
+        self
+    }
+
+    /// Cap any single WakaTime session at `max_session_hours`, if set.
+    pub fn set_max_session_hours(&mut self, max_session_hours: Option<f64>) -> &mut Self {
+        self.max_session_hours = max_session_hours;
+
+        self
+    }
+
+    /// Only count the hours on `project`, if set, capped or weighed or not.
+    pub fn set_project(&mut self, project: Option<String>) -> &mut Self {
+        self.project = project;
+
+        self
+    }
+
+    /// Count time coded outside `work_hours` for more, if set.
+    pub fn set_work_hours(&mut self, work_hours: Option<WorkHours>) -> &mut Self {
+        self.work_hours = work_hours;
+
+        self
+    }
+
+    /// What the values from this source count, GitHub activity being
+    /// weighted into hours.
+    pub fn unit(&self) -> Unit {
+        Unit::Hours
+    }
+
+    /// Get the blended score for the last n days.
+    ///
+    /// With extra accounts, their hours are added up before the GitHub
+    /// activity is blended in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a WakaTime request fails, unless failed accounts
+    /// are skipped and at least one didn't fail. GitHub failures are printed
+    /// and the score falls back to the hours alone.
+    pub async fn get_time_last_n_days(&self, days: i64) -> Result<Option<f64>, Box<dyn Error>> {
+        let accounts = iter::once(self.wakatime).chain(self.extra_accounts);
+
+        let mut total: Option<f64> = None;
+        let mut succeeded = false;
+        let mut last_error = None;
+
+        for (index, wakatime) in accounts.enumerate() {
+            match self.account_hours(wakatime, days).await {
+                Ok(hours) => {
+                    succeeded = true;
+
+                    if let Some(hours) = hours {
+                        total = Some(total.unwrap_or(0f64) + hours);
+                    }
+                }
+                Err(e) if self.on_account_failure == AccountFailure::Skip => {
+                    log::warn!(
+                        "Failed to get WakaTime account {}, skipping it: {e}",
+                        index + 1
+                    );
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let (false, Some(e)) = (succeeded, last_error) {
+            return Err(e);
+        }
+
+        let hours = match total {
+            Some(hours) => hours,
+            None => return Ok(None),
+        };
+
+        let activity = match self.github {
+            Some(github) => match github.get_activity_last_n_days(days).await {
+                Ok(activity) => Some(activity),
+                Err(e) => {
+                    log::warn!("Failed to get GitHub activity, using hours only: {e}");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok(Some(blend(hours, activity, self.weights)))
+    }
+}
+
+impl BlendedSource<'_> {
+    /// The hours from one WakaTime account, capped or weighed as set, for
+    /// the project if it's set.
+    async fn account_hours(
+        &self,
+        wakatime: &WakaTime,
+        days: i64,
+    ) -> Result<Option<f64>, Box<dyn Error>> {
+        let project = self.project.as_deref();
+
+        let hours = match (self.work_hours, self.max_session_hours) {
+            (Some(work_hours), max_session_hours) => {
+                let sessions = wakatime.get_sessions_last_n_days(days, project).await?;
+
+                Some(work_hours.weigh(&sessions, max_session_hours))
+            }
+            (None, Some(max_session_hours)) => Some(
+                wakatime
+                    .get_capped_time_last_n_days(days, max_session_hours, project)
+                    .await?,
+            ),
+            (None, None) => match project {
+                Some(project) => {
+                    wakatime
+                        .get_time_last_n_days_for_project(days, project)
+                        .await?
+                }
+                None => wakatime.get_time_last_n_days(days).await?,
+            },
+        };
+
+        Ok(hours)
+    }
+}
+
+/// Add the weighted GitHub activity, if any, to the hours.
+fn blend(hours: f64, activity: Option<Activity>, weights: BlendWeights) -> f64 {
+    match activity {
+        Some(activity) => {
+            hours
+                + activity.pull_requests as f64 * weights.pull_request
+                + activity.reviews as f64 * weights.review
+        }
+        None => hours,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blend, AccountFailure, BlendWeights, BlendedSource};
+    use crate::{
+        github::{Activity, GitHub},
+        wakatime::WakaTime,
+    };
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::{error::Error, time::Duration};
+
+    #[test]
+    fn test_blend() {
+        let weights = BlendWeights {
+            pull_request: 2.0,
+            review: 0.5,
+        };
+        let activity = Activity {
+            pull_requests: 3,
+            reviews: 4,
+        };
+
+        // 20 hours + 3 PRs * 2 + 4 reviews * 0.5
+        assert_eq!(blend(20.0, Some(activity), weights), 28.0);
+        assert_eq!(blend(20.0, Some(Activity::default()), weights), 20.0);
+        assert_eq!(blend(20.0, None, weights), 20.0);
+    }
+
+    #[tokio::test]
+    async fn test_blended_source() -> Result<(), Box<dyn Error>> {
+        let wakatime_server = MockServer::start();
+        wakatime_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "20.00" } }).to_string());
+        });
+
+        let github_server = MockServer::start();
+        let github_mock = github_server.mock(|when, then| {
+            when.method(GET).path("/search/issues");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "total_count": 2 }).to_string());
+        });
+
+        let mut wakatime = WakaTime::new("test")?;
+        wakatime.set_base_url(wakatime_server.base_url().parse()?);
+        let mut github = GitHub::new("test", "octocat")?;
+        github.set_base_url(github_server.base_url().parse()?);
+
+        let weights = BlendWeights::default();
+
+        // 2 PRs at 1 hour and 2 reviews at half an hour
+        let blended = BlendedSource::new(&wakatime, Some(&github), weights);
+        assert_eq!(blended.get_time_last_n_days(7).await?, Some(23.0));
+        github_mock.assert_hits(2);
+
+        // without GitHub it's just the hours
+        let hours_only = BlendedSource::new(&wakatime, None, weights);
+        assert_eq!(hours_only.get_time_last_n_days(7).await?, Some(20.0));
+
+        // and the same when GitHub is down
+        let mut unreachable = GitHub::new("test", "octocat")?;
+        unreachable.set_base_url("http://127.0.0.1:1".parse()?);
+        let degraded = BlendedSource::new(&wakatime, Some(&unreachable), weights);
+        assert_eq!(degraded.get_time_last_n_days(7).await?, Some(20.0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extra_accounts() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        for (api_key, decimal) in [("laptop", "20.00"), ("desktop", "12.50")] {
+            mock_server.mock(|when, then| {
+                when.method(GET)
+                    .path("/api/v1/users/current/summaries")
+                    .query_param("api_key", api_key);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(json!({ "cumulative_total": { "decimal": decimal } }).to_string());
+            });
+        }
+        mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/v1/users/current/summaries")
+                .query_param("api_key", "revoked");
+            then.status(401);
+        });
+
+        let account = |api_key: &str| -> Result<WakaTime, Box<dyn Error>> {
+            let mut wakatime = WakaTime::new(api_key)?.with_retries(0, Duration::ZERO);
+            wakatime.set_base_url(mock_server.base_url().parse()?);
+
+            Ok(wakatime)
+        };
+        let laptop = account("laptop")?;
+        let weights = BlendWeights::default();
+
+        let extra = [account("desktop")?];
+        let mut summed = BlendedSource::new(&laptop, None, weights);
+        summed.set_extra_accounts(&extra, AccountFailure::Abort);
+        assert_eq!(summed.get_time_last_n_days(7).await?, Some(32.5));
+
+        // a failed account fails the lot unless it's skipped
+        let extra = [account("desktop")?, account("revoked")?];
+        let mut aborted = BlendedSource::new(&laptop, None, weights);
+        aborted.set_extra_accounts(&extra, AccountFailure::Abort);
+        assert!(aborted.get_time_last_n_days(7).await.is_err());
+
+        let mut skipped = BlendedSource::new(&laptop, None, weights);
+        skipped.set_extra_accounts(&extra, AccountFailure::Skip);
+        assert_eq!(skipped.get_time_last_n_days(7).await?, Some(32.5));
+
+        // but not when there's nothing left to add up
+        let revoked = account("revoked")?;
+        let extra = [account("revoked")?];
+        let mut all_failed = BlendedSource::new(&revoked, None, weights);
+        all_failed.set_extra_accounts(&extra, AccountFailure::Skip);
+        assert!(all_failed.get_time_last_n_days(7).await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use crate::{error::BurnoutError, util::http_client};
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::{from_str, json, Value};
+
+/// The PDS most accounts are on, unless pointed somewhere else.
+pub const DEFAULT_BASE_URL: &str = "https://bsky.social/";
+
+/// The collection the profile record is in, under the key `self`.
+const PROFILE_COLLECTION: &str = "app.bsky.actor.profile";
+
+/// The parts of a new session the update needs.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    access_jwt: String,
+    did: String,
+}
+
+/// A record and the version of it that was read.
+#[derive(Deserialize, Debug)]
+struct Record {
+    cid: Option<String>,
+    value: Value,
+}
+
+/// How XRPC describes a failed call.
+#[derive(Deserialize, Debug)]
+struct XrpcError {
+    error: String,
+}
+
+/// A Bluesky account, signed in with its handle and an app password.
+#[derive(Debug, Clone)]
+pub struct Bluesky {
+    client: Client,
+    base_url: Url,
+    handle: String,
+    app_password: String,
+}
+
+impl Bluesky {
+    pub fn new(handle: &str, app_password: &str) -> Result<Self, BurnoutError> {
+        Self::with_client(handle, app_password, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(
+        handle: &str,
+        app_password: &str,
+        client: Client,
+    ) -> Result<Self, BurnoutError> {
+        Ok(Self {
+            client,
+            base_url: Url::parse(DEFAULT_BASE_URL)
+                .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?,
+            handle: handle.to_string(),
+            app_password: app_password.to_string(),
+        })
+    }
+
+    /// Point requests at a different PDS, e.g. a self-hosted one or a mock in
+    /// tests.
+    pub fn set_base_url(&mut self, base_url: Url) -> &mut Self {
+        self.base_url = base_url;
+
+        self
+    }
+
+    /// Set the profile description to `description`.
+    ///
+    /// A record is replaced whole, so the profile is read first and written
+    /// back with only the description changed, keeping the display name,
+    /// avatar, and the rest. It's written only if nothing else changed it in
+    /// between.
+    ///
+    /// https://docs.bsky.app/docs/api/com-atproto-repo-put-record
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if signing in or any request fails, or the PDS
+    /// answers with an XRPC error.
+    pub async fn update_description(&self, description: &str) -> Result<(), BurnoutError> {
+        let session = self.create_session().await?;
+
+        let (mut record, cid) = match self.get_profile(&session).await? {
+            Some(Record { value, cid }) => (value, cid),
+            // an account that never edited its profile doesn't have one yet
+            None => (json!({ "$type": PROFILE_COLLECTION }), None),
+        };
+        record["description"] = json!(description);
+
+        let mut body = json!({
+            "repo": session.did,
+            "collection": PROFILE_COLLECTION,
+            "rkey": "self",
+            "record": record,
+        });
+        if let Some(cid) = cid {
+            body["swapRecord"] = json!(cid);
+        }
+
+        let response = self
+            .client
+            .post(self.url("com.atproto.repo.putRecord")?)
+            .bearer_auth(&session.access_jwt)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+
+        check(response).await?;
+
+        Ok(())
+    }
+
+    /// Sign in with the app password.
+    ///
+    /// https://docs.bsky.app/docs/api/com-atproto-server-create-session
+    async fn create_session(&self) -> Result<Session, BurnoutError> {
+        let response = self
+            .client
+            .post(self.url("com.atproto.server.createSession")?)
+            .header(CONTENT_TYPE, "application/json")
+            .body(json!({ "identifier": self.handle, "password": self.app_password }).to_string())
+            .send()
+            .await?;
+
+        Ok(from_str(&check(response).await?)?)
+    }
+
+    /// Read the profile record, or `None` if there isn't one.
+    ///
+    /// https://docs.bsky.app/docs/api/com-atproto-repo-get-record
+    async fn get_profile(&self, session: &Session) -> Result<Option<Record>, BurnoutError> {
+        let mut url = self.url("com.atproto.repo.getRecord")?;
+        url.query_pairs_mut()
+            .append_pair("repo", &session.did)
+            .append_pair("collection", PROFILE_COLLECTION)
+            .append_pair("rkey", "self");
+
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(&session.access_jwt)
+            .send()
+            .await?;
+
+        match check(response).await {
+            Ok(body) => Ok(Some(from_str(&body)?)),
+            Err(BurnoutError::Bluesky(error)) if error == "RecordNotFound" => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn url(&self, method: &str) -> Result<Url, BurnoutError> {
+        self.base_url
+            .join(&format!("xrpc/{method}"))
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))
+    }
+}
+
+/// The body of a successful response, or the XRPC error of a failed one.
+async fn check(response: reqwest::Response) -> Result<String, BurnoutError> {
+    let status = response.status();
+
+    // XRPC errors are a 400 or 401 with the error in the body
+    if status == StatusCode::BAD_REQUEST || status == StatusCode::UNAUTHORIZED {
+        let body = response.text().await?;
+
+        return match from_str::<XrpcError>(&body) {
+            Ok(XrpcError { error }) => Err(BurnoutError::Bluesky(error)),
+            Err(e) => Err(e.into()),
+        };
+    }
+
+    Ok(response.error_for_status()?.text().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bluesky;
+    use crate::error::BurnoutError;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+
+    fn bluesky(mock_server: &MockServer) -> Result<Bluesky, Box<dyn Error>> {
+        let mut bluesky = Bluesky::new("alice.bsky.social", "app-password")?;
+        bluesky.set_base_url(mock_server.base_url().parse()?);
+
+        Ok(bluesky)
+    }
+
+    fn mock_session(mock_server: &MockServer) -> httpmock::Mock<'_> {
+        mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/xrpc/com.atproto.server.createSession")
+                .json_body(json!({
+                    "identifier": "alice.bsky.social",
+                    "password": "app-password",
+                }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "accessJwt": "access-jwt",
+                        "refreshJwt": "refresh-jwt",
+                        "handle": "alice.bsky.social",
+                        "did": "did:plc:alice",
+                    })
+                    .to_string(),
+                );
+        })
+    }
+
+    #[tokio::test]
+    async fn test_update_description() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let session = mock_session(&mock_server);
+        let avatar = json!({
+            "$type": "blob",
+            "ref": { "$link": "bafkreiavatar" },
+            "mimeType": "image/jpeg",
+            "size": 1234,
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/xrpc/com.atproto.repo.getRecord")
+                .query_param("repo", "did:plc:alice")
+                .query_param("collection", "app.bsky.actor.profile")
+                .query_param("rkey", "self")
+                .header("authorization", "Bearer access-jwt");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "uri": "at://did:plc:alice/app.bsky.actor.profile/self",
+                        "cid": "bafyreiold",
+                        "value": {
+                            "$type": "app.bsky.actor.profile",
+                            "displayName": "Alice",
+                            "description": "old",
+                            "avatar": avatar,
+                        },
+                    })
+                    .to_string(),
+                );
+        });
+        let put = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/xrpc/com.atproto.repo.putRecord")
+                .header("authorization", "Bearer access-jwt")
+                .json_body(json!({
+                    "repo": "did:plc:alice",
+                    "collection": "app.bsky.actor.profile",
+                    "rkey": "self",
+                    "record": {
+                        "$type": "app.bsky.actor.profile",
+                        "displayName": "Alice",
+                        "description": "🟨🟨⬜️⬜️ 22/40 hrs",
+                        "avatar": avatar,
+                    },
+                    "swapRecord": "bafyreiold",
+                }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "uri": "at://", "cid": "bafyreinew" }).to_string());
+        });
+
+        bluesky(&mock_server)?
+            .update_description("🟨🟨⬜️⬜️ 22/40 hrs")
+            .await?;
+
+        session.assert();
+        put.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_description_without_profile() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_session(&mock_server);
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/xrpc/com.atproto.repo.getRecord");
+            then.status(400)
+                .header("content-type", "application/json")
+                .body(
+                    json!({ "error": "RecordNotFound", "message": "Could not locate record" })
+                        .to_string(),
+                );
+        });
+        let put = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/xrpc/com.atproto.repo.putRecord")
+                .json_body(json!({
+                    "repo": "did:plc:alice",
+                    "collection": "app.bsky.actor.profile",
+                    "rkey": "self",
+                    "record": {
+                        "$type": "app.bsky.actor.profile",
+                        "description": "🟨🟨⬜️⬜️",
+                    },
+                }));
+            then.status(200)
+                .body(json!({ "uri": "at://", "cid": "bafyreinew" }).to_string());
+        });
+
+        bluesky(&mock_server)?
+            .update_description("🟨🟨⬜️⬜️")
+            .await?;
+
+        put.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_session_wrong_password() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/xrpc/com.atproto.server.createSession");
+            then.status(401)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "error": "AuthenticationRequired",
+                        "message": "Invalid identifier or password",
+                    })
+                    .to_string(),
+                );
+        });
+        let put = mock_server.mock(|when, then| {
+            when.method(POST).path("/xrpc/com.atproto.repo.putRecord");
+            then.status(200);
+        });
+
+        let result = bluesky(&mock_server)?.update_description("🟨🟨⬜️⬜️").await;
+
+        assert!(matches!(
+            result,
+            Err(BurnoutError::Bluesky(error)) if error == "AuthenticationRequired"
+        ));
+        put.assert_hits(0);
+
+        Ok(())
+    }
+}
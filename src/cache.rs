@@ -0,0 +1,215 @@
+use crate::error::BurnoutError;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string};
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process,
+};
+use time::{Duration, OffsetDateTime};
+
+/// The name of the lock file held in a shared directory.
+const LOCK_FILE: &str = ".burnout-meter.lock";
+
+/// How old cached hours can be and still stand in for a failed fetch.
+pub const MAX_AGE: Duration = Duration::hours(24);
+
+/// The hours from the last successful fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CachedHours {
+    pub hours: f64,
+    /// When they were fetched, as a unix timestamp in seconds.
+    pub timestamp: i64,
+}
+
+impl CachedHours {
+    /// The hours, if they were fetched less than [`MAX_AGE`] before `now`.
+    pub fn fresh_hours(&self, now: OffsetDateTime) -> Option<f64> {
+        self.fresh_within(now, MAX_AGE)
+    }
+
+    /// The hours, if they were fetched less than `max_age` before `now`.
+    pub fn fresh_within(&self, now: OffsetDateTime, max_age: Duration) -> Option<f64> {
+        let age = now.unix_timestamp() - self.timestamp;
+
+        (age < max_age.whole_seconds()).then_some(self.hours)
+    }
+}
+
+/// A lock on a directory shared between processes, held by creating a lock
+/// file in it and released by removing the file when dropped.
+///
+/// The file holds a token unique to the holder, so a holder whose lock was
+/// taken over as stale doesn't remove the new holder's when it's done.
+#[derive(Debug)]
+pub struct DirLock {
+    path: PathBuf,
+    token: String,
+}
+
+impl DirLock {
+    /// Take the lock on `dir`, or `None` if another process holds it.
+    ///
+    /// A lock file at least `stale_after` old is left from a process that
+    /// died holding it, so it's taken over.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock file can't be created or checked.
+    pub fn try_acquire(
+        dir: &Path,
+        stale_after: std::time::Duration,
+    ) -> Result<Option<Self>, BurnoutError> {
+        let path = dir.join(LOCK_FILE);
+
+        // creating the file only if it doesn't exist is atomic, so only one
+        // process gets it
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                let token = format!(
+                    "{}-{}",
+                    process::id(),
+                    OffsetDateTime::now_utc().unix_timestamp_nanos()
+                );
+                file.write_all(token.as_bytes())?;
+
+                Ok(Some(Self { path, token }))
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let age = fs::metadata(&path)?
+                    .modified()?
+                    .elapsed()
+                    .unwrap_or_default();
+
+                if age >= stale_after {
+                    fs::remove_file(&path)?;
+                    return Self::try_acquire(dir, stale_after);
+                }
+
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        // the lock may have been taken over since, and isn't ours to remove
+        if fs::read_to_string(&self.path).is_ok_and(|token| token == self.token) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Read the cached hours from `path`, or `None` if nothing is cached yet.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but can't be read or parsed.
+pub fn load_cache(path: &Path) -> Result<Option<CachedHours>, BurnoutError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(from_str(&fs::read_to_string(path)?)?))
+}
+
+/// Write `cached` to `path`, replacing what was there.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be written.
+pub fn save_cache(path: &Path, cached: &CachedHours) -> Result<(), BurnoutError> {
+    fs::write(path, to_string(cached)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_cache, save_cache, CachedHours, DirLock};
+    use std::{error::Error, fs, time::Duration};
+    use time::macros::datetime;
+
+    #[test]
+    fn test_save_and_load() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!(
+            "burnout-meter-cache-{}.json",
+            time::OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+
+        assert_eq!(load_cache(&path)?, None);
+
+        let cached = CachedHours {
+            hours: 22.5,
+            timestamp: 1674000000,
+        };
+        save_cache(&path, &cached)?;
+
+        let loaded = load_cache(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(loaded?, Some(cached));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fresh_hours() {
+        let fetched = datetime!(2023-01-18 09:00 UTC);
+        let cached = CachedHours {
+            hours: 22.5,
+            timestamp: fetched.unix_timestamp(),
+        };
+
+        assert_eq!(
+            cached.fresh_hours(datetime!(2023-01-19 08:59 UTC)),
+            Some(22.5)
+        );
+        assert_eq!(cached.fresh_hours(datetime!(2023-01-19 09:00 UTC)), None);
+        assert_eq!(
+            cached.fresh_within(datetime!(2023-01-18 09:04 UTC), time::Duration::minutes(5)),
+            Some(22.5)
+        );
+        assert_eq!(
+            cached.fresh_within(datetime!(2023-01-18 09:05 UTC), time::Duration::minutes(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dir_lock() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!(
+            "burnout-meter-lock-{}",
+            time::OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+        fs::create_dir(&dir)?;
+
+        let held = DirLock::try_acquire(&dir, Duration::from_secs(60))?;
+        assert!(held.is_some());
+        // a second process has to wait
+        assert!(DirLock::try_acquire(&dir, Duration::from_secs(60))?.is_none());
+
+        // until the first is done with it
+        drop(held);
+        let held = DirLock::try_acquire(&dir, Duration::from_secs(60))?;
+        assert!(held.is_some());
+
+        // or has been holding it too long
+        let taken_over = DirLock::try_acquire(&dir, Duration::ZERO)?;
+        assert!(taken_over.is_some());
+
+        // and the slow holder finishing doesn't release the new holder's lock
+        drop(held);
+        assert!(DirLock::try_acquire(&dir, Duration::from_secs(60))?.is_none());
+
+        drop(taken_over);
+        assert!(!dir.join(super::LOCK_FILE).exists());
+
+        fs::remove_dir_all(&dir)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use crate::error::BurnoutError;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, future::Future};
+use time::{Duration, OffsetDateTime};
+
+/// The part of a [`CircuitBreaker`] that changes between calls, so it can be
+/// persisted across restarts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitState {
+    pub failures: u32,
+    /// When the circuit opened, as a unix timestamp in seconds.
+    pub opened_at: Option<i64>,
+}
+
+/// A circuit breaker that stops calling a failing backend for a while.
+///
+/// After `threshold` consecutive failures the circuit opens and calls are
+/// skipped until `cooldown` has passed. The next call after the cooldown is a
+/// probe: a success closes the circuit again, a failure re-opens it for
+/// another cooldown.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::circuit::CircuitBreaker;
+/// use time::{Duration, OffsetDateTime};
+///
+/// let now = OffsetDateTime::now_utc();
+/// let mut breaker = CircuitBreaker::new(1, Duration::minutes(30));
+/// breaker.record_failure(now);
+/// assert!(!breaker.is_allowed(now));
+/// assert!(breaker.is_allowed(now + Duration::minutes(30)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    opened_at: Option<OffsetDateTime>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            failures: 0,
+            opened_at: None,
+        }
+    }
+
+    /// The consecutive failures and when the circuit opened, to persist.
+    pub fn snapshot(&self) -> CircuitState {
+        CircuitState {
+            failures: self.failures,
+            opened_at: self.opened_at.map(OffsetDateTime::unix_timestamp),
+        }
+    }
+
+    /// Pick up where a persisted [`CircuitBreaker::snapshot`] left off.
+    pub fn restore(&mut self, state: CircuitState) -> &mut Self {
+        self.failures = state.failures;
+        self.opened_at = state
+            .opened_at
+            .and_then(|opened_at| OffsetDateTime::from_unix_timestamp(opened_at).ok());
+
+        self
+    }
+
+    /// Whether the backend should be called at `now`.
+    ///
+    /// Returns `true` while the circuit is closed, and once the cooldown has
+    /// passed so the backend can be probed again.
+    pub fn is_allowed(&self, now: OffsetDateTime) -> bool {
+        match self.opened_at {
+            Some(opened_at) => now - opened_at >= self.cooldown,
+            None => true,
+        }
+    }
+
+    /// Record a successful call, closing the circuit.
+    pub fn record_success(&mut self) {
+        self.failures = 0;
+        self.opened_at = None;
+    }
+
+    /// Record a failed call, opening the circuit once the threshold is reached.
+    pub fn record_failure(&mut self, now: OffsetDateTime) {
+        self.failures += 1;
+
+        if self.failures >= self.threshold {
+            self.opened_at = Some(now);
+        }
+    }
+
+    /// Record the `result` of a call, as a success or a failure.
+    pub fn record<T, E>(&mut self, result: &Result<T, E>, now: OffsetDateTime) {
+        match result {
+            Ok(_) => self.record_success(),
+            Err(_) => self.record_failure(now),
+        }
+    }
+}
+
+/// A [`CircuitBreaker`] for each backend by name, all with the same threshold
+/// and cooldown, so one failing backend doesn't hold up the others.
+#[derive(Debug, Clone)]
+pub struct Circuits {
+    threshold: u32,
+    cooldown: Duration,
+    breakers: HashMap<String, CircuitBreaker>,
+}
+
+impl Circuits {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            breakers: HashMap::new(),
+        }
+    }
+
+    /// The circuit breaker for `backend`, closed if it hasn't been used yet.
+    pub fn backend(&mut self, backend: &str) -> &mut CircuitBreaker {
+        let (threshold, cooldown) = (self.threshold, self.cooldown);
+
+        self.breakers
+            .entry(backend.to_string())
+            .or_insert_with(|| CircuitBreaker::new(threshold, cooldown))
+    }
+
+    /// Each backend's [`CircuitBreaker::snapshot`], to persist.
+    pub fn snapshot(&self) -> HashMap<String, CircuitState> {
+        self.breakers
+            .iter()
+            .map(|(backend, breaker)| (backend.clone(), breaker.snapshot()))
+            .collect()
+    }
+
+    /// Pick up where a persisted [`Circuits::snapshot`] left off.
+    pub fn restore(&mut self, states: &HashMap<String, CircuitState>) -> &mut Self {
+        for (backend, state) in states {
+            self.backend(backend).restore(*state);
+        }
+
+        self
+    }
+
+    /// Make the `call` to `backend` unless its circuit is open, and record
+    /// how it went.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BurnoutError::CircuitOpen`] without making the call while the
+    /// circuit is open, and otherwise whatever error the call did.
+    pub async fn call<T, E: From<BurnoutError>>(
+        &mut self,
+        backend: &str,
+        now: OffsetDateTime,
+        call: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        if !self.backend(backend).is_allowed(now) {
+            return Err(BurnoutError::CircuitOpen(backend.to_string()).into());
+        }
+
+        let result = call.await;
+        self.backend(backend).record(&result, now);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitBreaker, Circuits};
+    use time::{macros::datetime, Duration};
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        let now = datetime!(2023-01-16 12:00 UTC);
+        let mut breaker = CircuitBreaker::new(3, Duration::minutes(30));
+
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+        assert!(breaker.is_allowed(now), "circuit opened too early");
+
+        breaker.record_failure(now);
+        assert!(!breaker.is_allowed(now), "circuit should be open");
+
+        // subsequent cycles within the cooldown skip the backend
+        assert!(!breaker.is_allowed(now + Duration::minutes(10)));
+        assert!(!breaker.is_allowed(now + Duration::minutes(29)));
+
+        // once the cooldown has passed a probe is attempted
+        assert!(breaker.is_allowed(now + Duration::minutes(30)));
+    }
+
+    #[test]
+    fn test_circuit_probe_result() {
+        let now = datetime!(2023-01-16 12:00 UTC);
+        let mut breaker = CircuitBreaker::new(3, Duration::minutes(30));
+
+        for _ in 0..3 {
+            breaker.record_failure(now);
+        }
+
+        // a failed probe re-opens the circuit for another cooldown
+        let probe = now + Duration::minutes(30);
+        breaker.record_failure(probe);
+        assert!(!breaker.is_allowed(probe + Duration::minutes(10)));
+        assert!(breaker.is_allowed(probe + Duration::minutes(30)));
+
+        // a successful probe closes the circuit
+        breaker.record_success();
+        assert!(breaker.is_allowed(probe));
+
+        // and it takes a full run of failures to open it again
+        breaker.record_failure(probe);
+        assert!(breaker.is_allowed(probe));
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let now = datetime!(2023-01-16 12:00 UTC);
+        let mut breaker = CircuitBreaker::new(3, Duration::minutes(30));
+
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+        breaker.record_success();
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+
+        assert!(breaker.is_allowed(now));
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let now = datetime!(2023-01-16 12:00 UTC);
+        let mut breaker = CircuitBreaker::new(2, Duration::minutes(30));
+
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+
+        let mut restored = CircuitBreaker::new(2, Duration::minutes(30));
+        restored.restore(breaker.snapshot());
+
+        assert!(!restored.is_allowed(now + Duration::minutes(10)));
+        assert!(restored.is_allowed(now + Duration::minutes(30)));
+        assert_eq!(restored.snapshot(), breaker.snapshot());
+    }
+
+    #[test]
+    fn test_circuits_are_per_backend() {
+        let now = datetime!(2023-01-16 12:00 UTC);
+        let mut circuits = Circuits::new(1, Duration::minutes(30));
+
+        circuits.backend("discord").record_failure(now);
+
+        assert!(!circuits.backend("discord").is_allowed(now));
+        assert!(circuits.backend("twitter").is_allowed(now));
+
+        let mut restored = Circuits::new(1, Duration::minutes(30));
+        restored.restore(&circuits.snapshot());
+
+        assert!(!restored.backend("discord").is_allowed(now));
+        assert_eq!(restored.snapshot(), circuits.snapshot());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::app::OutputFormat;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+/// Update your Twitter profile location with a meter of how close you are to
+/// burnout.
+///
+/// Everything else is set in the env, or in the config file from `--config`.
+#[derive(Debug, Clone, Default, Parser)]
+#[command(version)]
+pub struct Args {
+    /// What to do, anywhere among the flags, e.g. `meter` in
+    /// `rust-burnout-meter --profile work meter`.
+    #[arg(value_enum)]
+    pub command: Option<Command>,
+
+    /// Take the credentials from this profile in the config file.
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Load the settings from this TOML file instead of `BURNOUT_CONFIG`.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Check this config file and exit, without loading any credentials.
+    #[arg(long, value_name = "PATH")]
+    pub validate_config: Option<PathBuf>,
+
+    /// Print the meter as `text` or `json`, instead of `OUTPUT_FORMAT`.
+    #[arg(long, value_name = "FORMAT")]
+    pub format: Option<OutputFormat>,
+
+    /// Post the location even if it's the one last posted.
+    #[arg(long)]
+    pub force: bool,
+
+    /// Print the raw WakaTime responses to stderr.
+    #[arg(long)]
+    pub dump_wakatime: bool,
+
+    /// Suggest a `BURNOUT_LIMIT` from the last 8 weeks, without updating.
+    #[arg(long, group = "once")]
+    pub suggest_limit: bool,
+
+    /// Print the meter, stretched to the terminal, without updating.
+    #[arg(long, group = "once")]
+    pub print: bool,
+
+    /// Print a shields.io endpoint badge, without updating.
+    #[arg(long, group = "once")]
+    pub shields: bool,
+
+    /// Print the meter as an SVG image, without updating.
+    #[arg(long, group = "once")]
+    pub svg: bool,
+
+    /// Print the history as Grafana time series, without updating.
+    #[arg(long, group = "once")]
+    pub grafana: bool,
+
+    /// Try out the limit and length in an interactive preview.
+    #[cfg(feature = "tui")]
+    #[arg(long, group = "once")]
+    pub tui: bool,
+}
+
+impl Args {
+    /// The command to run, the update if none was given.
+    pub fn command(&self) -> Command {
+        self.command.unwrap_or_default()
+    }
+}
+
+/// What the command was asked to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Command {
+    /// Update the profile, as with no command at all.
+    #[default]
+    Update,
+    /// Print the meter and hours, e.g. for a terminal prompt.
+    Meter,
+    /// Print the hours alone.
+    Hours,
+}
@@ -0,0 +1,377 @@
+use crate::{app::parse_thresholds, twitter};
+use serde::Deserialize;
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+/// Settings loaded from a TOML config file.
+///
+/// # Examples
+///
+/// ```toml
+/// [wakatime]
+/// api_key = "waka_..."
+///
+/// [twitter]
+/// consumer_key = "..."
+/// consumer_secret = "..."
+/// access_token = "..."
+/// access_token_secret = "..."
+///
+/// [profiles.work]
+/// wakatime_api_key = "waka_..."
+///
+/// [profiles.work.twitter]
+/// consumer_key = "..."
+/// consumer_secret = "..."
+/// access_token = "..."
+/// access_token_secret = "..."
+///
+/// [meter]
+/// limit = 40
+/// length = 8
+/// thresholds = "20,30,38"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// The WakaTime key, used when there's no `--profile`.
+    #[serde(default)]
+    pub wakatime: WakaTimeConfig,
+    /// The Twitter credentials, used when there's no `--profile`.
+    #[serde(default)]
+    pub twitter: TwitterConfig,
+    /// Named sets of credentials, picked with `--profile <name>`.
+    #[serde(default)]
+    pub profiles: HashMap<String, CredentialProfile>,
+    /// Meter settings, used when the matching env var isn't set.
+    #[serde(default)]
+    pub meter: MeterConfig,
+}
+
+/// The `[meter]` table, the file equivalents of `BURNOUT_LIMIT`,
+/// `METER_LENGTH` and `THRESHOLDS`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MeterConfig {
+    pub limit: Option<f64>,
+    pub length: Option<u8>,
+    pub thresholds: Option<String>,
+}
+
+/// The `[wakatime]` table, the file equivalent of `WAKATIME_API_KEY`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WakaTimeConfig {
+    pub api_key: Option<String>,
+}
+
+/// The `[twitter]` table, the file equivalents of the `TWITTER_*`
+/// credentials.
+///
+/// Any left out come from the env.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TwitterConfig {
+    pub consumer_key: Option<String>,
+    pub consumer_secret: Option<String>,
+    pub access_token: Option<String>,
+    pub access_token_secret: Option<String>,
+}
+
+/// The credentials for one account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CredentialProfile {
+    pub wakatime_api_key: String,
+    pub twitter: twitter::Credentials,
+}
+
+impl Config {
+    /// Load the config from a TOML file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't valid, including
+    /// any of its [`Config::problems`].
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let config = Self::read(path)?;
+
+        match config.problems() {
+            problems if problems.is_empty() => Ok(config),
+            problems => Err(problems.join("; ").into()),
+        }
+    }
+
+    /// Read and parse the TOML file at `path`, without checking the values.
+    fn read(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config {}: {e}", path.display()))?;
+
+        contents.parse()
+    }
+
+    /// Get the credential profile called `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing the known profiles if there's none called `name`.
+    pub fn profile(&self, name: &str) -> Result<&CredentialProfile, Box<dyn Error>> {
+        match self.profiles.get(name) {
+            Some(profile) => Ok(profile),
+            None => {
+                let mut names: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+                names.sort_unstable();
+
+                Err(format!("Unknown profile: {name} (available: {})", names.join(", ")).into())
+            }
+        }
+    }
+
+    /// Every value in the config that would be rejected at runtime.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(api_key) = &self.wakatime.api_key {
+            if api_key.trim().is_empty() {
+                problems.push("wakatime.api_key is empty".to_string());
+            }
+        }
+
+        let mut names: Vec<_> = self.profiles.keys().collect();
+        names.sort_unstable();
+
+        for name in names {
+            if self.profiles[name].wakatime_api_key.trim().is_empty() {
+                problems.push(format!("profiles.{name}.wakatime_api_key is empty"));
+            }
+        }
+
+        if let Some(limit) = self.meter.limit {
+            if limit <= 0.0 {
+                problems.push(format!("meter.limit must be more than 0, got {limit}"));
+            }
+        }
+
+        if let Some(length) = self.meter.length {
+            if !(1..=MAX_METER_LENGTH).contains(&length) {
+                problems.push(format!(
+                    "meter.length must be between 1 and {MAX_METER_LENGTH}, got {length}"
+                ));
+            }
+        }
+
+        if let Some(thresholds) = &self.meter.thresholds {
+            if let Err(e) = parse_thresholds(thresholds) {
+                problems.push(format!("meter.thresholds: {e}"));
+            }
+        }
+
+        problems
+    }
+}
+
+/// The longest meter that fits in a Twitter location, with each emoji
+/// counted the way Twitter counts it.
+const MAX_METER_LENGTH: u8 = (twitter::LOCATION_MAX_LENGTH / twitter::EMOJI_WEIGHT) as u8;
+
+/// Check the config file at `path` without using it, returning every problem
+/// found, or none if it's valid.
+pub fn validate(path: &Path) -> Vec<String> {
+    match Config::read(path) {
+        Ok(config) => config.problems(),
+        Err(e) => vec![e.to_string()],
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, Config};
+    use std::{error::Error, fs, path::Path};
+
+    const CONFIG: &str = r#"
+        [profiles.personal]
+        wakatime_api_key = "personal_waka"
+
+        [profiles.personal.twitter]
+        consumer_key = "personal_consumer_key"
+        consumer_secret = "personal_consumer_secret"
+        access_token = "personal_access_token"
+        access_token_secret = "personal_access_token_secret"
+
+        [profiles.work]
+        wakatime_api_key = "work_waka"
+
+        [profiles.work.twitter]
+        consumer_key = "work_consumer_key"
+        consumer_secret = "work_consumer_secret"
+        access_token = "work_access_token"
+        access_token_secret = "work_access_token_secret"
+    "#;
+
+    #[test]
+    fn test_profile() -> Result<(), Box<dyn Error>> {
+        let config: Config = CONFIG.parse()?;
+
+        let work = config.profile("work")?;
+        assert_eq!(work.wakatime_api_key, "work_waka");
+        assert_eq!(work.twitter.consumer_key, "work_consumer_key");
+        assert_eq!(work.twitter.access_token_secret, "work_access_token_secret");
+
+        let personal = config.profile("personal")?;
+        assert_eq!(personal.wakatime_api_key, "personal_waka");
+        assert_eq!(personal.twitter.access_token, "personal_access_token");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_profile() -> Result<(), Box<dyn Error>> {
+        let config: Config = CONFIG.parse()?;
+
+        let err = config.profile("side-project").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown profile: side-project (available: personal, work)"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_valid_config() -> Result<(), Box<dyn Error>> {
+        let config: Config = format!(
+            r#"
+            {CONFIG}
+
+            [meter]
+            limit = 40
+            length = 8
+            thresholds = "20,30,38"
+            "#
+        )
+        .parse()?;
+
+        assert_eq!(config.meter.length, Some(8));
+        assert!(config.problems().is_empty(), "{:?}", config.problems());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_level_credentials() -> Result<(), Box<dyn Error>> {
+        let config: Config = r#"
+            [wakatime]
+            api_key = ""
+
+            [twitter]
+            consumer_key = "consumer_key"
+        "#
+        .parse()?;
+
+        assert_eq!(config.twitter.consumer_key.as_deref(), Some("consumer_key"));
+        assert_eq!(config.twitter.access_token, None);
+        assert_eq!(config.problems(), ["wakatime.api_key is empty"]);
+
+        assert!("[twitter]\nkey = \"typo\"".parse::<Config>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_thresholds() -> Result<(), Box<dyn Error>> {
+        let config: Config = r#"
+            [meter]
+            thresholds = "20,38,30"
+        "#
+        .parse()?;
+
+        assert_eq!(
+            config.problems(),
+            ["meter.thresholds: THRESHOLDS must be ascending: 20,38,30"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_length_and_limit() -> Result<(), Box<dyn Error>> {
+        let config: Config = r#"
+            [meter]
+            limit = -5
+            length = 0
+        "#
+        .parse()?;
+
+        assert_eq!(
+            config.problems(),
+            [
+                "meter.limit must be more than 0, got -5",
+                "meter.length must be between 1 and 15, got 0",
+            ]
+        );
+
+        // every emoji counts as 2 toward the location's 30
+        let config: Config = "[meter]\nlength = 16".parse()?;
+        assert_eq!(
+            config.problems(),
+            ["meter.length must be between 1 and 15, got 16"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_types_and_fields() {
+        // a length too big for the type
+        let err = "[meter]\nlength = 300".parse::<Config>().unwrap_err();
+        assert!(err.to_string().contains("length"), "{err}");
+
+        // a profile without its twitter credentials
+        let err = r#"
+            [profiles.work]
+            wakatime_api_key = "work_waka"
+        "#
+        .parse::<Config>()
+        .unwrap_err();
+        assert!(err.to_string().contains("missing field `twitter`"), "{err}");
+
+        // a misspelled meter setting
+        let err = "[meter]\nlenght = 8".parse::<Config>().unwrap_err();
+        assert!(err.to_string().contains("lenght"), "{err}");
+    }
+
+    #[test]
+    fn test_load_rejects_problems() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("burnout-meter-zero-limit.toml");
+        fs::write(&path, "[meter]\nlimit = 0")?;
+
+        // a zero limit would make every percentage NaN
+        let loaded = Config::load(&path);
+        let problems = validate(&path);
+        fs::remove_file(&path)?;
+
+        assert_eq!(
+            loaded.unwrap_err().to_string(),
+            "meter.limit must be more than 0, got 0"
+        );
+        assert_eq!(problems, ["meter.limit must be more than 0, got 0"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_missing_file() {
+        let problems = validate(Path::new("does-not-exist.toml"));
+
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].starts_with("Failed to read config does-not-exist.toml"),
+            "{problems:?}"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{error::BurnoutError, util::http_client};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde_json::json;
+
+/// A Discord channel, posted to through one of its webhooks.
+#[derive(Debug, Clone)]
+pub struct Discord {
+    client: Client,
+    webhook_url: Url,
+}
+
+impl Discord {
+    pub fn new(webhook_url: &str) -> Result<Self, BurnoutError> {
+        Self::with_client(webhook_url, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(webhook_url: &str, client: Client) -> Result<Self, BurnoutError> {
+        let webhook_url =
+            Url::parse(webhook_url).map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            webhook_url,
+        })
+    }
+
+    /// Post `content` as a message in the webhook's channel.
+    ///
+    /// https://discord.com/developers/docs/resources/webhook#execute-webhook
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or Discord answers with an error
+    /// status.
+    pub async fn send_message(&self, content: &str) -> Result<(), BurnoutError> {
+        self.client
+            .post(self.webhook_url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(json!({ "content": content }).to_string())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Discord;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+
+    #[tokio::test]
+    async fn test_send_message() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let webhook = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/webhooks/123/token")
+                .header("content-type", "application/json")
+                .json_body(json!({ "content": "🟨🟨⬜️⬜️ 22/40 hrs" }));
+            then.status(204);
+        });
+
+        let discord = Discord::new(&mock_server.url("/api/webhooks/123/token"))?;
+        discord.send_message("🟨🟨⬜️⬜️ 22/40 hrs").await?;
+
+        webhook.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_message_error_status() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/api/webhooks/123/token");
+            then.status(404);
+        });
+
+        let discord = Discord::new(&mock_server.url("/api/webhooks/123/token"))?;
+
+        assert!(discord.send_message("🟨🟨⬜️⬜️").await.is_err());
+
+        Ok(())
+    }
+}
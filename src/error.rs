@@ -0,0 +1,231 @@
+use std::{error::Error, fmt, io};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// Everything that can go wrong getting the hours, building the meter, or
+/// updating the profile.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::{error::BurnoutError, util::get_env_var};
+///
+/// match get_env_var::<u8>("BURNOUT_METER_UNSET_EXAMPLE") {
+///     Err(BurnoutError::MissingEnv(key)) => assert_eq!(key, "BURNOUT_METER_UNSET_EXAMPLE"),
+///     other => panic!("expected a missing env var, got {other:?}"),
+/// }
+/// ```
+#[derive(Debug)]
+pub enum BurnoutError {
+    /// A request failed before there was a response.
+    #[cfg(feature = "network")]
+    Http(reqwest::Error),
+    /// A request took longer than the client's timeout.
+    #[cfg(feature = "network")]
+    Timeout(reqwest::Error),
+    /// A Twitter request couldn't be signed.
+    #[cfg(feature = "network")]
+    OAuth(reqwest_oauth1::Error),
+    /// WakaTime answered with an error status, even after retrying.
+    #[cfg(feature = "network")]
+    WakaTimeStatus(reqwest::StatusCode),
+    /// WakaTime answered with `429 Too Many Requests`, even after retrying.
+    RateLimited,
+    /// Twitter answered with `429 Too Many Requests`, with when the limit
+    /// resets if it said.
+    TwitterRateLimited { reset_at: Option<OffsetDateTime> },
+    /// WakaTime didn't have any hours for the range.
+    WakaTimeEmpty,
+    /// A response or file wasn't the JSON expected.
+    Parse(serde_json::Error),
+    /// A decimal from WakaTime couldn't be read as a number.
+    InvalidDecimal(String),
+    /// A URL couldn't be parsed or joined.
+    InvalidUrl(String),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// The env var with this name isn't set.
+    MissingEnv(String),
+    /// The env var with this name is set, but to something that can't be parsed.
+    InvalidEnv(String),
+    /// The text is longer than Twitter allows in the profile field, counted
+    /// the way Twitter counts it.
+    LocationTooLong { length: usize, max: usize },
+    /// The Slack Web API answered with `ok` false and this error.
+    Slack(String),
+    /// Bluesky answered with this XRPC error, like `AuthenticationRequired`.
+    Bluesky(String),
+    /// The GitHub GraphQL API answered with these errors.
+    GitHub(Vec<String>),
+    /// The Telegram Bot API answered with `ok` false and this description.
+    Telegram(String),
+    /// The Mastodon profile has no room for another field, and none of its
+    /// fields has this name.
+    MastodonFieldsFull(String),
+    /// The circuit breaker for this backend is open after too many failures
+    /// in a row, so it wasn't called.
+    CircuitOpen(String),
+    /// Twitter answered the update without the profile field in it, so it
+    /// wasn't set.
+    ProfileNotUpdated(String),
+    /// The location template has a placeholder with this name that isn't one
+    /// of the ones filled in.
+    UnknownPlaceholder(String),
+    /// The meter was built without a current value.
+    InvalidMeterValue,
+    /// One of the meter's emoji was set to an empty string.
+    EmptyEmoji,
+    /// The meter's `[yellow, orange, red]` thresholds aren't ascending
+    /// fractions of the max.
+    InvalidThresholds([f64; 3]),
+}
+
+impl fmt::Display for BurnoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "network")]
+            Self::Http(e) => write!(f, "{e}"),
+            #[cfg(feature = "network")]
+            Self::Timeout(e) => match e.url() {
+                Some(url) => write!(f, "Timed out waiting for {}", url.host_str().unwrap_or("")),
+                None => write!(f, "Timed out waiting for a response"),
+            },
+            #[cfg(feature = "network")]
+            Self::OAuth(e) => write!(f, "{e}"),
+            #[cfg(feature = "network")]
+            Self::WakaTimeStatus(status) => write!(f, "WakaTime returned {status}"),
+            Self::RateLimited => write!(f, "Rate limited by WakaTime"),
+            Self::TwitterRateLimited { reset_at } => {
+                match reset_at.and_then(|reset_at| reset_at.format(&Rfc3339).ok()) {
+                    Some(reset_at) => write!(f, "Rate limited by Twitter until {reset_at}"),
+                    None => write!(f, "Rate limited by Twitter"),
+                }
+            }
+            Self::WakaTimeEmpty => write!(f, "No hours found from WakaTime"),
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::InvalidDecimal(decimal) => {
+                write!(f, "Failed to parse WakaTime decimal: {decimal}")
+            }
+            Self::InvalidUrl(e) => write!(f, "Invalid URL: {e}"),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::MissingEnv(key) => write!(f, "{key} not set"),
+            Self::InvalidEnv(key) => write!(f, "Failed to parse {key}"),
+            Self::LocationTooLong { length, max } => write!(
+                f,
+                "Too long for Twitter at {length} characters, over the limit of {max}"
+            ),
+            Self::Slack(error) => write!(f, "Slack returned {error}"),
+            Self::Bluesky(error) => write!(f, "Bluesky returned {error}"),
+            Self::GitHub(errors) => write!(f, "GitHub returned {}", errors.join("; ")),
+            Self::Telegram(description) => write!(f, "Telegram returned {description}"),
+            Self::MastodonFieldsFull(name) => write!(
+                f,
+                "The Mastodon profile has no room for another field, and none is called {name}"
+            ),
+            Self::CircuitOpen(backend) => {
+                write!(f, "Skipping {backend}, too many consecutive failures")
+            }
+            Self::ProfileNotUpdated(field) => write!(f, "Twitter {field} not updated"),
+            Self::UnknownPlaceholder(name) => {
+                write!(f, "Unknown placeholder {{{name}}} in LOCATION_TEMPLATE")
+            }
+            Self::InvalidMeterValue => write!(f, "No current value"),
+            Self::EmptyEmoji => write!(f, "Meter emoji can't be empty"),
+            Self::InvalidThresholds([yellow, orange, red]) => write!(
+                f,
+                "Thresholds must be ascending and within 0 to 1, got {yellow}, {orange}, {red}"
+            ),
+        }
+    }
+}
+
+impl Error for BurnoutError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "network")]
+            Self::Http(e) | Self::Timeout(e) => Some(e),
+            #[cfg(feature = "network")]
+            Self::OAuth(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+impl From<reqwest::Error> for BurnoutError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.is_timeout() {
+            true => Self::Timeout(e),
+            false => Self::Http(e),
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+impl From<reqwest_oauth1::Error> for BurnoutError {
+    fn from(e: reqwest_oauth1::Error) -> Self {
+        match e {
+            // a failed request is the same failure whether or not it was signed
+            reqwest_oauth1::Error::Reqwest(e) => e.into(),
+            e => Self::OAuth(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for BurnoutError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<io::Error> for BurnoutError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BurnoutError;
+    use std::error::Error;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            BurnoutError::MissingEnv("WAKATIME_API_KEY".to_string()).to_string(),
+            "WAKATIME_API_KEY not set"
+        );
+        assert_eq!(
+            BurnoutError::InvalidEnv("METER_LENGTH".to_string()).to_string(),
+            "Failed to parse METER_LENGTH"
+        );
+        assert_eq!(
+            BurnoutError::InvalidMeterValue.to_string(),
+            "No current value"
+        );
+        assert_eq!(
+            BurnoutError::WakaTimeEmpty.to_string(),
+            "No hours found from WakaTime"
+        );
+    }
+
+    #[test]
+    fn test_from_parse_error() {
+        let e: BurnoutError = serde_json::from_str::<u8>("nope").unwrap_err().into();
+
+        assert!(matches!(e, BurnoutError::Parse(_)));
+        assert!(e.source().is_some());
+    }
+
+    #[test]
+    fn test_matches_through_box() {
+        // callers still using `Box<dyn Error>` can get the variant back
+        let boxed: Box<dyn Error> = BurnoutError::RateLimited.into();
+
+        assert!(matches!(
+            boxed.downcast_ref::<BurnoutError>(),
+            Some(BurnoutError::RateLimited)
+        ));
+    }
+}
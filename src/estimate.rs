@@ -0,0 +1,111 @@
+use time::{OffsetDateTime, UtcOffset};
+
+const SECONDS_PER_DAY: f64 = 86_400f64;
+
+/// Hours that may still go up or down while the window is open, as a range
+/// either side of the hours so far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Estimate {
+    /// Spread `hours` by `margin` of itself, scaled down by how much of the
+    /// window is still `open`, from 0 (complete) to 1 (just started).
+    pub fn new(hours: f64, margin: f64, open: f64) -> Self {
+        let spread = hours * margin * open.clamp(0f64, 1f64);
+
+        Self {
+            low: (hours - spread).max(0f64),
+            high: hours + spread,
+        }
+    }
+
+    /// The estimate with both ends capped at `max`.
+    pub fn clamped(&self, max: f64) -> Self {
+        Self {
+            low: self.low.min(max),
+            high: self.high.min(max),
+        }
+    }
+
+    /// Render the whole hours, e.g. `22`, or the range if the ends round
+    /// differently, e.g. `20–24`.
+    pub fn render(&self) -> String {
+        let (low, high) = (self.low.round() as i64, self.high.round() as i64);
+
+        if low == high {
+            low.to_string()
+        } else {
+            format!("{low}–{high}")
+        }
+    }
+}
+
+/// How much of the window from Monday to the end of today is still to come,
+/// from 0 at midnight tonight to 1 at midnight on Monday morning.
+pub fn open_fraction(now: OffsetDateTime, offset: UtcOffset, days_since_monday: i64) -> f64 {
+    let (hour, minute, second) = now.to_offset(offset).time().as_hms();
+
+    let elapsed_today = (hour as u32 * 3600 + minute as u32 * 60 + second as u32) as f64;
+    let window = (days_since_monday + 1) as f64 * SECONDS_PER_DAY;
+
+    (SECONDS_PER_DAY - elapsed_today) / window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_fraction, Estimate};
+    use time::{
+        macros::{datetime, offset},
+        UtcOffset,
+    };
+
+    #[test]
+    fn test_complete_window_is_a_point() {
+        let estimate = Estimate::new(22.2, 0.5, 0f64);
+
+        assert_eq!(estimate.low, estimate.high);
+        assert_eq!(estimate.render(), "22");
+    }
+
+    #[test]
+    fn test_mid_day_window_is_a_range() {
+        // noon on a Wednesday, half of one of the three days is still open
+        let open = open_fraction(datetime!(2023-01-18 12:00 UTC), UtcOffset::UTC, 2);
+        assert!((open - 1f64 / 6f64).abs() < 1e-9);
+
+        let estimate = Estimate::new(22.0, 0.5, open);
+
+        assert_eq!(estimate.render(), "20–24");
+        assert!(estimate.high > 22.0);
+    }
+
+    #[test]
+    fn test_open_fraction() {
+        // midnight on Monday morning, the whole window is open
+        assert_eq!(
+            open_fraction(datetime!(2023-01-16 00:00 UTC), UtcOffset::UTC, 0),
+            1.0
+        );
+        // a minute to midnight on Sunday in UTC-5
+        let open = open_fraction(datetime!(2023-01-23 04:59 UTC), offset!(-5), 6);
+        assert!(open < 0.001);
+    }
+
+    #[test]
+    fn test_clamped() {
+        let estimate = Estimate::new(180.0, 0f64, 0f64);
+
+        assert_eq!(estimate.clamped(160.0).render(), "160");
+        assert_eq!(estimate.clamped(200.0).render(), "180");
+
+        // a range that crosses the cap is cut off at it
+        let range = Estimate {
+            low: 150.0,
+            high: 170.0,
+        };
+        assert_eq!(range.clamped(160.0).render(), "150–160");
+    }
+}
@@ -0,0 +1,236 @@
+use crate::meter::{Band, Builder};
+use serde::Serialize;
+use std::{
+    error::Error,
+    io::{ErrorKind, Write},
+    process::{Command, Stdio},
+};
+
+/// What the command gets on stdin, as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct Payload {
+    pub location: String,
+    pub meter: String,
+    pub hours: f64,
+    pub max: f64,
+    /// The hours as a percentage of the max.
+    pub percent: f64,
+    /// The hours left before the max, or zero once it's reached.
+    pub remaining: f64,
+    pub band: Band,
+}
+
+impl Payload {
+    /// The payload for `hours` out of `max`, working out the percent and
+    /// remaining hours from them.
+    pub fn new(location: String, meter: String, hours: f64, max: f64, band: Band) -> Self {
+        Self {
+            location,
+            meter,
+            hours,
+            max,
+            percent: Builder::percent_of(hours, max),
+            remaining: (max - hours).max(0f64),
+            band,
+        }
+    }
+
+    /// Round the hours, percent, and remaining hours to `decimals` places,
+    /// for commands that don't want every digit of a float.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::{exec::Payload, meter::Band};
+    ///
+    /// let payload = Payload::new(String::new(), String::new(), 22.456, 40.0, Band::Busy)
+    ///     .with_precision(1);
+    ///
+    /// assert_eq!((payload.hours, payload.percent, payload.remaining), (22.5, 56.1, 17.5));
+    /// ```
+    pub fn with_precision(self, decimals: u32) -> Self {
+        Self {
+            hours: round_to(self.hours, decimals),
+            percent: round_to(self.percent, decimals),
+            remaining: round_to(self.remaining, decimals),
+            ..self
+        }
+    }
+}
+
+fn round_to(value: f64, decimals: u32) -> f64 {
+    let scale = 10f64.powi(decimals as i32);
+
+    (value * scale).round() / scale
+}
+
+/// A backend that hands each update to an external command, so any
+/// destination can be scripted.
+#[derive(Debug, Clone)]
+pub struct Exec {
+    command: String,
+}
+
+impl Exec {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+        }
+    }
+
+    /// Run the command with `sh -c`, writing `payload` to its stdin as JSON.
+    ///
+    /// Returns what the command wrote to stdout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command can't be started or exits with a
+    /// non-zero status, including what it wrote to stderr.
+    pub fn update(&self, payload: &Payload) -> Result<String, Box<dyn Error>> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(serde_json::to_string(payload)?.as_bytes()) {
+                // the command exited without reading it, its status says how it went
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => {}
+                result => result?,
+            }
+        }
+
+        let output = child.wait_with_output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "`{}` failed with {}: {}",
+                self.command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+/// Run the `PRE_UPDATE_HOOK` command with `sh -c` before an update, with the
+/// payload in `BURNOUT_*` env vars.
+///
+/// Returns whether the update should go ahead, which is only when the hook
+/// exits successfully.
+///
+/// # Errors
+///
+/// Returns an error if the hook can't be started.
+pub fn run_hook(command: &str, payload: &Payload) -> Result<bool, Box<dyn Error>> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("BURNOUT_LOCATION", &payload.location)
+        .env("BURNOUT_METER", &payload.meter)
+        .env("BURNOUT_HOURS", payload.hours.to_string())
+        .env("BURNOUT_MAX", payload.max.to_string())
+        .env("BURNOUT_BAND", payload.band.to_string())
+        .stdin(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        log::warn!(
+            "Pre-update hook `{command}` failed with {}, skipping update: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_hook, Exec, Payload};
+    use crate::meter::Band;
+    use serde_json::json;
+    use std::error::Error;
+
+    fn payload() -> Payload {
+        Payload::new(
+            "🟨🟨🟨⬜️⬜️ 22/40 hours".to_string(),
+            "🟨🟨🟨⬜️⬜️".to_string(),
+            22.5,
+            40.0,
+            Band::Busy,
+        )
+    }
+
+    #[test]
+    fn test_command_receives_payload() -> Result<(), Box<dyn Error>> {
+        let stdout = Exec::new("cat").update(&payload())?;
+
+        let received: serde_json::Value = serde_json::from_str(&stdout)?;
+
+        assert_eq!(
+            received,
+            json!({
+                "location": "🟨🟨🟨⬜️⬜️ 22/40 hours",
+                "meter": "🟨🟨🟨⬜️⬜️",
+                "hours": 22.5,
+                "max": 40.0,
+                "percent": 56.25,
+                "remaining": 17.5,
+                "band": "busy"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_precision_rounds_payload() -> Result<(), Box<dyn Error>> {
+        let payload = Payload::new(String::new(), String::new(), 12.3456, 40.0, Band::Fresh);
+
+        let json = serde_json::to_string(&payload.clone().with_precision(2))?;
+        assert!(json.contains(r#""hours":12.35,"#), "{json}");
+        assert!(json.contains(r#""percent":30.86,"#), "{json}");
+        assert!(json.contains(r#""remaining":27.65,"#), "{json}");
+
+        // whole numbers are left as they were, without padding
+        let json = serde_json::to_string(&payload.with_precision(0))?;
+        assert!(json.contains(r#""hours":12.0,"#), "{json}");
+        assert!(json.contains(r#""max":40.0,"#), "{json}");
+        assert!(json.contains(r#""remaining":28.0,"#), "{json}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_zero_exit_fails() {
+        let error = Exec::new("echo nope >&2; exit 3")
+            .update(&payload())
+            .unwrap_err();
+
+        assert!(error.to_string().contains("nope"), "{error}");
+    }
+
+    #[test]
+    fn test_passing_hook_allows_update() -> Result<(), Box<dyn Error>> {
+        let hook = r#"test "$BURNOUT_BAND" = busy && test "$BURNOUT_HOURS" = 22.5"#;
+
+        assert!(run_hook(hook, &payload())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failing_hook_blocks_update() -> Result<(), Box<dyn Error>> {
+        assert!(!run_hook("echo not today >&2; exit 1", &payload())?);
+
+        Ok(())
+    }
+}
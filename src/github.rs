@@ -0,0 +1,391 @@
+use crate::{error::BurnoutError, meter::Band, redact::log_request, util::http_client};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde::Deserialize;
+use serde_json::{from_str, json};
+use std::error::Error;
+use time::{format_description::well_known::Rfc3339, macros::format_description, OffsetDateTime};
+
+/// The most characters GitHub allows in a status message.
+pub const STATUS_MAX_LENGTH: usize = 80;
+
+/// Set the status, giving back what it was set to.
+///
+/// https://docs.github.com/en/graphql/reference/mutations#changeuserstatus
+const CHANGE_USER_STATUS: &str = "mutation($input: ChangeUserStatusInput!) { \
+    changeUserStatus(input: $input) { status { message emoji } } }";
+
+#[derive(Deserialize, Debug)]
+struct SearchResults {
+    total_count: u64,
+}
+
+/// A GraphQL response, which can have errors even with a `200 OK`.
+#[derive(Deserialize, Debug)]
+struct GraphQlResponse {
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphQlError {
+    message: String,
+}
+
+/// How much you've been doing on GitHub over a period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Activity {
+    pub pull_requests: u64,
+    pub reviews: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitHub {
+    client: Client,
+    base_url: Url,
+    token: String,
+    user: String,
+}
+
+impl GitHub {
+    pub fn new(token: &str, user: &str) -> Result<Self, Box<dyn Error>> {
+        Self::with_client(token, user, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(token: &str, user: &str, client: Client) -> Result<Self, Box<dyn Error>> {
+        let base_url = "https://api.github.com".parse()?;
+
+        Ok(Self {
+            client,
+            base_url,
+            token: token.to_string(),
+            user: user.to_string(),
+        })
+    }
+
+    /// Create the client for setting the status alone, with no user whose
+    /// activity to search for.
+    pub fn status_only(token: &str, client: Client) -> Self {
+        Self {
+            client,
+            base_url: "https://api.github.com"
+                .parse()
+                .expect("Failed to parse the GitHub API URL"),
+            token: token.to_string(),
+            user: String::new(),
+        }
+    }
+
+    /// Point requests at a different server, e.g. a mock in tests.
+    pub fn set_base_url(&mut self, base_url: Url) -> &mut Self {
+        self.base_url = base_url;
+
+        self
+    }
+
+    /// Get the pull requests you opened and the pull requests of others you
+    /// reviewed in the last n days from the GitHub search API.
+    ///
+    /// https://docs.github.com/en/rest/search#search-issues-and-pull-requests
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either request fails or a response cannot be parsed.
+    pub async fn get_activity_last_n_days(&self, days: i64) -> Result<Activity, Box<dyn Error>> {
+        let since = (OffsetDateTime::now_utc() - time::Duration::days(days))
+            .date()
+            .format(format_description!("[year]-[month]-[day]"))?;
+        let user = &self.user;
+
+        let pull_requests = self
+            .search(&format!("type:pr author:{user} created:>={since}"))
+            .await?;
+        let reviews = self
+            .search(&format!(
+                "type:pr reviewed-by:{user} -author:{user} updated:>={since}"
+            ))
+            .await?;
+
+        Ok(Activity {
+            pull_requests,
+            reviews,
+        })
+    }
+
+    /// Set the profile status to `message`, cut to [`STATUS_MAX_LENGTH`]
+    /// characters, with `emoji` like `:green_circle:`, clearing itself at
+    /// `expires_at` or never if it's `None`.
+    ///
+    /// The token needs the `user` scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or GitHub answers with an error
+    /// status, and [`BurnoutError::GitHub`] with the messages if the
+    /// response has GraphQL errors.
+    pub async fn set_status(
+        &self,
+        message: &str,
+        emoji: &str,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<(), BurnoutError> {
+        let message: String = message.chars().take(STATUS_MAX_LENGTH).collect();
+
+        let input = json!({
+            "message": message,
+            "emoji": emoji,
+            // null is how GitHub says never
+            "expiresAt": expires_at.and_then(|at| at.format(&Rfc3339).ok()),
+        });
+
+        let url = self
+            .base_url
+            .join("/graphql")
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+
+        let request = self
+            .client
+            .post(url)
+            .bearer_auth(&self.token)
+            .header("user-agent", "rust-burnout-meter")
+            .header(CONTENT_TYPE, "application/json")
+            .body(
+                json!({ "query": CHANGE_USER_STATUS, "variables": { "input": input } }).to_string(),
+            )
+            .build()?;
+        log_request(&request);
+
+        let body = self
+            .client
+            .execute(request)
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let response: GraphQlResponse = from_str(&body)?;
+
+        match response.errors.is_empty() {
+            true => Ok(()),
+            false => Err(BurnoutError::GitHub(
+                response
+                    .errors
+                    .into_iter()
+                    .map(|error| error.message)
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Count the issues and pull requests matching `query`.
+    async fn search(&self, query: &str) -> Result<u64, Box<dyn Error>> {
+        let url = self.base_url.join("/search/issues")?;
+
+        let request = self
+            .client
+            .get(url)
+            .bearer_auth(&self.token)
+            .header("accept", "application/vnd.github+json")
+            .header("user-agent", "rust-burnout-meter")
+            .query(&[("q", query), ("per_page", "1")])
+            .build()?;
+        log_request(&request);
+
+        let response = self.client.execute(request).await?;
+
+        let body = response.text().await?;
+
+        let result: SearchResults = from_str(&body)?;
+
+        Ok(result.total_count)
+    }
+}
+
+/// The GitHub emoji for the status in `band`, a circle the color of the
+/// meter.
+pub fn status_emoji(band: Band) -> &'static str {
+    match band {
+        Band::Fresh => ":green_circle:",
+        Band::Busy => ":yellow_circle:",
+        Band::Heavy => ":orange_circle:",
+        Band::Burnout | Band::Overflow => ":red_circle:",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{status_emoji, Activity, GitHub, STATUS_MAX_LENGTH};
+    use crate::{error::BurnoutError, meter::Band};
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+    use time::{macros::datetime, macros::format_description, OffsetDateTime};
+
+    #[tokio::test]
+    async fn test_get_activity_last_n_days() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let since = (OffsetDateTime::now_utc() - time::Duration::days(7))
+            .date()
+            .format(format_description!("[year]-[month]-[day]"))?;
+
+        let pull_requests = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/search/issues")
+                .header("authorization", "Bearer test_token")
+                .query_param("q", format!("type:pr author:octocat created:>={since}"));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "total_count": 4, "items": [] }).to_string());
+        });
+
+        let reviews = mock_server.mock(|when, then| {
+            when.method(GET).path("/search/issues").query_param(
+                "q",
+                format!("type:pr reviewed-by:octocat -author:octocat updated:>={since}"),
+            );
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "total_count": 7, "items": [] }).to_string());
+        });
+
+        let mut client = GitHub::new("test_token", "octocat")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let activity = client.get_activity_last_n_days(7).await?;
+
+        pull_requests.assert();
+        reviews.assert();
+
+        assert_eq!(
+            activity,
+            Activity {
+                pull_requests: 4,
+                reviews: 7
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_status() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let status = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/graphql")
+                .header("authorization", "Bearer test_token")
+                .json_body_partial(
+                    json!({
+                        "variables": {
+                            "input": {
+                                "message": "🟨🟨⬜️⬜️ 22/40 hours",
+                                "emoji": ":yellow_circle:",
+                                "expiresAt": "2023-01-16T12:00:00Z",
+                            }
+                        }
+                    })
+                    .to_string(),
+                );
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "data": {
+                            "changeUserStatus": {
+                                "status": {
+                                    "message": "🟨🟨⬜️⬜️ 22/40 hours",
+                                    "emoji": ":yellow_circle:",
+                                }
+                            }
+                        }
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = GitHub::new("test_token", "octocat")?;
+        client.set_base_url(mock_server.base_url().parse()?);
+        client
+            .set_status(
+                "🟨🟨⬜️⬜️ 22/40 hours",
+                status_emoji(Band::Busy),
+                Some(datetime!(2023-01-16 12:00 UTC)),
+            )
+            .await?;
+
+        status.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_status_cut_to_limit() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let status = mock_server.mock(|when, then| {
+            when.method(POST).path("/graphql").json_body_partial(
+                json!({
+                    "variables": {
+                        "input": { "message": "x".repeat(STATUS_MAX_LENGTH) }
+                    }
+                })
+                .to_string(),
+            );
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "data": { "changeUserStatus": null } }).to_string());
+        });
+
+        let mut client = GitHub::status_only("test_token", reqwest::Client::new());
+        client.set_base_url(mock_server.base_url().parse()?);
+        client
+            .set_status(&"x".repeat(100), ":green_circle:", None)
+            .await?;
+
+        status.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_status_graphql_errors() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/graphql");
+            // GraphQL errors still come back as 200 OK
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "data": { "changeUserStatus": null },
+                        "errors": [
+                            {
+                                "type": "INSUFFICIENT_SCOPES",
+                                "message": "Your token has not been granted the required scopes",
+                            },
+                            { "message": "Emoji isn't valid" },
+                        ],
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = GitHub::status_only("test_token", reqwest::Client::new());
+        client.set_base_url(mock_server.base_url().parse()?);
+        let result = client.set_status("🟩⬜️", ":green_circle:", None).await;
+
+        match result {
+            Err(BurnoutError::GitHub(errors)) => assert_eq!(
+                errors,
+                [
+                    "Your token has not been granted the required scopes",
+                    "Emoji isn't valid",
+                ]
+            ),
+            other => panic!("expected GraphQL errors, got {other:?}"),
+        }
+
+        Ok(())
+    }
+}
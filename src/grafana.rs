@@ -0,0 +1,93 @@
+use crate::state::HistoryEntry;
+use serde::Serialize;
+
+/// A single series in the shape the Grafana SimpleJSON and Infinity data
+/// sources expect from a query.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Series {
+    pub target: String,
+    /// Each point as `[value, unix timestamp in milliseconds]`.
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+/// Turn the run history into Grafana time series for the hours and the limit.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::grafana::to_series;
+///
+/// let series = to_series(&[]);
+/// assert_eq!(series[0].target, "hours");
+/// ```
+pub fn to_series(history: &[HistoryEntry]) -> Vec<Series> {
+    let series = |target: &str, value: fn(&HistoryEntry) -> f64| Series {
+        target: target.to_string(),
+        datapoints: history
+            .iter()
+            .map(|entry| (value(entry), entry.timestamp * 1000))
+            .collect(),
+    };
+
+    vec![
+        series("hours", |entry| entry.hours),
+        series("limit", |entry| entry.limit),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_series;
+    use crate::state::HistoryEntry;
+    use serde_json::{json, to_value};
+    use std::error::Error;
+
+    #[test]
+    fn test_to_series() -> Result<(), Box<dyn Error>> {
+        let history = [
+            HistoryEntry {
+                timestamp: 1674000000,
+                hours: 12.5,
+                limit: 40.0,
+            },
+            HistoryEntry {
+                timestamp: 1674021600,
+                hours: 14.0,
+                limit: 40.0,
+            },
+        ];
+
+        let json = to_value(to_series(&history))?;
+
+        assert_eq!(
+            json,
+            json!([
+                {
+                    "target": "hours",
+                    "datapoints": [[12.5, 1674000000000i64], [14.0, 1674021600000i64]]
+                },
+                {
+                    "target": "limit",
+                    "datapoints": [[40.0, 1674000000000i64], [40.0, 1674021600000i64]]
+                }
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_series_empty() -> Result<(), Box<dyn Error>> {
+        let json = to_value(to_series(&[]))?;
+
+        assert_eq!(
+            json,
+            json!([
+                { "target": "hours", "datapoints": [] },
+                { "target": "limit", "datapoints": [] }
+            ])
+        );
+
+        Ok(())
+    }
+}
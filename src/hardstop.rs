@@ -0,0 +1,195 @@
+use crate::{meter::Band, state::State};
+use time::{OffsetDateTime, UtcOffset};
+
+/// The message posted instead of the meter when updates stop.
+pub const DEFAULT_BREAK_MESSAGE: &str = "taking a break 🌴";
+
+/// What a run should do about a sustained burnout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Update as normal.
+    Update,
+    /// Post the break message once and stop updating.
+    Stop,
+    /// Updates are stopped until the sentinel is cleared.
+    Stopped,
+}
+
+/// Work out the step for a run at `now` in the `band`, stopping once the
+/// meter has been red for `days` days in a row, and track the streak in
+/// `state`.
+///
+/// Once stopped, runs stay stopped while the sentinel exists. Clearing it
+/// starts a fresh streak and forgets the last band, so the next run posts
+/// the meter again.
+pub fn step(
+    state: &mut State,
+    band: Band,
+    now: OffsetDateTime,
+    offset: UtcOffset,
+    days: u32,
+    sentinel_exists: bool,
+) -> Step {
+    if state.on_break {
+        if sentinel_exists {
+            return Step::Stopped;
+        }
+
+        state.on_break = false;
+        state.red_since = None;
+        state.last_band = None;
+    }
+
+    if band < Band::Burnout {
+        state.red_since = None;
+
+        return Step::Update;
+    }
+
+    let red_since = *state.red_since.get_or_insert(now.unix_timestamp());
+
+    if red_days(red_since, now, offset) >= days as i64 {
+        state.on_break = true;
+
+        return Step::Stop;
+    }
+
+    Step::Update
+}
+
+/// How many days, counting today, the meter has been red since the unix
+/// timestamp `red_since`.
+fn red_days(red_since: i64, now: OffsetDateTime, offset: UtcOffset) -> i64 {
+    let since = match OffsetDateTime::from_unix_timestamp(red_since) {
+        Ok(since) => since.to_offset(offset).date(),
+        Err(_) => return 0,
+    };
+
+    (now.to_offset(offset).date() - since).whole_days() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{step, Step};
+    use crate::{meter::Band, state::State};
+    use time::{
+        macros::{datetime, offset},
+        UtcOffset,
+    };
+
+    #[test]
+    fn test_stops_after_red_days() {
+        let mut state = State::default();
+
+        // two red days aren't enough
+        let monday = datetime!(2023-01-16 09:00 UTC);
+        assert_eq!(
+            step(&mut state, Band::Burnout, monday, UtcOffset::UTC, 3, false),
+            Step::Update
+        );
+        let tuesday = datetime!(2023-01-17 21:00 UTC);
+        assert_eq!(
+            step(
+                &mut state,
+                Band::Overflow,
+                tuesday,
+                UtcOffset::UTC,
+                3,
+                false
+            ),
+            Step::Update
+        );
+
+        // the third stops, once
+        let wednesday = datetime!(2023-01-18 09:00 UTC);
+        assert_eq!(
+            step(
+                &mut state,
+                Band::Burnout,
+                wednesday,
+                UtcOffset::UTC,
+                3,
+                false
+            ),
+            Step::Stop
+        );
+        assert!(state.on_break);
+
+        let thursday = datetime!(2023-01-19 09:00 UTC);
+        assert_eq!(
+            step(&mut state, Band::Burnout, thursday, UtcOffset::UTC, 3, true),
+            Step::Stopped
+        );
+        // even after the meter drops
+        assert_eq!(
+            step(&mut state, Band::Fresh, thursday, UtcOffset::UTC, 3, true),
+            Step::Stopped
+        );
+    }
+
+    #[test]
+    fn test_clearing_sentinel_resumes() {
+        let mut state = State::default();
+        let now = datetime!(2023-01-18 09:00 UTC);
+
+        assert_eq!(
+            step(&mut state, Band::Burnout, now, UtcOffset::UTC, 1, false),
+            Step::Stop
+        );
+        state.last_band = Some(Band::Burnout);
+
+        // the next run after the sentinel is gone updates, starting a new streak
+        let later = datetime!(2023-01-20 09:00 UTC);
+        assert_eq!(
+            step(&mut state, Band::Heavy, later, UtcOffset::UTC, 1, false),
+            Step::Update
+        );
+        assert!(!state.on_break);
+        assert_eq!(state.red_since, None);
+        assert_eq!(state.last_band, None);
+    }
+
+    #[test]
+    fn test_streak_resets() {
+        let mut state = State::default();
+
+        let runs = [
+            (Band::Burnout, datetime!(2023-01-16 09:00 UTC)),
+            (Band::Burnout, datetime!(2023-01-17 09:00 UTC)),
+            // a run out of the red breaks the streak
+            (Band::Heavy, datetime!(2023-01-17 18:00 UTC)),
+        ];
+        for (band, now) in runs {
+            step(&mut state, band, now, UtcOffset::UTC, 3, false);
+        }
+
+        let wednesday = datetime!(2023-01-18 09:00 UTC);
+        assert_eq!(
+            step(
+                &mut state,
+                Band::Burnout,
+                wednesday,
+                UtcOffset::UTC,
+                3,
+                false
+            ),
+            Step::Update
+        );
+        assert_eq!(state.red_since, Some(wednesday.unix_timestamp()));
+    }
+
+    #[test]
+    fn test_red_days_in_offset() {
+        let mut state = State::default();
+
+        // 23:00 on Monday in UTC is already Tuesday at UTC+2
+        let evening = datetime!(2023-01-16 20:00 UTC);
+        step(&mut state, Band::Burnout, evening, offset!(+2), 2, false);
+        let late = datetime!(2023-01-16 23:00 UTC);
+
+        assert_eq!(
+            step(&mut state, Band::Burnout, late, offset!(+2), 2, false),
+            Step::Stop
+        );
+    }
+}
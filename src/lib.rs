@@ -0,0 +1,70 @@
+//! Update your Twitter profile location with a meter of how close you are to burnout.
+//!
+//! Uses the [WakaTime API] to get the total time spent coding in the last 30 days.
+//! Then creates a meter with emoji to show how close you are to burnout.
+//! Finally, updates your Twitter profile location with the meter.
+//!
+//! The meter can be used on its own, without the network features:
+//!
+//! ```
+//! use rust_burnout_meter::meter::Builder;
+//!
+//! let mut meter = Builder::new();
+//! meter.set_max(40).set_length(5).set_current(22).build()?;
+//! assert_eq!(meter, "🟨🟨⬜️⬜️⬜️");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [WakaTime API]: https://wakatime.com/developers
+
+#[cfg(feature = "network")]
+pub mod app;
+#[cfg(feature = "network")]
+pub mod blended;
+#[cfg(feature = "network")]
+pub mod bluesky;
+pub mod cache;
+pub mod circuit;
+#[cfg(feature = "network")]
+pub mod cli;
+#[cfg(feature = "network")]
+pub mod config;
+#[cfg(feature = "network")]
+pub mod discord;
+pub mod error;
+pub mod estimate;
+pub mod exec;
+#[cfg(feature = "network")]
+pub mod github;
+pub mod grafana;
+pub mod hardstop;
+pub mod logger;
+#[cfg(feature = "network")]
+pub mod mastodon;
+pub mod meter;
+pub mod metrics;
+pub mod preview;
+#[cfg(feature = "network")]
+pub mod ratelimit;
+#[cfg(feature = "network")]
+pub mod redact;
+pub mod shields;
+#[cfg(feature = "network")]
+pub mod slack;
+pub mod state;
+pub mod store;
+#[cfg(feature = "network")]
+pub mod summary;
+pub mod svg;
+#[cfg(feature = "network")]
+pub mod telegram;
+pub mod template;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "network")]
+pub mod twitter;
+pub mod util;
+#[cfg(feature = "network")]
+pub mod wakatime;
+#[cfg(feature = "network")]
+pub mod workhours;
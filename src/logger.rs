@@ -0,0 +1,102 @@
+use crate::util::get_env_var;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{
+    io::{self, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The level logged at when `RUST_LOG` isn't set.
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Whether everything goes to stderr, set by [`log_to_stderr`].
+static STDERR_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Writes this crate's records on a line each, warnings and errors to stderr
+/// and the rest to stdout, so the output is the same as before it was logged.
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // the HTTP client logs a lot at debug, which isn't ours to show
+        metadata.level() <= log::max_level()
+            && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match record.level() {
+            Level::Error | Level::Warn => eprintln!("{}", record.args()),
+            _ if STDERR_ONLY.load(Ordering::Relaxed) => eprintln!("{}", record.args()),
+            _ => println!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {
+        let _ = io::stdout().flush();
+    }
+}
+
+static LOGGER: Logger = Logger;
+
+/// Read the level from `RUST_LOG`, like `warn` or `debug`, or `off` for
+/// nothing at all.
+///
+/// # Examples
+///
+/// ```
+/// use log::LevelFilter;
+/// use rust_burnout_meter::logger::{parse_level, DEFAULT_LEVEL};
+///
+/// assert_eq!(parse_level(Some("warn")), LevelFilter::Warn);
+/// assert_eq!(parse_level(None), DEFAULT_LEVEL);
+/// ```
+pub fn parse_level(value: Option<&str>) -> LevelFilter {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_LEVEL)
+}
+
+/// Send everything logged from now on to stderr, leaving stdout to output
+/// meant for other programs, like the JSON of `OUTPUT_FORMAT=json`.
+pub fn log_to_stderr() {
+    STDERR_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Log at the level in `RUST_LOG`, or [`DEFAULT_LEVEL`] if it isn't set or
+/// isn't a level. `LOG_REQUESTS=true` without `RUST_LOG` logs at debug, for
+/// the requests.
+///
+/// Only the first call sets the logger, later ones do nothing.
+pub fn init() {
+    if log::set_logger(&LOGGER).is_err() {
+        return;
+    }
+
+    // set first so anything logged while reading `RUST_LOG` isn't lost
+    log::set_max_level(DEFAULT_LEVEL);
+
+    let level = get_env_var::<String>("RUST_LOG").ok().or_else(|| {
+        get_env_var("LOG_REQUESTS")
+            .unwrap_or(false)
+            .then(|| "debug".to_string())
+    });
+    log::set_max_level(parse_level(level.as_deref()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_level, DEFAULT_LEVEL};
+    use log::LevelFilter;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level(Some("debug")), LevelFilter::Debug);
+        assert_eq!(parse_level(Some("ERROR")), LevelFilter::Error);
+        assert_eq!(parse_level(Some(" off ")), LevelFilter::Off);
+        assert_eq!(parse_level(Some("loud")), DEFAULT_LEVEL);
+        assert_eq!(parse_level(None), DEFAULT_LEVEL);
+    }
+}
@@ -1,25 +1,119 @@
-//! Update your Twitter profile location with a meter of how close you are to burnout.
-//!
-//! Uses the [WakaTime API] to get the total time spent coding in the last 30 days.
-//! Then creates a meter with emoji to show how close you are to burnout.
-//! Finally, updates your Twitter profile location with the meter.
-//!
-//! [WakaTime API]: https://wakatime.com/developers
-
-mod app;
-mod meter;
-mod twitter;
-mod util;
-mod wakatime;
-
-use app::{App, AppSettings};
+//! The `rust-burnout-meter` command, a thin wrapper around the library.
+
+#[cfg(feature = "network")]
+use clap::Parser;
+#[cfg(feature = "network")]
+use rust_burnout_meter::{
+    app::{App, AppSettings, OutputFormat},
+    cli::{Args, Command},
+    config, logger,
+};
 use std::error::Error;
 
+#[cfg(feature = "network")]
 #[tokio::main(flavor = "current_thread")]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let settings = AppSettings::default();
+async fn main() {
+    logger::init();
+
+    // a panic is logged like any other error, rather than only to stderr
+    std::panic::set_hook(Box::new(|info| log::error!("{info}")));
+
+    if let Err(e) = run().await {
+        log::error!("{e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "network")]
+async fn run() -> Result<(), Box<dyn Error>> {
+    // parsed before the settings, so a typo doesn't need credentials to show
+    let args = Args::parse();
+
+    #[cfg(feature = "tui")]
+    if args.tui {
+        return rust_burnout_meter::tui::run();
+    }
+
+    // checked before loading any settings, so no credentials are needed
+    if let Some(path) = &args.validate_config {
+        let problems = config::validate(path);
+
+        if problems.is_empty() {
+            println!("{}: OK", path.display());
+
+            return Ok(());
+        }
+
+        for problem in &problems {
+            eprintln!("{}: {problem}", path.display());
+        }
+
+        std::process::exit(1);
+    }
+
+    let settings = AppSettings::load(&args)?;
+
+    // keep stdout to the JSON alone, so it can be piped
+    if settings.output_format() == OutputFormat::Json {
+        logger::log_to_stderr();
+    }
+    let daemon_interval = settings.daemon_interval();
     let mut app = App::new(settings)?;
-    app.run().await?;
+
+    match args.command() {
+        Command::Meter => return app.print_meter().await,
+        Command::Hours => return app.print_hours().await,
+        Command::Update => {}
+    }
+
+    if args.suggest_limit {
+        return app.suggest_limit().await;
+    }
+
+    if args.print {
+        return app.print_meter().await;
+    }
+
+    if args.shields {
+        return app.print_shields().await;
+    }
+
+    if args.svg {
+        return app.print_svg().await;
+    }
+
+    if args.grafana {
+        return app.print_grafana();
+    }
+
+    match daemon_interval {
+        Some(interval) => app.run_daemon(interval).await?,
+        None => {
+            app.run().await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Without the network features, just print the meter for the hours given as
+/// the first argument.
+#[cfg(not(feature = "network"))]
+fn main() -> Result<(), Box<dyn Error>> {
+    #[cfg(feature = "tui")]
+    if rust_burnout_meter::util::has_flag("--tui") {
+        return rust_burnout_meter::tui::run();
+    }
+
+    let hours: f64 = std::env::args()
+        .nth(1)
+        .ok_or("Usage: rust-burnout-meter HOURS")?
+        .parse()?;
+
+    let mut meter = rust_burnout_meter::meter::Builder::new();
+    meter.set_current(hours).build()?;
+
+    println!("{meter}");
 
     Ok(())
 }
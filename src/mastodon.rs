@@ -0,0 +1,347 @@
+use crate::{error::BurnoutError, util::http_client};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::from_str;
+use std::fmt;
+
+/// The profile field the meter goes in by default.
+pub const DEFAULT_FIELD: &str = "Burnout";
+
+/// The most fields Mastodon allows on a profile.
+pub const MAX_FIELDS: usize = 4;
+
+/// The parts of a Mastodon account's profile the update cares about.
+#[derive(Deserialize, Debug)]
+pub struct MastodonProfile {
+    #[serde(default)]
+    pub fields: Vec<Field>,
+}
+
+/// The account as the owner sees it, from `verify_credentials`.
+#[derive(Deserialize, Debug)]
+struct CredentialAccount {
+    #[serde(default)]
+    source: Source,
+}
+
+/// The profile as it was written, before Mastodon rendered it to HTML.
+#[derive(Deserialize, Debug, Default)]
+struct Source {
+    #[serde(default)]
+    fields: Vec<Field>,
+}
+
+/// A name and value shown in the profile's metadata table.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub value: String,
+}
+
+/// A Mastodon account on one instance, authorized with an access token.
+#[derive(Debug, Clone)]
+pub struct Mastodon {
+    client: Client,
+    base_url: Url,
+    token: String,
+}
+
+impl Mastodon {
+    pub fn new(base_url: &str, token: &str) -> Result<Self, BurnoutError> {
+        Self::with_client(base_url, token, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(base_url: &str, token: &str, client: Client) -> Result<Self, BurnoutError> {
+        let base_url = Url::parse(base_url).map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            base_url,
+            token: token.to_string(),
+        })
+    }
+
+    /// The instance this account is on.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Set the profile field called `name` to `value`, adding it after the
+    /// others if there isn't one yet.
+    ///
+    /// Mastodon replaces every field on an update, so the current fields are
+    /// read first and sent back with this one changed. They're read from the
+    /// account's `source`, as written, since the top-level fields are
+    /// rendered to HTML.
+    ///
+    /// https://docs.joinmastodon.org/methods/accounts/#update_credentials
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BurnoutError::MastodonFieldsFull`] without updating if the
+    /// profile already has [`MAX_FIELDS`] fields and none is called `name`,
+    /// or an error if either request fails or a response cannot be parsed.
+    pub async fn update_field(
+        &self,
+        name: &str,
+        value: &str,
+    ) -> Result<MastodonProfile, BurnoutError> {
+        let account: CredentialAccount = from_str(
+            &self
+                .client
+                .get(self.url("/api/v1/accounts/verify_credentials")?)
+                .bearer_auth(&self.token)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?,
+        )?;
+
+        let mut fields = account.source.fields;
+        let full = fields.len() >= MAX_FIELDS;
+        match fields.iter_mut().find(|field| field.name == name) {
+            Some(field) => field.value = value.to_string(),
+            None if full => return Err(BurnoutError::MastodonFieldsFull(name.to_string())),
+            None => fields.push(Field {
+                name: name.to_string(),
+                value: value.to_string(),
+            }),
+        }
+
+        let form: Vec<(String, String)> = fields
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, field)| {
+                [
+                    (format!("fields_attributes[{i}][name]"), field.name),
+                    (format!("fields_attributes[{i}][value]"), field.value),
+                ]
+            })
+            .collect();
+
+        let body = self
+            .client
+            .patch(self.url("/api/v1/accounts/update_credentials")?)
+            .bearer_auth(&self.token)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(from_str(&body)?)
+    }
+
+    fn url(&self, endpoint: &str) -> Result<Url, BurnoutError> {
+        self.base_url
+            .join(endpoint)
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))
+    }
+}
+
+/// How an update across every instance went.
+#[derive(Debug, Default)]
+pub struct Updates {
+    /// The instances that were updated.
+    pub updated: Vec<Url>,
+    /// The instances that failed, with the reason.
+    pub failed: Vec<(Url, BurnoutError)>,
+}
+
+impl Updates {
+    /// Whether some instances were updated but not all.
+    pub fn is_partial(&self) -> bool {
+        !self.updated.is_empty() && !self.failed.is_empty()
+    }
+}
+
+impl fmt::Display for Updates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.updated.len() + self.failed.len();
+
+        write!(
+            f,
+            "Updated {} of {total} Mastodon instances",
+            self.updated.len()
+        )
+    }
+}
+
+/// Set the field called `name` to `value` on every one of `instances`, one
+/// after another, carrying on past any that fail.
+pub async fn update_all(instances: &[Mastodon], name: &str, value: &str) -> Updates {
+    let mut updates = Updates::default();
+
+    for instance in instances {
+        match instance.update_field(name, value).await {
+            Ok(_) => updates.updated.push(instance.base_url.clone()),
+            Err(e) => updates.failed.push((instance.base_url.clone(), e)),
+        }
+    }
+
+    updates
+}
+
+/// Parse a `;` separated list of instances and their access tokens, e.g.
+/// `https://mastodon.social=abc123;https://hachyderm.io=def456`.
+///
+/// # Errors
+///
+/// Returns an error if an entry isn't `url=token`.
+pub fn parse_instances(value: &str) -> Result<Vec<(String, String)>, String> {
+    value
+        .split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((url, token)) if !token.trim().is_empty() => {
+                Ok((url.trim().to_string(), token.trim().to_string()))
+            }
+            _ => Err(format!("Failed to parse MASTODON_INSTANCES entry: {entry}")),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_instances, update_all, Mastodon, MAX_FIELDS};
+    use crate::error::BurnoutError;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+
+    #[tokio::test]
+    async fn test_update_field_keeps_other_fields() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let account = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/v1/accounts/verify_credentials")
+                .header("authorization", "Bearer token");
+            // the top-level fields are rendered, the source ones as written
+            then.status(200).body(
+                json!({
+                    "fields": [
+                        { "name": "Web", "value": "<a href=\"https://travisaw.com\">travisaw.com</a>" },
+                        { "name": "Burnout", "value": "🟩⬜️⬜️⬜️" },
+                    ],
+                    "source": { "fields": [
+                        { "name": "Web", "value": "travisaw.com" },
+                        { "name": "Burnout", "value": "🟩⬜️⬜️⬜️" },
+                    ] },
+                })
+                .to_string(),
+            );
+        });
+        let update = mock_server.mock(|when, then| {
+            when.method("PATCH")
+                .path("/api/v1/accounts/update_credentials")
+                .header("authorization", "Bearer token")
+                .x_www_form_urlencoded_tuple("fields_attributes[0][name]", "Web")
+                .x_www_form_urlencoded_tuple("fields_attributes[0][value]", "travisaw.com")
+                .x_www_form_urlencoded_tuple("fields_attributes[1][name]", "Burnout")
+                .x_www_form_urlencoded_tuple("fields_attributes[1][value]", "🟨🟨🟨⬜️");
+            then.status(200).body(
+                json!({ "fields": [
+                    { "name": "Web", "value": "travisaw.com" },
+                    { "name": "Burnout", "value": "🟨🟨🟨⬜️" },
+                ] })
+                .to_string(),
+            );
+        });
+
+        let mastodon = Mastodon::new(&mock_server.base_url(), "token")?;
+        let result = mastodon.update_field("Burnout", "🟨🟨🟨⬜️").await?;
+
+        account.assert();
+        update.assert();
+        assert_eq!(result.fields[1].value, "🟨🟨🟨⬜️");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_field_no_room() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let fields: Vec<_> = (1..=MAX_FIELDS)
+            .map(|i| json!({ "name": format!("Field {i}"), "value": "value" }))
+            .collect();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/accounts/verify_credentials");
+            then.status(200)
+                .body(json!({ "source": { "fields": fields } }).to_string());
+        });
+        let update = mock_server.mock(|when, then| {
+            when.method("PATCH")
+                .path("/api/v1/accounts/update_credentials");
+            then.status(422);
+        });
+
+        let mastodon = Mastodon::new(&mock_server.base_url(), "token")?;
+        let error = mastodon
+            .update_field("Burnout", "🟨🟨🟨⬜️")
+            .await
+            .unwrap_err();
+
+        update.assert_hits(0);
+        assert!(matches!(
+            error,
+            BurnoutError::MastodonFieldsFull(ref name) if name == "Burnout"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_all_partial() -> Result<(), Box<dyn Error>> {
+        let up = MockServer::start();
+        let down = MockServer::start();
+
+        let up_account = up.mock(|when, then| {
+            when.method(GET).path("/api/v1/accounts/verify_credentials");
+            then.status(200).body(json!({ "fields": [] }).to_string());
+        });
+        let up_update = up.mock(|when, then| {
+            when.method("PATCH")
+                .path("/api/v1/accounts/update_credentials");
+            then.status(200).body(json!({ "fields": [] }).to_string());
+        });
+        let down_account = down.mock(|when, then| {
+            when.method(GET).path("/api/v1/accounts/verify_credentials");
+            then.status(503);
+        });
+
+        // the failing instance comes first, so the other is still tried after it
+        let instances = [
+            Mastodon::new(&down.base_url(), "down")?,
+            Mastodon::new(&up.base_url(), "up")?,
+        ];
+        let updates = update_all(&instances, "Burnout", "🟧🟧🟧⬜️").await;
+
+        down_account.assert();
+        up_account.assert();
+        up_update.assert();
+        assert!(updates.is_partial());
+        assert_eq!(updates.to_string(), "Updated 1 of 2 Mastodon instances");
+        assert_eq!(updates.updated, [up.base_url().parse()?]);
+        assert_eq!(updates.failed[0].0, down.base_url().parse()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_instances() {
+        assert_eq!(
+            parse_instances("https://mastodon.social=abc; https://hachyderm.io=def;"),
+            Ok(vec![
+                ("https://mastodon.social".to_string(), "abc".to_string()),
+                ("https://hachyderm.io".to_string(), "def".to_string()),
+            ])
+        );
+        assert_eq!(parse_instances(""), Ok(vec![]));
+        assert!(parse_instances("https://mastodon.social").is_err());
+        assert!(parse_instances("https://mastodon.social=").is_err());
+    }
+}
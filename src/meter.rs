@@ -1,9 +1,453 @@
 use std::{
-    error::Error,
+    collections::HashMap,
     fmt::{Display, Formatter},
+    str::FromStr,
 };
 
-use crate::util::get_env_var;
+use crate::{
+    error::BurnoutError,
+    util::{get_env_var, shortcode},
+};
+use serde::{Deserialize, Serialize};
+
+/// How the meter glyphs are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeterStyle {
+    /// Colored square emoji, e.g. `🟩⬜️`.
+    #[default]
+    Emoji,
+    /// Emoji shortcodes, e.g. `:green_square::white_large_square:`.
+    Shortcode,
+    /// Plain characters with no color, e.g. `####----`, for platforms that
+    /// can't show the emoji.
+    Ascii { filled: char, empty: char },
+    /// Unicode blocks with an eighths block for the part of a cell that's
+    /// filled, e.g. `███▌    `, for more precision than whole cells.
+    ///
+    /// The blocks fill sideways, so a vertical meter gets whole cells.
+    SmoothBlocks,
+}
+
+/// A whole cell of [`MeterStyle::SmoothBlocks`].
+pub const FULL_BLOCK: char = '█';
+
+/// An empty cell of [`MeterStyle::SmoothBlocks`].
+pub const EMPTY_BLOCK: char = ' ';
+
+/// The blocks for one to seven eighths of a cell.
+const EIGHTH_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+impl MeterStyle {
+    /// How many terminal columns a cell takes up at most.
+    pub fn cell_width(&self) -> usize {
+        match self {
+            // emoji are drawn two columns wide
+            Self::Emoji => 2,
+            // the blank cell has the longest shortcode
+            Self::Shortcode => shortcode(BLANK).chars().count(),
+            Self::Ascii { .. } | Self::SmoothBlocks => 1,
+        }
+    }
+}
+
+impl FromStr for MeterStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "emoji" => Ok(Self::Emoji),
+            "shortcode" => Ok(Self::Shortcode),
+            "ascii" => Ok(Self::Ascii {
+                filled: '#',
+                empty: '-',
+            }),
+            "smooth" => Ok(Self::SmoothBlocks),
+            _ => Err(format!("Unknown meter style: {s}")),
+        }
+    }
+}
+
+/// Which way the meter cells run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Cells side by side, filling left to right.
+    #[default]
+    Horizontal,
+    /// One cell per line, filling bottom to top.
+    Vertical,
+}
+
+impl FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            _ => Err(format!("Unknown meter orientation: {s}")),
+        }
+    }
+}
+
+/// What the meter is counting, so the same meter can fill from hours or show
+/// them as a percentage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unit {
+    /// Hours out of the burnout limit, e.g. `22/40 hours`.
+    #[default]
+    Hours,
+    /// A percentage of the max, e.g. `55%`.
+    Percent,
+}
+
+impl Unit {
+    /// The label after the value.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Hours => "hours",
+            Self::Percent => "%",
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hours" => Ok(Self::Hours),
+            "percent" => Ok(Self::Percent),
+            _ => Err(format!("Unknown meter unit: {s}")),
+        }
+    }
+}
+
+/// The glyph used for the empty part of the meter.
+const BLANK: &str = "⬜️";
+
+/// The glyph that starts the meter while you're away.
+const AWAY: &str = "🌙";
+
+/// The glyph used for recovery below zero in a centered meter.
+const RECOVERY: &str = "🟦";
+
+/// The glyph repeated in place of the meter when there's no data by default.
+pub const DEFAULT_NO_DATA: &str = "❔";
+
+/// The fractions of max where the meter turns yellow, orange, and red.
+pub const DEFAULT_THRESHOLDS: [f64; 3] = [0.45, 0.7, 0.94];
+
+/// How many of `length` cells to fill for `current` out of `max`.
+///
+/// Negative values fill nothing. Meters of 10 or more cells round up once the
+/// current is more than halfway into the next whole number.
+fn filled_cells(current: f64, max: f64, length: u8) -> u8 {
+    if current <= 0f64 {
+        return 0;
+    }
+
+    // get the current without the decimal
+    let hours = current.floor();
+    let remainder = current - hours;
+    let percentage = current / max;
+    let mut filled = (percentage * length as f64).floor() as u8;
+
+    if remainder > 0.5 && length >= 10 && filled < length {
+        filled += 1;
+    }
+
+    // make sure the meter is never longer than the length set
+    if filled > length {
+        filled = length;
+    }
+
+    filled
+}
+
+/// Write `length` cells of blocks to `buf` with `cells` of them filled,
+/// ending in the eighths block for the part of a cell left over.
+///
+/// The part is rounded down to the eighth, so a cell is only full once it's
+/// all the way there.
+fn push_smooth_blocks(buf: &mut String, cells: f64, length: u8) {
+    let cells = cells.clamp(0f64, length as f64);
+    let full = cells.floor() as u8;
+    let eighths = ((cells - full as f64) * 8f64).floor() as usize;
+
+    for _ in 0..full {
+        buf.push(FULL_BLOCK);
+    }
+
+    let mut empty = length - full;
+
+    if eighths > 0 {
+        buf.push(EIGHTH_BLOCKS[eighths - 1]);
+        empty -= 1;
+    }
+
+    for _ in 0..empty {
+        buf.push(EMPTY_BLOCK);
+    }
+}
+
+/// How close to burnout the meter is, from the last build.
+///
+/// Bands are ordered from the freshest to the furthest over the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Band {
+    /// Up to 45% of max, 🟩.
+    Fresh,
+    /// Up to 70% of max, 🟨.
+    Busy,
+    /// Up to 94% of max, 🟧.
+    Heavy,
+    /// Up to max, 🟥.
+    Burnout,
+    /// Past max, 🟥.
+    Overflow,
+}
+
+impl Band {
+    /// Pick the band for how far along to burnout `percentage` is, where
+    /// `1.0` is the max.
+    pub fn from_percentage(percentage: f64) -> Self {
+        Self::from_fractions(percentage, DEFAULT_THRESHOLDS)
+    }
+
+    /// Pick the band for `percentage` against `[busy, heavy, burnout]`
+    /// fractions of the max.
+    pub fn from_fractions(percentage: f64, thresholds: [f64; 3]) -> Self {
+        Self::from_hours(percentage, 1.0, thresholds)
+    }
+
+    /// Pick the band for `hours` against absolute `[busy, heavy, burnout]`
+    /// hour boundaries.
+    pub fn from_hours(hours: f64, max: f64, [busy, heavy, burnout]: [f64; 3]) -> Self {
+        if hours > max {
+            Self::Overflow
+        } else if hours > burnout {
+            Self::Burnout
+        } else if hours > heavy {
+            Self::Heavy
+        } else if hours > busy {
+            Self::Busy
+        } else {
+            Self::Fresh
+        }
+    }
+
+    /// The emoji used for the filled part of the meter.
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            Self::Fresh => "🟩",
+            Self::Busy => "🟨",
+            Self::Heavy => "🟧",
+            Self::Burnout | Self::Overflow => "🟥",
+        }
+    }
+
+    /// The caption for the mood of the band, used when none is configured.
+    pub fn caption(&self) -> &'static str {
+        match self {
+            Self::Fresh => "Cruising",
+            Self::Busy => "Keeping busy",
+            Self::Heavy => "Ease off",
+            Self::Burnout | Self::Overflow => "Take a break!",
+        }
+    }
+}
+
+impl FromStr for Band {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fresh" => Ok(Self::Fresh),
+            "busy" => Ok(Self::Busy),
+            "heavy" => Ok(Self::Heavy),
+            "burnout" => Ok(Self::Burnout),
+            "overflow" => Ok(Self::Overflow),
+            _ => Err(format!("Unknown band: {s}")),
+        }
+    }
+}
+
+impl Display for Band {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Fresh => "fresh",
+            Self::Busy => "busy",
+            Self::Heavy => "heavy",
+            Self::Burnout => "burnout",
+            Self::Overflow => "overflow",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// The color of the meter, the four-way view of [`Band`] for dashboards
+/// that don't care whether it's at the max or past it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Zone {
+    Green,
+    Yellow,
+    Orange,
+    Red,
+}
+
+impl From<Band> for Zone {
+    fn from(band: Band) -> Self {
+        match band {
+            Band::Fresh => Self::Green,
+            Band::Busy => Self::Yellow,
+            Band::Heavy => Self::Orange,
+            Band::Burnout | Band::Overflow => Self::Red,
+        }
+    }
+}
+
+/// The caption for `band` from `captions`, or its default caption if it
+/// doesn't have one.
+pub fn caption(band: Band, captions: &HashMap<Band, String>) -> &str {
+    captions.get(&band).map_or(band.caption(), String::as_str)
+}
+
+/// Whether the last `away_days` or more days in `daily` have no activity at
+/// all, meaning you're away rather than just having a quiet week.
+pub fn is_away(daily: &[f64], away_days: usize) -> bool {
+    let idle = daily
+        .iter()
+        .rev()
+        .take_while(|hours| **hours <= 0f64)
+        .count();
+
+    away_days > 0 && idle >= away_days
+}
+
+/// How many cells fit in `columns` of terminal next to a `suffix` of that many
+/// columns, given how wide each cell is.
+///
+/// Without a terminal width, e.g. when the output isn't a terminal, it's the
+/// `fallback` length.
+pub fn fit_length(columns: Option<usize>, suffix: usize, cell_width: usize, fallback: u8) -> u8 {
+    match columns {
+        Some(columns) => {
+            let cells = columns.saturating_sub(suffix) / cell_width.max(1);
+
+            cells.clamp(1, u8::MAX as usize) as u8
+        }
+        None => fallback,
+    }
+}
+
+/// Render one cell per day of the week, Monday to Sunday, each colored by
+/// that day's hours against `daily_limit`.
+///
+/// `daily` holds the hours for each day so far this week, starting with
+/// Monday and ending with today. Today's cell is wrapped in brackets and the
+/// days still to come are left blank.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::meter::render_week;
+///
+/// let week = render_week(&[2.0, 8.0, 4.0], 8.0);
+/// assert_eq!(week, "🟩🟥[🟨]⬜️⬜️⬜️⬜️");
+/// ```
+pub fn render_week(daily: &[f64], daily_limit: f64) -> String {
+    let mut week = String::new();
+
+    for day in 0..7 {
+        match daily.get(day) {
+            Some(hours) => {
+                let emoji = Band::from_percentage(hours / daily_limit).glyph();
+
+                if day == daily.len() - 1 {
+                    week += &format!("[{emoji}]");
+                } else {
+                    week += emoji;
+                }
+            }
+            None => week += BLANK,
+        }
+    }
+
+    week
+}
+
+/// Short labels for the languages WakaTime names in full.
+const LANGUAGE_LABELS: [(&str, &str); 12] = [
+    ("Rust", "rs"),
+    ("Python", "py"),
+    ("Go", "go"),
+    ("JavaScript", "js"),
+    ("TypeScript", "ts"),
+    ("Ruby", "rb"),
+    ("Markdown", "md"),
+    ("Bash", "sh"),
+    ("C++", "cpp"),
+    ("C#", "cs"),
+    ("Kotlin", "kt"),
+    ("Haskell", "hs"),
+];
+
+/// The short label for a language, e.g. `rs` for `Rust`, or its name in
+/// lowercase if it has none.
+pub fn language_label(name: &str) -> String {
+    LANGUAGE_LABELS
+        .iter()
+        .find(|(language, _)| *language == name)
+        .map_or_else(|| name.to_lowercase(), |(_, label)| label.to_string())
+}
+
+/// Render a mini meter for each of the `top` languages, labelled and
+/// separated by spaces, e.g. `rs🟧🟧🟧 py🟨🟨 go🟩`.
+///
+/// `languages` holds the hours for each language, most first. Every meter is
+/// on the same scale, with `length` cells for `max` hours, so they can be
+/// compared. A language gets at least one cell, colored by its own hours
+/// against `max`, and the blanks are left off to keep it compact.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::meter::render_languages;
+///
+/// let languages = [("Rust".to_string(), 30.0), ("Python".to_string(), 19.0)];
+/// assert_eq!(render_languages(&languages, 40.0, 4, 3), "rs🟧🟧🟧 py🟨🟨");
+/// ```
+pub fn render_languages(languages: &[(String, f64)], max: f64, length: u8, top: usize) -> String {
+    languages
+        .iter()
+        .take(top)
+        .map(|(name, hours)| {
+            let cells = (hours / max * length as f64)
+                .round()
+                .clamp(1f64, length.max(1) as f64) as usize;
+            let glyph = Band::from_percentage(hours / max).glyph();
+
+            format!("{}{}", language_label(name), glyph.repeat(cells))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Render a row of `length` `glyph`s to show in place of the meter when
+/// there are no hours at all, so it isn't mistaken for an empty meter.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::meter::{render_no_data, DEFAULT_NO_DATA};
+///
+/// assert_eq!(render_no_data(4, DEFAULT_NO_DATA), "❔❔❔❔");
+/// ```
+pub fn render_no_data(length: u8, glyph: &str) -> String {
+    glyph.repeat(length as usize)
+}
 
 /// A struct to build a meter with emoji to show how close you are to code burnout.
 ///
@@ -12,8 +456,10 @@ use crate::util::get_env_var;
 /// # Examples
 ///
 /// ```
-/// use meter::Meter;
-/// let mut meter = Meter::new(Some(1.5)).build().unwrap();
+/// use rust_burnout_meter::meter::Builder;
+///
+/// let mut meter = Builder::new();
+/// meter.set_max(40).set_length(8).set_current(5).build().unwrap();
 /// assert_eq!(meter, "🟩⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
 /// ```
 #[derive(Debug, Clone)]
@@ -21,6 +467,21 @@ pub struct Builder {
     current: Option<f64>,
     max: f64,
     length: u8,
+    style: MeterStyle,
+    thresholds: [f64; 3],
+    hour_thresholds: Option<[f64; 3]>,
+    centered: bool,
+    reversed: bool,
+    orientation: Orientation,
+    unit: Unit,
+    away: bool,
+    fill_pattern: Vec<String>,
+    /// The glyph for each band from fresh to burnout, overflow sharing
+    /// burnout's.
+    band_glyphs: [String; 4],
+    /// The glyph for the empty part of the meter.
+    blank: String,
+    band: Band,
     meter: String,
 }
 
@@ -29,25 +490,149 @@ impl Builder {
         let current = Some(0 as f64);
         let max: f64 = get_env_var("BURNOUT_LIMIT").unwrap_or(160f64);
         let length = get_env_var("METER_LENGTH").unwrap_or(8u8);
-        let meter = Self::create_meter(current, max, length).unwrap_or_else(|_| {
-            panic!("Failed to create meter. Current value: {current:?}");
-        });
 
-        Self {
+        let mut builder = Self {
             current,
             max,
             length,
-            meter,
-        }
+            style: MeterStyle::default(),
+            thresholds: DEFAULT_THRESHOLDS,
+            hour_thresholds: None,
+            centered: false,
+            reversed: false,
+            orientation: Orientation::default(),
+            unit: Unit::default(),
+            away: false,
+            fill_pattern: Vec::new(),
+            band_glyphs: [Band::Fresh, Band::Busy, Band::Heavy, Band::Burnout]
+                .map(|band| band.glyph().to_string()),
+            blank: BLANK.to_string(),
+            band: Band::Fresh,
+            meter: String::new(),
+        };
+
+        builder.meter = builder.create_meter().unwrap_or_else(|_| {
+            panic!("Failed to create meter. Current value: {current:?}");
+        });
+
+        builder
     }
 
     /// Build the meter.
-    pub fn build(&mut self) -> Result<&mut Self, Box<dyn Error>> {
-        self.meter = Self::create_meter(self.current, self.max, self.length)?;
+    ///
+    /// The meter from the last build is written over, so its allocation is
+    /// reused rather than creating a new string every build.
+    pub fn build(&mut self) -> Result<&mut Self, BurnoutError> {
+        let mut meter = std::mem::take(&mut self.meter);
+        let result = self.create_meter_into(&mut meter);
+        self.meter = meter;
+        result?;
+
+        self.band = self.current_band();
 
         Ok(self)
     }
 
+    /// The band of the meter from the last build.
+    pub fn band(&self) -> Band {
+        self.band
+    }
+
+    /// The current value as a fraction of the max, e.g. `0.55`, going past
+    /// `1.0` over the max.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_current(22).set_max(40);
+    ///
+    /// assert_eq!(meter.percentage(), 0.55);
+    /// ```
+    pub fn percentage(&self) -> f64 {
+        self.current.unwrap_or(0f64) / self.max
+    }
+
+    /// `current` as a percent of `max`, e.g. `55.0` for 22 of 40, going past
+    /// `100.0` over the max.
+    ///
+    /// Every percent shown, from the location to the JSON output, is worked
+    /// out here, so they can't disagree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// assert_eq!(Builder::percent_of(45.0, 40.0), 112.5);
+    /// ```
+    pub fn percent_of(current: f64, max: f64) -> f64 {
+        current / max * 100f64
+    }
+
+    /// The current value as a percent of the max, by [`Builder::percent_of`].
+    pub fn percent(&self) -> f64 {
+        Self::percent_of(self.current.unwrap_or(0f64), self.max)
+    }
+
+    /// The percentage of the max as text for compact status lines, e.g.
+    /// `63%` for 25 of 40 hours.
+    ///
+    /// It's rounded half up to a whole percent and capped at `100%`, unlike
+    /// [`Builder::percentage`], so going over the max shows as full. A
+    /// negative current shows as `0%`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_current(25).set_max(40);
+    ///
+    /// assert_eq!(meter.percentage_string(), "63%");
+    /// ```
+    pub fn percentage_string(&self) -> String {
+        let percent = self.percent().clamp(0f64, 100f64);
+
+        format!("{}%", (percent + 0.5).floor())
+    }
+
+    /// The zone of the current value, by the same thresholds that color
+    /// the meter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::{Builder, Zone};
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_current(30).set_max(40);
+    ///
+    /// assert_eq!(meter.zone(), Zone::Orange);
+    /// ```
+    pub fn zone(&self) -> Zone {
+        self.current_band().into()
+    }
+
+    /// Whether the current value is in the red, at burnout or past the max,
+    /// from the same values as [`Builder::percentage`].
+    pub fn is_burnt_out(&self) -> bool {
+        self.zone() == Zone::Red
+    }
+
+    /// Work out the band for the current value, using the hour thresholds if set.
+    fn current_band(&self) -> Band {
+        let current = self.current.unwrap_or(0f64);
+
+        match self.hour_thresholds {
+            Some(thresholds) => Band::from_hours(current, self.max, thresholds),
+            None => Band::from_fractions(current / self.max, self.thresholds),
+        }
+    }
+
     /// Create a meter with emoji to show how close you are to burnout.
     ///
     /// # Errors
@@ -56,55 +641,155 @@ impl Builder {
     /// # Examples
     ///
     /// ```
-    /// use util::create_meter;
-    /// use std::error::Error;
+    /// use rust_burnout_meter::meter::Builder;
     ///
-    /// let meter = create_meter(Some(10f64), 100f64, 10)?;
+    /// let mut meter = Builder::new();
+    /// meter.set_current(10).set_max(100).set_length(10).build()?;
     /// assert_eq!(meter, "🟩⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    fn create_meter(current: Option<f64>, max: f64, length: u8) -> Result<String, Box<dyn Error>> {
-        if current.is_none() {
-            return Err("No current value".into());
-        }
+    fn create_meter(&self) -> Result<String, BurnoutError> {
+        let mut meter = String::new();
+        self.create_meter_into(&mut meter)?;
 
-        let current = current.unwrap();
+        Ok(meter)
+    }
 
-        // get the current without the decimal
-        let hours = current.floor();
-        let remainder = current - hours;
-        let percentage = current / max;
-        let mut filled = (percentage * length as f64).floor() as u8;
+    /// Create the meter like [`Builder::create_meter`], but write it into
+    /// `buf` so its allocation can be reused between builds.
+    ///
+    /// `buf` is cleared first.
+    ///
+    /// # Errors
+    /// Returns an error if the current value is `None`.
+    fn create_meter_into(&self, buf: &mut String) -> Result<(), BurnoutError> {
+        let (max, length) = (self.max, self.length);
 
-        if remainder > 0.5 && length >= 10 && filled < length {
-            filled += 1;
-        }
+        let current = match self.current {
+            Some(current) => current,
+            None => return Err(BurnoutError::InvalidMeterValue),
+        };
 
-        // make sure the meter is never longer than the length set
-        if filled > length {
-            filled = length;
-        }
+        buf.clear();
 
-        let empty = length - filled;
+        if self.style == MeterStyle::SmoothBlocks
+            && self.orientation == Orientation::Horizontal
+            && !self.away
+            && !self.centered
+        {
+            let used = current / max * length as f64;
 
-        let mut emoji = String::from("🟩");
+            match self.reversed {
+                true => push_smooth_blocks(buf, length as f64 - used, length),
+                false => push_smooth_blocks(buf, used, length),
+            }
 
-        if percentage > 0.45 {
-            emoji = String::from("🟨");
+            return Ok(());
         }
 
-        if percentage > 0.7 {
-            emoji = String::from("🟧");
-        }
+        let emoji = [self.band_glyph(self.current_band())];
+        let fill: Vec<&str> = self.fill_pattern.iter().map(String::as_str).collect();
+        let fill = if fill.is_empty() {
+            &emoji[..]
+        } else {
+            &fill[..]
+        };
+
+        // runs of cells from the start of the meter to the end
+        let runs = if self.away {
+            let away = length.min(1);
 
-        if percentage > 0.94 {
-            emoji = String::from("🟥");
+            [
+                (&[AWAY][..], away),
+                (&[BLANK][..], length - away),
+                (&[BLANK][..], 0),
+                (&[BLANK][..], 0),
+            ]
+        } else if self.centered {
+            // zero is in the middle, recovery (a negative current) fills blue
+            // leftward from it and burnout fills rightward, with the extra
+            // cell of an odd length going to the burnout side
+            let left = length / 2;
+            let right = length - left;
+
+            let recovery = filled_cells(-current, max, left);
+            let burnout = filled_cells(current, max, right);
+
+            [
+                (&[BLANK][..], left - recovery),
+                (&[RECOVERY][..], recovery),
+                (fill, burnout),
+                (&[BLANK][..], right - burnout),
+            ]
+        } else {
+            let filled = match self.reversed {
+                // what's left of the budget, as the cells the current would
+                // leave blank
+                true => length - filled_cells(current, max, length),
+                false => filled_cells(current, max, length),
+            };
+
+            [
+                (fill, filled),
+                (&[BLANK][..], length - filled),
+                (&[BLANK][..], 0),
+                (&[BLANK][..], 0),
+            ]
+        };
+
+        match self.orientation {
+            Orientation::Horizontal => {
+                for (glyphs, count) in runs {
+                    self.push_cells(buf, glyphs, count);
+                }
+            }
+            // the start of the meter is at the bottom, so write from the end
+            Orientation::Vertical => {
+                for (glyphs, count) in runs.into_iter().rev() {
+                    self.push_cells(buf, glyphs, count);
+                }
+            }
         }
 
-        let blank = "⬜️";
+        Ok(())
+    }
 
-        let meter = emoji.repeat(filled as usize) + &blank.repeat(empty as usize);
+    /// Write `count` cells to `buf` in the meter's style, cycling through
+    /// `glyphs` from the start of the run, one per line if the meter is
+    /// vertical.
+    fn push_cells(&self, buf: &mut String, glyphs: &[&str], count: u8) {
+        for cell in 0..count {
+            // a vertical run is written from its end
+            let cell = match self.orientation {
+                Orientation::Horizontal => cell,
+                Orientation::Vertical => count - 1 - cell,
+            };
+            let glyph = glyphs[cell as usize % glyphs.len()];
 
-        Ok(meter)
+            if self.orientation == Orientation::Vertical && !buf.is_empty() {
+                buf.push('\n');
+            }
+
+            // the empty cells are only told apart by the constant
+            let emoji = match glyph {
+                BLANK => &self.blank,
+                glyph => glyph,
+            };
+
+            match self.style {
+                MeterStyle::Emoji => buf.push_str(emoji),
+                MeterStyle::Shortcode => buf.push_str(shortcode(emoji)),
+                // no colors, only whether the cell is filled
+                MeterStyle::Ascii { filled, empty } => match glyph {
+                    BLANK => buf.push(empty),
+                    _ => buf.push(filled),
+                },
+                MeterStyle::SmoothBlocks => match glyph {
+                    BLANK => buf.push(EMPTY_BLOCK),
+                    _ => buf.push(FULL_BLOCK),
+                },
+            }
+        }
     }
 
     /// Set the current value.
@@ -128,33 +813,251 @@ impl Builder {
         self
     }
 
-    pub fn max(&self) -> &f64 {
-        &self.max
+    /// Set how the meter glyphs are rendered.
+    pub fn set_style(&mut self, style: MeterStyle) -> &mut Self {
+        self.style = style;
+
+        self
     }
-}
 
-impl Display for Builder {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let meter = &self.meter;
+    /// Set the fractions of max where the meter turns yellow, orange, and
+    /// red, in place of the 45%, 70%, and 94% defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fractions aren't ascending or aren't all
+    /// within `0.0..=1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_max(10).set_length(10).set_current(5);
+    /// meter.set_thresholds(0.6, 0.8, 0.95)?.build()?;
+    /// assert_eq!(meter, "🟩🟩🟩🟩🟩⬜️⬜️⬜️⬜️⬜️");
+    ///
+    /// assert!(meter.set_thresholds(0.8, 0.6, 0.95).is_err());
+    /// # Ok::<(), rust_burnout_meter::error::BurnoutError>(())
+    /// ```
+    pub fn set_thresholds(
+        &mut self,
+        yellow: f64,
+        orange: f64,
+        red: f64,
+    ) -> Result<&mut Self, BurnoutError> {
+        let thresholds = [yellow, orange, red];
 
-        write!(f, "{meter}")
+        let in_range = thresholds.iter().all(|t| (0.0..=1.0).contains(t));
+        if !in_range || yellow >= orange || orange >= red {
+            return Err(BurnoutError::InvalidThresholds(thresholds));
+        }
+
+        self.thresholds = thresholds;
+
+        Ok(self)
     }
-}
 
-// allows comparing meter to a String or &str
-impl PartialEq<String> for Builder {
-    fn eq(&self, other: &String) -> bool {
-        self.meter == *other
+    /// Color the meter by absolute hours instead of the percentage of max,
+    /// switching to yellow, orange, and red above each boundary.
+    pub fn set_hour_thresholds(&mut self, thresholds: Option<[f64; 3]>) -> &mut Self {
+        self.hour_thresholds = thresholds;
+
+        self
     }
-}
 
-impl PartialEq<&str> for Builder {
-    fn eq(&self, other: &&str) -> bool {
-        self.meter == *other
+    /// Put zero in the middle of the meter, so negative values can show
+    /// recovery to the left and positive values burnout to the right.
+    pub fn set_centered(&mut self, centered: bool) -> &mut Self {
+        self.centered = centered;
+
+        self
     }
-}
 
-impl PartialEq<str> for Builder {
+    /// Fill the meter with the budget left instead of the value used, so it's
+    /// full at zero and empties toward the max. The fill is still colored by
+    /// how close the current is to burnout, so it turns red as it empties.
+    ///
+    /// With [`MeterStyle::SmoothBlocks`] the last block shows the part of a
+    /// cell left rather than the part used. A centered or away meter isn't
+    /// reversed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_max(40).set_length(4).set_reversed(true);
+    ///
+    /// meter.set_current(0).build()?;
+    /// assert_eq!(meter, "🟩🟩🟩🟩");
+    /// meter.set_current(30).build()?;
+    /// assert_eq!(meter, "🟧⬜️⬜️⬜️");
+    /// # Ok::<(), rust_burnout_meter::error::BurnoutError>(())
+    /// ```
+    pub fn set_reversed(&mut self, reversed: bool) -> &mut Self {
+        self.reversed = reversed;
+
+        self
+    }
+
+    /// Set which way the meter cells run.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> &mut Self {
+        self.orientation = orientation;
+
+        self
+    }
+
+    /// Show the away state instead of the current value.
+    pub fn set_away(&mut self, away: bool) -> &mut Self {
+        self.away = away;
+
+        self
+    }
+
+    /// Fill the meter by cycling through `pattern`, e.g. `🟩🟢` for a striped
+    /// look. An empty pattern, the default, fills with the glyph of the band.
+    pub fn set_fill_pattern(&mut self, pattern: Vec<String>) -> &mut Self {
+        self.fill_pattern = pattern;
+
+        self
+    }
+
+    /// Replace the glyph for each band and the empty cells, e.g. batteries
+    /// for a different look. Overflow uses the `red` glyph, like burnout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BurnoutError::EmptyEmoji`] if any of them is empty, and the
+    /// glyphs are left as they were.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_max(40).set_length(4).set_current(22);
+    /// meter.set_emojis("💚", "💛", "🧡", "❤️", "🤍")?.build()?;
+    /// assert_eq!(meter, "💛💛🤍🤍");
+    ///
+    /// assert!(meter.set_emojis("💚", "", "🧡", "❤️", "🤍").is_err());
+    /// # Ok::<(), rust_burnout_meter::error::BurnoutError>(())
+    /// ```
+    pub fn set_emojis(
+        &mut self,
+        green: &str,
+        yellow: &str,
+        orange: &str,
+        red: &str,
+        blank: &str,
+    ) -> Result<&mut Self, BurnoutError> {
+        let glyphs = [green, yellow, orange, red, blank];
+
+        if glyphs.iter().any(|glyph| glyph.is_empty()) {
+            return Err(BurnoutError::EmptyEmoji);
+        }
+
+        self.band_glyphs = [green, yellow, orange, red].map(str::to_string);
+        self.blank = blank.to_string();
+
+        Ok(self)
+    }
+
+    /// The glyph the meter fills with in `band`.
+    fn band_glyph(&self, band: Band) -> &str {
+        match band {
+            Band::Fresh => &self.band_glyphs[0],
+            Band::Busy => &self.band_glyphs[1],
+            Band::Heavy => &self.band_glyphs[2],
+            Band::Burnout | Band::Overflow => &self.band_glyphs[3],
+        }
+    }
+
+    /// Set what the current and max values are counting.
+    pub fn set_unit(&mut self, unit: Unit) -> &mut Self {
+        self.unit = unit;
+
+        self
+    }
+
+    pub fn max(&self) -> &f64 {
+        &self.max
+    }
+
+    /// The current value, or `None` if it was never set.
+    pub fn current(&self) -> Option<f64> {
+        self.current
+    }
+
+    /// How many cells the current value fills out of the length, whichever
+    /// way the meter is drawn, e.g. `4` for 22 of 40 hours in 8 cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_burnout_meter::meter::Builder;
+    ///
+    /// let mut meter = Builder::new();
+    /// meter.set_current(22).set_max(40).set_length(8);
+    ///
+    /// assert_eq!(meter.filled(), 4);
+    /// ```
+    pub fn filled(&self) -> u8 {
+        filled_cells(self.current.unwrap_or(0f64), self.max, self.length)
+    }
+
+    /// How many cells the meter has.
+    pub fn length(&self) -> u8 {
+        self.length
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// The current value out of the max in the meter's unit, e.g.
+    /// `22/40 hours` or `55%`, the percent as [`Builder::percentage_string`].
+    pub fn quantity(&self) -> String {
+        let current = self.current.unwrap_or(0f64);
+
+        match self.unit {
+            Unit::Percent => self.percentage_string(),
+            Unit::Hours => format!("{}/{} {}", current.round(), self.max, self.unit.label()),
+        }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for Builder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let meter = &self.meter;
+
+        write!(f, "{meter}")
+    }
+}
+
+// allows comparing meter to a String or &str
+impl PartialEq<String> for Builder {
+    fn eq(&self, other: &String) -> bool {
+        self.meter == *other
+    }
+}
+
+impl PartialEq<&str> for Builder {
+    fn eq(&self, other: &&str) -> bool {
+        self.meter == *other
+    }
+}
+
+impl PartialEq<str> for Builder {
     fn eq(&self, other: &str) -> bool {
         self.meter == other
     }
@@ -163,6 +1066,7 @@ impl PartialEq<str> for Builder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::error::Error;
 
     #[test]
     fn test_create_meter() -> Result<(), Box<dyn Error>> {
@@ -191,4 +1095,622 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_create_meter_shortcode_style() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+
+        meter
+            .set_current(5)
+            .set_length(4)
+            .set_max(10)
+            .set_style(MeterStyle::Shortcode)
+            .build()?;
+        assert_eq!(
+            meter,
+            ":yellow_square::yellow_square::white_large_square::white_large_square:"
+        );
+
+        meter.set_style(MeterStyle::Emoji).build()?;
+        assert_eq!(meter, "🟨🟨⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_meter_ascii_style() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+
+        meter
+            .set_current(5)
+            .set_max(10)
+            .set_length(10)
+            .set_style(MeterStyle::Ascii {
+                filled: '#',
+                empty: '-',
+            })
+            .build()?;
+        assert_eq!(meter, "#####-----");
+
+        // the same whatever the band
+        meter.set_current(10).build()?;
+        assert_eq!(meter, "##########");
+
+        assert_eq!(
+            "ascii".parse::<MeterStyle>(),
+            Ok(MeterStyle::Ascii {
+                filled: '#',
+                empty: '-'
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_band_matches_the_glyphs() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_max(100).set_length(100);
+
+        // either side of each default threshold of 45%, 70% and 94%
+        let expected = [
+            (45.0, Band::Fresh, Zone::Green, "🟩"),
+            (45.01, Band::Busy, Zone::Yellow, "🟨"),
+            (70.0, Band::Busy, Zone::Yellow, "🟨"),
+            (70.01, Band::Heavy, Zone::Orange, "🟧"),
+            (94.0, Band::Heavy, Zone::Orange, "🟧"),
+            (94.01, Band::Burnout, Zone::Red, "🟥"),
+            (100.0, Band::Burnout, Zone::Red, "🟥"),
+            (100.01, Band::Overflow, Zone::Red, "🟥"),
+        ];
+
+        for (current, band, zone, glyph) in expected {
+            meter.set_current(current).build()?;
+
+            assert_eq!(meter.band(), band, "{current}");
+            assert_eq!(meter.zone(), zone, "{current}");
+            assert!(meter.to_string().starts_with(glyph), "{current}");
+            assert_eq!(meter.is_burnt_out(), band >= Band::Burnout, "{current}");
+            assert_eq!(meter.percentage(), current / 100.0);
+        }
+
+        // and the custom thresholds too
+        meter.set_thresholds(0.5, 0.6, 0.7)?;
+        meter.set_current(70.01).build()?;
+        assert!(meter.is_burnt_out());
+        assert!(meter.to_string().starts_with("🟥"));
+
+        // the status follows the current value before it's built, like the
+        // percentage does
+        meter.set_current(10);
+        assert!(!meter.is_burnt_out());
+        assert_eq!(meter.zone(), Zone::Green);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_meter_smooth_blocks() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter
+            .set_max(8)
+            .set_length(8)
+            .set_style(MeterStyle::SmoothBlocks);
+
+        meter.set_current(3.5).build()?;
+        assert_eq!(meter, "███▌    ");
+
+        // every eighth has its block
+        meter.set_current(0.125).build()?;
+        assert_eq!(meter, "▏       ");
+        meter.set_current(7.875).build()?;
+        assert_eq!(meter, "███████▉");
+
+        // all empty, and all full with no partial block after
+        meter.set_current(0).build()?;
+        assert_eq!(meter, "        ");
+        meter.set_current(8).build()?;
+        assert_eq!(meter, "████████");
+        meter.set_current(12).build()?;
+        assert_eq!(meter, "████████");
+
+        assert_eq!("smooth".parse::<MeterStyle>(), Ok(MeterStyle::SmoothBlocks));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_meter_reversed() -> Result<(), Box<dyn Error>> {
+        let mut normal = Builder::new();
+        normal.set_max(40).set_length(8);
+        let mut reversed = normal.clone();
+        reversed.set_reversed(true);
+
+        for (current, expected_normal, expected_reversed) in [
+            (0.0, "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️", "🟩🟩🟩🟩🟩🟩🟩🟩"),
+            (20.0, "🟨🟨🟨🟨⬜️⬜️⬜️⬜️", "🟨🟨🟨🟨⬜️⬜️⬜️⬜️"),
+            (35.0, "🟧🟧🟧🟧🟧🟧🟧⬜️", "🟧⬜️⬜️⬜️⬜️⬜️⬜️⬜️"),
+            (40.0, "🟥🟥🟥🟥🟥🟥🟥🟥", "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️"),
+        ] {
+            normal.set_current(current).build()?;
+            reversed.set_current(current).build()?;
+
+            assert_eq!(normal, expected_normal, "{current}h");
+            assert_eq!(reversed, expected_reversed, "{current}h");
+            // the danger is the same either way
+            assert_eq!(reversed.band(), normal.band());
+        }
+
+        reversed
+            .set_max(8)
+            .set_length(8)
+            .set_style(MeterStyle::SmoothBlocks)
+            .set_current(3.5)
+            .build()?;
+        assert_eq!(reversed, "████▌   ");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_meter_custom_thresholds() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+
+        meter
+            .set_max(20)
+            .set_length(10)
+            .set_thresholds(0.6, 0.8, 0.95)?;
+
+        // each color starts just past its threshold
+        meter.set_current(12).build()?;
+        assert_eq!(meter, "🟩🟩🟩🟩🟩🟩⬜️⬜️⬜️⬜️");
+        meter.set_current(12.2).build()?;
+        assert_eq!(meter, "🟨🟨🟨🟨🟨🟨⬜️⬜️⬜️⬜️");
+
+        meter.set_current(16).build()?;
+        assert_eq!(meter, "🟨🟨🟨🟨🟨🟨🟨🟨⬜️⬜️");
+        meter.set_current(16.2).build()?;
+        assert_eq!(meter, "🟧🟧🟧🟧🟧🟧🟧🟧⬜️⬜️");
+
+        meter.set_current(19).build()?;
+        assert_eq!(meter, "🟧🟧🟧🟧🟧🟧🟧🟧🟧⬜️");
+        meter.set_current(19.2).build()?;
+        assert_eq!(meter, "🟥🟥🟥🟥🟥🟥🟥🟥🟥⬜️");
+        assert_eq!(meter.band(), Band::Burnout);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_thresholds_validates() {
+        let mut meter = Builder::new();
+
+        assert!(meter.set_thresholds(0.7, 0.45, 0.94).is_err());
+        assert!(meter.set_thresholds(0.45, 0.45, 0.94).is_err());
+        assert!(meter.set_thresholds(-0.1, 0.7, 0.94).is_err());
+        assert!(meter.set_thresholds(0.45, 0.7, 1.2).is_err());
+
+        // a rejected set keeps the defaults, where 50% is yellow
+        meter.set_max(40).set_length(4).set_current(20);
+        assert_eq!(meter.build().unwrap().band(), Band::Busy);
+    }
+
+    #[test]
+    fn test_create_meter_hour_thresholds() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+
+        meter
+            .set_max(40)
+            .set_length(4)
+            .set_hour_thresholds(Some([20.0, 30.0, 38.0]));
+
+        meter.set_current(20).build()?;
+        assert_eq!(meter, "🟩🟩⬜️⬜️");
+
+        meter.set_current(21).build()?;
+        assert_eq!(meter, "🟨🟨⬜️⬜️");
+
+        meter.set_current(31).build()?;
+        assert_eq!(meter, "🟧🟧🟧⬜️");
+
+        meter.set_current(38.5).build()?;
+        assert_eq!(meter, "🟥🟥🟥⬜️");
+
+        // back to the percentage defaults, where half of max is yellow
+        meter.set_current(20).set_hour_thresholds(None).build()?;
+        assert_eq!(meter, "🟨🟨⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_meter_negative() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+
+        meter.set_current(-2.7).set_max(10).set_length(10).build()?;
+        assert_eq!(meter, "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_meter_centered() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_max(10).set_length(8).set_centered(true);
+
+        meter.set_current(0).build()?;
+        assert_eq!(meter, "⬜️⬜️⬜️⬜️⬜️⬜️⬜️⬜️");
+
+        // recovery grows leftward from the middle
+        meter.set_current(-5).build()?;
+        assert_eq!(meter, "⬜️⬜️🟦🟦⬜️⬜️⬜️⬜️");
+
+        meter.set_current(-10).build()?;
+        assert_eq!(meter, "🟦🟦🟦🟦⬜️⬜️⬜️⬜️");
+
+        // burnout grows rightward from the middle
+        meter.set_current(5).build()?;
+        assert_eq!(meter, "⬜️⬜️⬜️⬜️🟨🟨⬜️⬜️");
+
+        meter.set_current(10).build()?;
+        assert_eq!(meter, "⬜️⬜️⬜️⬜️🟥🟥🟥🟥");
+
+        // an odd length gives the extra cell to the burnout side
+        meter.set_length(5).set_current(-10).build()?;
+        assert_eq!(meter, "🟦🟦⬜️⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_band() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_max(100).set_length(10);
+
+        let cases = [
+            (0.0, Band::Fresh),
+            (45.0, Band::Fresh),
+            (45.5, Band::Busy),
+            (70.0, Band::Busy),
+            (71.0, Band::Heavy),
+            (94.0, Band::Heavy),
+            (95.0, Band::Burnout),
+            (100.0, Band::Burnout),
+            (120.0, Band::Overflow),
+        ];
+
+        for (current, band) in cases {
+            meter.set_current(current).build()?;
+            assert_eq!(meter.band(), band, "wrong band for {current}");
+        }
+
+        // the band follows the hour thresholds when they're set
+        meter
+            .set_max(40)
+            .set_hour_thresholds(Some([20.0, 30.0, 38.0]))
+            .set_current(25)
+            .build()?;
+        assert_eq!(meter.band(), Band::Busy);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_week() {
+        // Wednesday, with a light Monday and a heavy Tuesday
+        assert_eq!(render_week(&[1.0, 7.5, 4.0], 8.0), "🟩🟧[🟨]⬜️⬜️⬜️⬜️");
+
+        // Monday, nothing coded yet
+        assert_eq!(render_week(&[0.0], 8.0), "[🟩]⬜️⬜️⬜️⬜️⬜️⬜️");
+
+        // Sunday, every day is in the past or today
+        let daily = [8.0, 8.0, 8.0, 8.0, 8.0, 2.0, 0.0];
+        assert_eq!(render_week(&daily, 8.0), "🟥🟥🟥🟥🟥🟩[🟩]");
+    }
+
+    #[test]
+    fn test_render_languages() {
+        let languages = [
+            ("Rust".to_string(), 24.0),
+            ("Python".to_string(), 12.0),
+            ("Go".to_string(), 1.0),
+            ("Elixir".to_string(), 0.5),
+        ];
+
+        // 6 cells for 40 hours, with a little still getting a cell
+        assert_eq!(
+            render_languages(&languages, 40.0, 6, 3),
+            "rs🟨🟨🟨🟨 py🟩🟩 go🟩"
+        );
+
+        // only the top languages
+        assert_eq!(render_languages(&languages, 40.0, 6, 1), "rs🟨🟨🟨🟨");
+
+        // past the max stops at the length
+        let languages = [("Elixir".to_string(), 60.0)];
+        assert_eq!(render_languages(&languages, 40.0, 3, 3), "elixir🟥🟥🟥");
+    }
+
+    #[test]
+    fn test_create_meter_into_reuses_buffer() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_max(10).set_length(10);
+
+        for current in [0.0, 3.2, 5.5, 7.1, 9.4, 12.0, -3.0] {
+            meter.set_current(current);
+
+            let mut buf = String::from("left over from before");
+            meter.create_meter_into(&mut buf)?;
+            assert_eq!(buf, meter.create_meter()?, "mismatch for {current}");
+        }
+
+        for style in [MeterStyle::Emoji, MeterStyle::Shortcode] {
+            meter.set_style(style).set_centered(true).set_current(-4.0);
+
+            let mut buf = String::new();
+            meter.create_meter_into(&mut buf)?;
+            assert_eq!(buf, meter.create_meter()?, "mismatch for {style:?}");
+        }
+
+        // repeated builds write into the same allocation
+        meter
+            .set_style(MeterStyle::Emoji)
+            .set_centered(false)
+            .set_current(2)
+            .build()?;
+        let capacity = meter.meter.capacity();
+        let ptr = meter.meter.as_ptr();
+
+        for current in [4.0, 6.0, 8.0, 10.0] {
+            meter.set_current(current).build()?;
+            assert_eq!(meter.meter.capacity(), capacity);
+            assert_eq!(meter.meter.as_ptr(), ptr);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vertical_meter() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter
+            .set_max(40)
+            .set_length(5)
+            .set_orientation(Orientation::Vertical)
+            .set_current(16)
+            .build()?;
+
+        let lines: Vec<&str> = meter.meter.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines, ["⬜️", "⬜️", "⬜️", "🟩", "🟩"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_units() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+
+        // an hours source
+        meter
+            .set_length(5)
+            .set_max(40)
+            .set_unit(Unit::Hours)
+            .set_current(22)
+            .build()?;
+
+        assert_eq!(meter, "🟨🟨⬜️⬜️⬜️");
+        assert_eq!(meter.quantity(), "22/40 hours");
+
+        // a different target
+        meter.set_max(30).set_current(24).build()?;
+
+        assert_eq!(meter, "🟧🟧🟧🟧⬜️");
+        assert_eq!(meter.quantity(), "24/30 hours");
+
+        meter.set_unit(Unit::Percent);
+
+        assert_eq!(meter.quantity(), "80%");
+
+        // full past the max, as the meter is
+        meter.set_current(36);
+        assert_eq!(meter.quantity(), "100%");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fit_length() {
+        // 80 columns less a 12 column suffix like ` 22/40 hours` fits 34 emoji
+        assert_eq!(
+            fit_length(Some(80), 12, MeterStyle::Emoji.cell_width(), 8),
+            34
+        );
+        assert_eq!(
+            fit_length(Some(80), 12, MeterStyle::Shortcode.cell_width(), 8),
+            3
+        );
+        // always at least one cell, even in a tiny terminal
+        assert_eq!(fit_length(Some(10), 12, 2, 8), 1);
+        // not a terminal
+        assert_eq!(fit_length(None, 12, 2, 8), 8);
+    }
+
+    #[test]
+    fn test_is_away() {
+        // three idle days to finish
+        assert!(is_away(&[6.0, 4.5, 0.0, 0.0, 0.0], 3));
+        // scattered idle days aren't away
+        assert!(!is_away(&[0.0, 4.5, 0.0, 3.0, 0.0], 3));
+        assert!(!is_away(&[6.0, 4.5, 2.0, 0.0, 0.0], 3));
+    }
+
+    #[test]
+    fn test_away_meter() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter
+            .set_length(5)
+            .set_max(40)
+            .set_current(22)
+            .set_away(true)
+            .build()?;
+
+        assert_eq!(meter, "🌙⬜️⬜️⬜️⬜️");
+
+        meter.set_style(MeterStyle::Shortcode).build()?;
+
+        assert_eq!(
+            meter.to_string(),
+            ":crescent_moon::white_large_square::white_large_square::white_large_square::white_large_square:"
+        );
+
+        Ok(())
+    }
+
+    /// The meter has to keep working with `--no-default-features`, so this
+    /// only uses what's left without the network features.
+    #[test]
+    fn test_render_standalone() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_max(40).set_length(10).set_current(30).build()?;
+
+        assert_eq!(meter, "🟧🟧🟧🟧🟧🟧🟧⬜️⬜️⬜️");
+        assert_eq!(meter.band(), Band::Heavy);
+        assert_eq!(meter.quantity(), "30/40 hours");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fill_pattern() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter
+            .set_max(8)
+            .set_length(8)
+            .set_fill_pattern(vec!["🟩".to_string(), "🟢".to_string()]);
+
+        // an even fill ends on the second glyph
+        meter.set_current(2).build()?;
+        assert_eq!(meter, "🟩🟢⬜️⬜️⬜️⬜️⬜️⬜️");
+
+        meter.set_current(4).build()?;
+        assert_eq!(meter, "🟩🟢🟩🟢⬜️⬜️⬜️⬜️");
+
+        // an odd fill ends back on the first
+        meter.set_current(3).build()?;
+        assert_eq!(meter, "🟩🟢🟩⬜️⬜️⬜️⬜️⬜️");
+
+        // the pattern starts at the bottom of a vertical meter
+        meter.set_orientation(Orientation::Vertical).build()?;
+        assert_eq!(meter, "⬜️\n⬜️\n⬜️\n⬜️\n⬜️\n🟩\n🟢\n🟩");
+
+        // an empty pattern goes back to the glyph of the band
+        meter
+            .set_orientation(Orientation::Horizontal)
+            .set_fill_pattern(Vec::new())
+            .build()?;
+        assert_eq!(meter, "🟩🟩🟩⬜️⬜️⬜️⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_percentage_string() {
+        let mut meter = Builder::new();
+        meter.set_max(40);
+
+        for (current, expected) in [
+            (0.0, "0%"),
+            (25.0, "63%"),
+            (24.9, "62%"),
+            (40.0, "100%"),
+            (55.0, "100%"),
+            (-4.0, "0%"),
+        ] {
+            meter.set_current(current);
+            assert_eq!(meter.percentage_string(), expected, "at {current} hours");
+        }
+    }
+
+    #[test]
+    fn test_set_emojis() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter
+            .set_max(40)
+            .set_length(4)
+            .set_emojis("🔋", "🔌", "🪫", "💀", "▫️")?;
+
+        for (current, expected) in [
+            (0, "▫️▫️▫️▫️"),
+            (10, "🔋▫️▫️▫️"),
+            (22, "🔌🔌▫️▫️"),
+            (30, "🪫🪫🪫▫️"),
+            (40, "💀💀💀💀"),
+            (50, "💀💀💀💀"),
+        ] {
+            meter.set_current(current).build()?;
+            assert_eq!(meter, expected, "at {current} hours");
+        }
+
+        // ascii is still only filled or not
+        meter
+            .set_style(MeterStyle::Ascii {
+                filled: '#',
+                empty: '-',
+            })
+            .set_current(22)
+            .build()?;
+        assert_eq!(meter, "##--");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_emojis_empty() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_max(40).set_length(4).set_current(22);
+
+        for emojis in [["", "🔌", "🪫", "💀", "▫️"], ["🔋", "🔌", "🪫", "💀", ""]]
+        {
+            let [green, yellow, orange, red, blank] = emojis;
+
+            assert!(matches!(
+                meter.set_emojis(green, yellow, orange, red, blank),
+                Err(BurnoutError::EmptyEmoji)
+            ));
+        }
+
+        // a failed set keeps the glyphs from before
+        meter.build()?;
+        assert_eq!(meter, "🟨🟨⬜️⬜️");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_caption() {
+        let bands = [
+            Band::Fresh,
+            Band::Busy,
+            Band::Heavy,
+            Band::Burnout,
+            Band::Overflow,
+        ];
+        let captions: HashMap<Band, String> = bands
+            .iter()
+            .map(|band| (*band, format!("{band} caption")))
+            .collect();
+
+        for band in bands {
+            assert_eq!(caption(band, &captions), format!("{band} caption"));
+            assert_eq!(band.to_string().parse(), Ok(band));
+        }
+
+        // a band without one falls back to the default
+        let captions = HashMap::from([(Band::Fresh, "Chilling".to_string())]);
+        assert_eq!(caption(Band::Fresh, &captions), "Chilling");
+        assert_eq!(caption(Band::Burnout, &captions), "Take a break!");
+        assert_eq!(caption(Band::Busy, &HashMap::new()), "Keeping busy");
+    }
 }
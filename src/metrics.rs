@@ -0,0 +1,93 @@
+use crate::meter::Builder;
+
+/// Render the meter as gauges in the Prometheus text exposition format, e.g.
+/// for a node exporter's textfile collector to pick up.
+///
+/// https://prometheus.io/docs/instrumenting/exposition_formats/
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::{meter::Builder, metrics::render};
+///
+/// let mut meter = Builder::new();
+/// meter.set_current(22).set_max(40).set_length(8).build()?;
+///
+/// assert!(render(&meter).contains("\nburnout_hours 22\n"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn render(meter: &Builder) -> String {
+    let metrics = [
+        (
+            "burnout_hours",
+            "The hours counted toward the limit.",
+            meter.current().unwrap_or(0f64),
+        ),
+        ("burnout_limit", "The hours at burnout.", *meter.max()),
+        (
+            "burnout_percentage",
+            "The hours as a fraction of the limit, past 1 over it.",
+            meter.percentage(),
+        ),
+        (
+            "burnout_meter_filled",
+            "How many cells of the meter are filled.",
+            meter.filled() as f64,
+        ),
+    ];
+
+    metrics
+        .iter()
+        .map(|(name, help, value)| {
+            format!("# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::meter::Builder;
+    use std::error::Error;
+
+    #[test]
+    fn test_render() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_current(22).set_max(40).set_length(8).build()?;
+
+        let text = render(&meter);
+
+        for line in [
+            "# TYPE burnout_hours gauge",
+            "burnout_hours 22",
+            "burnout_limit 40",
+            "burnout_percentage 0.55",
+            "burnout_meter_filled 4",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line} not in {text}");
+        }
+        assert!(text.ends_with('\n'));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_over_limit() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_current(50).set_max(40).set_length(8).build()?;
+
+        let text = render(&meter);
+
+        assert!(
+            text.lines().any(|l| l == "burnout_percentage 1.25"),
+            "{text}"
+        );
+        // the meter can't fill more cells than it has
+        assert!(
+            text.lines().any(|l| l == "burnout_meter_filled 8"),
+            "{text}"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{
+    error::BurnoutError,
+    meter::{Builder, MeterStyle},
+};
+
+/// Hours added or taken away by the left and right arrows.
+const SMALL_STEP: f64 = 1f64;
+
+/// Hours added or taken away by the up and down arrows.
+const LARGE_STEP: f64 = 10f64;
+
+/// The styles [`Key::NextPalette`] goes through, in order.
+const PALETTES: [MeterStyle; 2] = [MeterStyle::Emoji, MeterStyle::SmoothBlocks];
+
+/// A key press the preview understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Switch between ASCII and the palette's style.
+    ToggleAscii,
+    /// Switch to the next palette, e.g. from emoji to blocks.
+    NextPalette,
+    Quit,
+}
+
+/// A meter whose hours are scrubbed a key at a time, to try out the limit,
+/// length, and style without fetching anything.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::{
+///     meter::Builder,
+///     preview::{Key, Preview},
+/// };
+///
+/// let mut meter = Builder::new();
+/// meter.set_max(40).set_length(5);
+///
+/// let mut preview = Preview::new(meter);
+/// preview.apply(Key::Up);
+/// preview.apply(Key::Up);
+/// preview.apply(Key::Right);
+///
+/// assert_eq!(preview.render()?, "🟨🟨⬜️⬜️⬜️ 21/40h busy 53%");
+/// # Ok::<(), rust_burnout_meter::error::BurnoutError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Preview {
+    meter: Builder,
+    hours: f64,
+    palette: usize,
+    ascii: bool,
+}
+
+impl Preview {
+    /// Preview `meter`, starting from zero hours.
+    pub fn new(meter: Builder) -> Self {
+        Self {
+            meter,
+            hours: 0f64,
+            palette: 0,
+            ascii: false,
+        }
+    }
+
+    /// The hours being previewed.
+    pub fn hours(&self) -> f64 {
+        self.hours
+    }
+
+    /// Apply `key`, returning `false` once it's time to quit.
+    ///
+    /// The hours never go below zero, but can go past the max to preview the
+    /// overflow.
+    pub fn apply(&mut self, key: Key) -> bool {
+        let step = match key {
+            Key::Up => LARGE_STEP,
+            Key::Down => -LARGE_STEP,
+            Key::Right => SMALL_STEP,
+            Key::Left => -SMALL_STEP,
+            Key::ToggleAscii => {
+                self.ascii = !self.ascii;
+                0f64
+            }
+            Key::NextPalette => {
+                self.palette = (self.palette + 1) % PALETTES.len();
+                0f64
+            }
+            Key::Quit => return false,
+        };
+
+        self.hours = (self.hours + step).max(0f64);
+
+        true
+    }
+
+    /// Render the meter for the hours with the hours, max, band, and
+    /// percent of the max after it, e.g. `🟨🟨⬜️⬜️⬜️ 21/40h busy 53%`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the meter can't be built.
+    pub fn render(&mut self) -> Result<String, BurnoutError> {
+        let style = match self.ascii {
+            true => MeterStyle::Ascii {
+                filled: '#',
+                empty: '-',
+            },
+            false => PALETTES[self.palette],
+        };
+
+        self.meter
+            .set_style(style)
+            .set_current(self.hours)
+            .build()?;
+
+        Ok(format!(
+            "{} {}/{}h {} {}%",
+            self.meter,
+            self.hours,
+            self.meter.max(),
+            self.meter.band(),
+            self.meter.percent().round()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, Preview};
+    use crate::meter::{Band, Builder};
+
+    fn preview() -> Preview {
+        let mut meter = Builder::new();
+        meter.set_max(40).set_length(4);
+
+        Preview::new(meter)
+    }
+
+    #[test]
+    fn test_keys_scrub_hours() {
+        let mut preview = preview();
+
+        let keys = [Key::Up, Key::Up, Key::Up, Key::Right, Key::Right, Key::Left];
+        for key in keys {
+            assert!(preview.apply(key));
+        }
+        assert_eq!(preview.hours(), 31.0);
+        assert_eq!(preview.render().unwrap(), "🟧🟧🟧⬜️ 31/40h heavy 78%");
+
+        // never below zero
+        for _ in 0..5 {
+            preview.apply(Key::Down);
+        }
+        assert_eq!(preview.hours(), 0.0);
+        assert_eq!(preview.render().unwrap(), "⬜️⬜️⬜️⬜️ 0/40h fresh 0%");
+    }
+
+    #[test]
+    fn test_past_max() {
+        let mut preview = preview();
+
+        for _ in 0..5 {
+            preview.apply(Key::Up);
+        }
+
+        assert_eq!(preview.render().unwrap(), "🟥🟥🟥🟥 50/40h overflow 125%");
+        assert_eq!(preview.meter.band(), Band::Overflow);
+    }
+
+    #[test]
+    fn test_toggle_ascii() {
+        let mut preview = preview();
+        preview.apply(Key::Up);
+        preview.apply(Key::Up);
+
+        preview.apply(Key::ToggleAscii);
+        assert_eq!(preview.render().unwrap(), "##-- 20/40h busy 50%");
+
+        preview.apply(Key::ToggleAscii);
+        assert_eq!(preview.render().unwrap(), "🟨🟨⬜️⬜️ 20/40h busy 50%");
+    }
+
+    #[test]
+    fn test_next_palette() {
+        let mut preview = preview();
+        preview.apply(Key::Up);
+        preview.apply(Key::Up);
+
+        preview.apply(Key::NextPalette);
+        assert_eq!(preview.render().unwrap(), "██   20/40h busy 50%");
+
+        // ASCII wins until it's toggled back off
+        preview.apply(Key::ToggleAscii);
+        assert_eq!(preview.render().unwrap(), "##-- 20/40h busy 50%");
+        preview.apply(Key::ToggleAscii);
+
+        // and round to the emoji again
+        preview.apply(Key::NextPalette);
+        assert_eq!(preview.render().unwrap(), "🟨🟨⬜️⬜️ 20/40h busy 50%");
+    }
+
+    #[test]
+    fn test_quit() {
+        assert!(!preview().apply(Key::Quit));
+    }
+}
@@ -0,0 +1,131 @@
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Remaining requests at or below which a warning is printed.
+pub const LOW_REMAINING: u64 = 10;
+
+/// The rate limit an API reported on its last response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RateLimit {
+    /// Requests allowed in the window.
+    pub limit: Option<u64>,
+    /// Requests left in the window.
+    pub remaining: u64,
+    /// When the window resets, as a unix timestamp in seconds.
+    pub reset: Option<i64>,
+}
+
+impl RateLimit {
+    /// Read the rate limit from the `X-RateLimit-*` headers, or Twitter's
+    /// `X-Rate-Limit-*` spelling.
+    ///
+    /// Returns `None` if there's no remaining count.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        Some(Self {
+            limit: header(headers, "limit"),
+            remaining: header(headers, "remaining")?,
+            reset: header(headers, "reset"),
+        })
+    }
+
+    /// Whether few enough requests are left to warn about.
+    pub fn is_low(&self) -> bool {
+        self.remaining <= LOW_REMAINING
+    }
+}
+
+/// When the window resets by the `X-RateLimit-Reset` or
+/// `X-Rate-Limit-Reset` header, as a unix timestamp in seconds, e.g. from a
+/// `429` that doesn't say how many requests are left.
+pub fn reset(headers: &HeaderMap) -> Option<i64> {
+    header(headers, "reset")
+}
+
+/// Parse the `X-RateLimit-{name}` or `X-Rate-Limit-{name}` header.
+fn header<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    [
+        format!("x-ratelimit-{name}"),
+        format!("x-rate-limit-{name}"),
+    ]
+    .iter()
+    .find_map(|key| headers.get(key.as_str()))
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.trim().parse().ok())
+}
+
+/// A warning to print if `api` is running low on requests.
+pub fn warning(api: &str, rate_limit: &RateLimit) -> Option<String> {
+    if !rate_limit.is_low() {
+        return None;
+    }
+
+    let total = match rate_limit.limit {
+        Some(limit) => format!(" of {limit}"),
+        None => String::new(),
+    };
+
+    Some(format!(
+        "Only {}{total} {api} requests left, consider a longer DAEMON_INTERVAL_MINUTES",
+        rate_limit.remaining
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{warning, RateLimit};
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("100"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("42"));
+        headers.insert("X-RateLimit-Reset", HeaderValue::from_static("1674000000"));
+
+        assert_eq!(
+            RateLimit::from_headers(&headers),
+            Some(RateLimit {
+                limit: Some(100),
+                remaining: 42,
+                reset: Some(1674000000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_twitter_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rate-limit-remaining", HeaderValue::from_static("3"));
+
+        let rate_limit = RateLimit::from_headers(&headers).unwrap();
+
+        assert_eq!(rate_limit.remaining, 3);
+        assert_eq!(rate_limit.limit, None);
+    }
+
+    #[test]
+    fn test_missing_headers() {
+        assert_eq!(RateLimit::from_headers(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_warning() {
+        let low = RateLimit {
+            limit: Some(100),
+            remaining: 10,
+            reset: None,
+        };
+        let plenty = RateLimit {
+            remaining: 11,
+            ..low
+        };
+
+        assert_eq!(
+            warning("WakaTime", &low).as_deref(),
+            Some(
+                "Only 10 of 100 WakaTime requests left, consider a longer DAEMON_INTERVAL_MINUTES"
+            )
+        );
+        assert_eq!(warning("WakaTime", &plenty), None);
+    }
+}
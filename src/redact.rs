@@ -0,0 +1,133 @@
+use reqwest::{Request, Url};
+
+/// Query params and headers whose values are masked before logging.
+const SECRET_FIELDS: [&str; 6] = [
+    "api_key",
+    "authorization",
+    "access_token",
+    "oauth_token",
+    "oauth_signature",
+    "token",
+];
+
+/// What secret values are replaced with.
+const MASK: &str = "****";
+
+fn is_secret(name: &str) -> bool {
+    SECRET_FIELDS
+        .iter()
+        .any(|field| field.eq_ignore_ascii_case(name))
+}
+
+/// `url` with the values of any secret query params masked, e.g.
+/// `api_key=****`.
+pub fn redact_url(url: &Url) -> Url {
+    let mut url = url.clone();
+
+    if url.query().is_none() {
+        return url;
+    }
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| match is_secret(&key) {
+            true => (key.to_string(), MASK.to_string()),
+            false => (key.to_string(), value.to_string()),
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+
+    url
+}
+
+/// Describe `request` for logging, with its method and URL on the first line
+/// and a line for each header, masking secret params and headers.
+pub fn redact_request(request: &Request) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        request.method(),
+        redact_url(request.url())
+    )];
+
+    for (name, value) in request.headers() {
+        let value = match is_secret(name.as_str()) {
+            true => MASK.to_string(),
+            false => String::from_utf8_lossy(value.as_bytes()).to_string(),
+        };
+
+        lines.push(format!("{name}: {value}"));
+    }
+
+    lines.join("\n")
+}
+
+/// Log `request` at debug, redacted.
+pub fn log_request(request: &Request) {
+    if log::log_enabled!(log::Level::Debug) {
+        log::debug!("{}", redact_request(request));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_request, redact_url};
+    use reqwest::{Client, Url};
+    use std::error::Error;
+
+    #[test]
+    fn test_redact_url() -> Result<(), Box<dyn Error>> {
+        let url: Url =
+            "https://wakatime.com/api/v1/users/current/durations?api_key=secret&date=2023-01-18"
+                .parse()?;
+
+        assert_eq!(
+            redact_url(&url).as_str(),
+            "https://wakatime.com/api/v1/users/current/durations?api_key=****&date=2023-01-18"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_redact_request() -> Result<(), Box<dyn Error>> {
+        let request = Client::new()
+            .get("https://api.github.com/search/issues")
+            .query(&[
+                ("q", "type:pr author:octocat"),
+                ("access_token", "ghp_secret"),
+            ])
+            .bearer_auth("ghp_secret")
+            .header("user-agent", "rust-burnout-meter")
+            .build()?;
+
+        let logged = redact_request(&request);
+
+        assert!(!logged.contains("ghp_secret"), "{logged}");
+        assert!(logged.contains("access_token=****"), "{logged}");
+        assert!(logged.contains("authorization: ****"), "{logged}");
+        assert!(
+            logged.contains("user-agent: rust-burnout-meter"),
+            "{logged}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_redact_oauth_header() -> Result<(), Box<dyn Error>> {
+        let request = Client::new()
+            .post("https://api.twitter.com/1.1/account/update_profile.json")
+            .header(
+                "Authorization",
+                r#"OAuth oauth_token="token", oauth_signature="signature""#,
+            )
+            .build()?;
+
+        assert_eq!(
+            redact_request(&request),
+            "POST https://api.twitter.com/1.1/account/update_profile.json\nauthorization: ****"
+        );
+
+        Ok(())
+    }
+}
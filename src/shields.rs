@@ -0,0 +1,73 @@
+use crate::meter::Band;
+use serde::Serialize;
+
+/// The label on the left of the badge.
+const LABEL: &str = "burnout";
+
+/// The JSON a shields.io endpoint badge is built from.
+///
+/// https://shields.io/badges/endpoint-badge
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+    pub schema_version: u8,
+    pub label: String,
+    pub message: String,
+    pub color: String,
+}
+
+impl Endpoint {
+    /// A badge showing `message`, colored by the `band`.
+    pub fn new(message: &str, band: Band) -> Self {
+        Self {
+            schema_version: 1,
+            label: LABEL.to_string(),
+            message: message.to_string(),
+            color: color(band).to_string(),
+        }
+    }
+}
+
+/// The shields.io named color matching the band's glyph.
+pub fn color(band: Band) -> &'static str {
+    match band {
+        Band::Fresh => "green",
+        Band::Busy => "yellow",
+        Band::Heavy => "orange",
+        Band::Burnout | Band::Overflow => "red",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{color, Endpoint};
+    use crate::meter::Band;
+    use serde_json::json;
+    use std::error::Error;
+
+    #[test]
+    fn test_endpoint_json() -> Result<(), Box<dyn Error>> {
+        let endpoint = Endpoint::new("22/40 hours", Band::Busy);
+
+        assert_eq!(
+            serde_json::to_value(&endpoint)?,
+            json!({
+                "schemaVersion": 1,
+                "label": "burnout",
+                "message": "22/40 hours",
+                "color": "yellow"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(color(Band::Fresh), "green");
+        assert_eq!(color(Band::Busy), "yellow");
+        assert_eq!(color(Band::Heavy), "orange");
+        assert_eq!(color(Band::Burnout), "red");
+        assert_eq!(color(Band::Overflow), "red");
+    }
+}
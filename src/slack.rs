@@ -0,0 +1,337 @@
+use crate::{error::BurnoutError, util::http_client};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde::Deserialize;
+use serde_json::{from_str, json, Value};
+
+/// The Slack Web API, unless pointed somewhere else.
+const DEFAULT_BASE_URL: &str = "https://slack.com/api/";
+
+/// The most characters Slack allows in a custom status.
+pub const STATUS_MAX_LENGTH: usize = 100;
+
+/// Where messages go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    /// An incoming webhook, which posts to the one channel it was made for.
+    Webhook(String),
+    /// A channel, posted to by a bot with the `chat:write` scope.
+    Channel { token: String, channel: String },
+}
+
+/// The part of a Web API response that says whether it worked.
+#[derive(Deserialize, Debug)]
+struct SlackResponse {
+    ok: bool,
+    error: Option<String>,
+}
+
+/// A Slack channel to post the meter in.
+#[derive(Debug, Clone)]
+pub struct Slack {
+    client: Client,
+    base_url: Url,
+    /// Where messages go, if anywhere.
+    destination: Option<Destination>,
+    /// The user token to set the status with, which a bot can't.
+    user_token: Option<String>,
+}
+
+impl Slack {
+    pub fn new(destination: Destination) -> Result<Self, BurnoutError> {
+        Self::with_client(destination, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(destination: Destination, client: Client) -> Result<Self, BurnoutError> {
+        if let Destination::Webhook(webhook_url) = &destination {
+            Url::parse(webhook_url).map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+        }
+
+        let mut slack = Self::status_only(None, client);
+        slack.destination = Some(destination);
+
+        Ok(slack)
+    }
+
+    /// Create the client for setting the status alone, with no channel to
+    /// post messages in.
+    pub fn status_only(user_token: Option<&str>, client: Client) -> Self {
+        Self {
+            client,
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("Failed to parse the Slack API URL"),
+            destination: None,
+            user_token: user_token.map(str::to_string),
+        }
+    }
+
+    /// Set the status with `token`, a user token with the
+    /// `users.profile:write` scope.
+    pub fn set_user_token(&mut self, token: &str) -> &mut Self {
+        self.user_token = Some(token.to_string());
+
+        self
+    }
+
+    /// Point Web API requests at a different server, e.g. a mock in tests.
+    pub fn set_base_url(&mut self, base_url: Url) -> &mut Self {
+        self.base_url = base_url;
+
+        self
+    }
+
+    /// Post `text` as a message.
+    ///
+    /// https://api.slack.com/methods/chat.postMessage
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, Slack answers with an error
+    /// status, or the Web API answers with `ok` false, and
+    /// `channel_not_found` if it was made for the status only.
+    pub async fn post_message(&self, text: &str) -> Result<(), BurnoutError> {
+        match &self.destination {
+            None => Err(BurnoutError::Slack("channel_not_found".to_string())),
+            Some(Destination::Webhook(webhook_url)) => {
+                // a webhook answers with a bare `ok` rather than JSON
+                self.client
+                    .post(webhook_url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(json!({ "text": text }).to_string())
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                Ok(())
+            }
+            Some(Destination::Channel { token, channel }) => {
+                self.call(
+                    token,
+                    "chat.postMessage",
+                    json!({ "channel": channel, "text": text }),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Set the user's custom status to `text`, cut to [`STATUS_MAX_LENGTH`]
+    /// characters, with `emoji` like `:green_square:`, clearing itself at the
+    /// unix timestamp `expiration` or never if it's `None`.
+    ///
+    /// https://api.slack.com/methods/users.profile.set
+    ///
+    /// # Errors
+    ///
+    /// Returns `not_authed` if there's no user token, and an error if the
+    /// request fails or the Web API answers with `ok` false.
+    pub async fn set_status(
+        &self,
+        text: &str,
+        emoji: &str,
+        expiration: Option<i64>,
+    ) -> Result<(), BurnoutError> {
+        let Some(token) = &self.user_token else {
+            return Err(BurnoutError::Slack("not_authed".to_string()));
+        };
+
+        let text: String = text.chars().take(STATUS_MAX_LENGTH).collect();
+
+        self.call(
+            token,
+            "users.profile.set",
+            json!({
+                "profile": {
+                    "status_text": text,
+                    "status_emoji": emoji,
+                    // 0 is how Slack says never
+                    "status_expiration": expiration.unwrap_or(0),
+                }
+            }),
+        )
+        .await
+    }
+
+    /// Call the Web API `method` with `body`, checking the `ok` in the
+    /// response.
+    async fn call(&self, token: &str, method: &str, body: Value) -> Result<(), BurnoutError> {
+        let url = self
+            .base_url
+            .join(method)
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+
+        let body = self
+            .client
+            .post(url)
+            .bearer_auth(token)
+            .header(CONTENT_TYPE, "application/json; charset=utf-8")
+            .body(body.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let response: SlackResponse = from_str(&body)?;
+
+        match response.ok {
+            true => Ok(()),
+            false => Err(BurnoutError::Slack(
+                response
+                    .error
+                    .unwrap_or_else(|| "unknown_error".to_string()),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Destination, Slack, STATUS_MAX_LENGTH};
+    use crate::error::BurnoutError;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+
+    fn bot(mock_server: &MockServer) -> Result<Slack, Box<dyn Error>> {
+        let mut slack = Slack::new(Destination::Channel {
+            token: "xoxb-token".to_string(),
+            channel: "C0123".to_string(),
+        })?;
+        slack.set_base_url(mock_server.url("/api/").parse()?);
+
+        Ok(slack)
+    }
+
+    #[tokio::test]
+    async fn test_post_message_as_bot() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/chat.postMessage")
+                .header("authorization", "Bearer xoxb-token")
+                .json_body(json!({ "channel": "C0123", "text": "🟨🟨⬜️⬜️ 22/40 hrs" }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "ok": true, "channel": "C0123" }).to_string());
+        });
+
+        bot(&mock_server)?
+            .post_message("🟨🟨⬜️⬜️ 22/40 hrs")
+            .await?;
+
+        mock.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_message_not_ok() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        // the Web API fails with a 200 and `ok` false
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/api/chat.postMessage");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "ok": false, "error": "channel_not_found" }).to_string());
+        });
+
+        let result = bot(&mock_server)?.post_message("🟨🟨⬜️⬜️").await;
+
+        assert!(matches!(
+            result,
+            Err(BurnoutError::Slack(error)) if error == "channel_not_found"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_status() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let long_text = "🟨".repeat(STATUS_MAX_LENGTH + 20);
+        let cut_text = "🟨".repeat(STATUS_MAX_LENGTH);
+
+        let expiring = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/users.profile.set")
+                .header("authorization", "Bearer xoxp-token")
+                .json_body(json!({
+                    "profile": {
+                        "status_text": "🟨🟨⬜️⬜️ 22/40 hrs",
+                        "status_emoji": ":yellow_square:",
+                        "status_expiration": 1674086400,
+                    }
+                }));
+            then.status(200).body(json!({ "ok": true }).to_string());
+        });
+        let forever = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/users.profile.set")
+                .json_body(json!({
+                    "profile": {
+                        "status_text": cut_text,
+                        "status_emoji": ":yellow_square:",
+                        "status_expiration": 0,
+                    }
+                }));
+            then.status(200).body(json!({ "ok": true }).to_string());
+        });
+
+        let mut slack = Slack::status_only(Some("xoxp-token"), reqwest::Client::new());
+        slack.set_base_url(mock_server.url("/api/").parse()?);
+
+        slack
+            .set_status("🟨🟨⬜️⬜️ 22/40 hrs", ":yellow_square:", Some(1674086400))
+            .await?;
+        slack
+            .set_status(&long_text, ":yellow_square:", None)
+            .await?;
+
+        expiring.assert();
+        forever.assert();
+
+        // a status-only client has nowhere to post
+        assert!(slack.post_message("🟨🟨⬜️⬜️").await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_status_without_user_token() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let result = bot(&mock_server)?
+            .set_status("🟨🟨⬜️⬜️", ":yellow_square:", None)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(BurnoutError::Slack(error)) if error == "not_authed"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_message_to_webhook() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/services/T0/B0/secret")
+                .json_body(json!({ "text": "🟨🟨⬜️⬜️" }));
+            then.status(200).body("ok");
+        });
+
+        let slack = Slack::new(Destination::Webhook(
+            mock_server.url("/services/T0/B0/secret"),
+        ))?;
+        slack.post_message("🟨🟨⬜️⬜️").await?;
+
+        mock.assert();
+
+        assert!(Slack::new(Destination::Webhook("not a url".to_string())).is_err());
+
+        Ok(())
+    }
+}
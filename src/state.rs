@@ -0,0 +1,263 @@
+use crate::{
+    circuit::CircuitState,
+    meter::Band,
+    store::{FileStore, StateStore},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_value, to_value, Map, Value};
+use std::{collections::HashMap, error::Error, path::Path};
+
+/// How many runs of history to keep.
+const HISTORY_LIMIT: usize = 1000;
+
+/// The hours and limit from a single run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the run happened, as a unix timestamp in seconds.
+    pub timestamp: i64,
+    pub hours: f64,
+    pub limit: f64,
+}
+
+/// State persisted between runs, stored as JSON.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    /// The band of the meter the last time the profile was updated.
+    #[serde(default)]
+    pub last_band: Option<Band>,
+
+    /// The band of the meter on the last run, whether or not it was posted.
+    #[serde(default)]
+    pub run_band: Option<Band>,
+
+    /// The week the profile was last updated, from [`crate::util::week_id`].
+    #[serde(default)]
+    pub last_update_week: Option<String>,
+
+    /// The week the weekly goal was last celebrated, from [`crate::util::week_id`].
+    #[serde(default)]
+    pub celebrated_week: Option<String>,
+
+    /// The day the daily log was last posted, from [`crate::util::day_id`].
+    #[serde(default)]
+    pub last_post_day: Option<String>,
+
+    /// When the meter turned red, as a unix timestamp in seconds, or `None`
+    /// if it isn't red.
+    #[serde(default)]
+    pub red_since: Option<i64>,
+
+    /// Whether updates are stopped after a sustained burnout, see
+    /// [`crate::hardstop`].
+    #[serde(default)]
+    pub on_break: bool,
+
+    /// The circuit breaker for each backend, so a restart doesn't hammer a
+    /// failing one.
+    #[serde(default)]
+    pub circuits: HashMap<String, CircuitState>,
+
+    /// The hours from each run, oldest first.
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+
+    /// The location last posted to each target, like `twitter:location`.
+    #[serde(default)]
+    pub last_posted: HashMap<String, String>,
+}
+
+impl State {
+    /// Load the state from `path`, or the default state if the file doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::load_from(&FileStore::open(path)?)
+    }
+
+    /// Load the state from `store`, with anything missing left as the default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store fails or a stored value can't be parsed.
+    pub fn load_from(store: &dyn StateStore) -> Result<Self, Box<dyn Error>> {
+        let mut values = Map::new();
+
+        for key in Self::keys()? {
+            if let Some(value) = store.get(&key)? {
+                values.insert(key, value);
+            }
+        }
+
+        Ok(from_value(Value::Object(values))?)
+    }
+
+    /// The keys the state is stored under, one per field.
+    fn keys() -> Result<Vec<String>, Box<dyn Error>> {
+        match to_value(Self::default())? {
+            Value::Object(values) => Ok(values.into_iter().map(|(key, _)| key).collect()),
+            _ => Err("State isn't stored as an object".into()),
+        }
+    }
+
+    /// Add a run to the history, dropping the oldest runs past the limit.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        self.history.push(entry);
+
+        if self.history.len() > HISTORY_LIMIT {
+            let excess = self.history.len() - HISTORY_LIMIT;
+            self.history.drain(..excess);
+        }
+    }
+
+    /// The location last posted to `target`, or `None` if nothing has been.
+    pub fn load_last_posted(&self, target: &str) -> Option<&str> {
+        self.last_posted.get(target).map(String::as_str)
+    }
+
+    /// Remember `location` as the last posted to `target`.
+    pub fn save_last_posted(&mut self, target: &str, location: &str) {
+        self.last_posted
+            .insert(target.to_string(), location.to_string());
+    }
+
+    /// Save the state to `path`, replacing what was there.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.save_to(&mut FileStore::open(path)?)
+    }
+
+    /// Save the state to `store`, each field under its own key, in one
+    /// change.
+    pub fn save_to(&self, store: &mut dyn StateStore) -> Result<(), Box<dyn Error>> {
+        match to_value(self)? {
+            Value::Object(values) => store.set_all(values),
+            _ => Err("State isn't stored as an object".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryEntry, State, HISTORY_LIMIT};
+    use crate::{
+        meter::Band,
+        store::{InMemoryStore, StateStore},
+    };
+    use serde_json::{Map, Value};
+    use std::error::Error;
+
+    #[test]
+    fn test_load_and_save() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!(
+            "burnout-meter-state-{}.json",
+            time::OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+
+        // a missing file is the default state
+        assert_eq!(State::load(&path)?, State::default());
+
+        let mut state = State {
+            last_band: Some(Band::Heavy),
+            ..Default::default()
+        };
+        state.record(HistoryEntry {
+            timestamp: 1674000000,
+            hours: 12.5,
+            limit: 40.0,
+        });
+        state.save(&path)?;
+        let loaded = State::load(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(loaded?, state);
+
+        Ok(())
+    }
+
+    /// Counts the writes to a store, to check a save is one change.
+    #[derive(Default)]
+    struct CountingStore {
+        inner: InMemoryStore,
+        writes: usize,
+    }
+
+    impl StateStore for CountingStore {
+        fn get(&self, key: &str) -> Result<Option<Value>, Box<dyn Error>> {
+            self.inner.get(key)
+        }
+
+        fn set(&mut self, key: &str, value: Value) -> Result<(), Box<dyn Error>> {
+            self.writes += 1;
+            self.inner.set(key, value)
+        }
+
+        fn set_all(&mut self, values: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+            self.writes += 1;
+            self.inner.set_all(values)
+        }
+    }
+
+    #[test]
+    fn test_save_is_one_write() -> Result<(), Box<dyn Error>> {
+        let mut store = CountingStore::default();
+        let state = State {
+            last_band: Some(Band::Heavy),
+            ..Default::default()
+        };
+
+        state.save_to(&mut store)?;
+
+        assert_eq!(store.writes, 1);
+        assert_eq!(State::load_from(&store)?, state);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_drops_oldest() {
+        let mut state = State::default();
+
+        for timestamp in 0..(HISTORY_LIMIT as i64 + 5) {
+            state.record(HistoryEntry {
+                timestamp,
+                hours: 1.0,
+                limit: 40.0,
+            });
+        }
+
+        assert_eq!(state.history.len(), HISTORY_LIMIT);
+        assert_eq!(state.history[0].timestamp, 5);
+    }
+
+    #[test]
+    fn test_load_and_save_store() -> Result<(), Box<dyn Error>> {
+        let mut store = InMemoryStore::new();
+
+        // an empty store is the default state
+        assert_eq!(State::load_from(&store)?, State::default());
+
+        let mut state = State {
+            run_band: Some(Band::Busy),
+            last_post_day: Some("2023-01-18".to_string()),
+            ..Default::default()
+        };
+        state.record(HistoryEntry {
+            timestamp: 1674000000,
+            hours: 22.5,
+            limit: 40.0,
+        });
+        state.save_last_posted("twitter:location", "🟨🟨⬜️⬜️ 22/40 hours");
+        state.save_to(&mut store)?;
+
+        let loaded = State::load_from(&store)?;
+        assert_eq!(loaded, state);
+        assert_eq!(
+            loaded.load_last_posted("twitter:location"),
+            Some("🟨🟨⬜️⬜️ 22/40 hours")
+        );
+        assert_eq!(loaded.load_last_posted("twitter:description"), None);
+
+        Ok(())
+    }
+}
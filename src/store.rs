@@ -0,0 +1,181 @@
+use serde_json::{from_str, to_string_pretty, Map, Value};
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+
+/// Somewhere to keep values between runs, by key.
+pub trait StateStore {
+    /// Get the value stored at `key`, if there is one.
+    fn get(&self, key: &str) -> Result<Option<Value>, Box<dyn Error>>;
+
+    /// Store `value` at `key`, replacing what was there.
+    fn set(&mut self, key: &str, value: Value) -> Result<(), Box<dyn Error>>;
+
+    /// Store each of `values` at its key, as one change where the store
+    /// can, so a save of many keys isn't written a key at a time.
+    fn set_all(&mut self, values: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        for (key, value) in values {
+            self.set(&key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add `value` to the end of the list stored at `key`, starting a new
+    /// list if there's nothing there yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value at `key` isn't a list.
+    fn append(&mut self, key: &str, value: Value) -> Result<(), Box<dyn Error>> {
+        let mut list = match self.get(key)? {
+            Some(Value::Array(list)) => list,
+            Some(_) => return Err(format!("Can't append to {key}, it isn't a list").into()),
+            None => Vec::new(),
+        };
+        list.push(value);
+
+        self.set(key, Value::Array(list))
+    }
+}
+
+/// Keeps the values as a JSON object in a file, written on every change.
+///
+/// Each change is written to a temporary file next to it and renamed over
+/// it, so an interrupted write leaves the last whole file behind.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+    values: Map<String, Value>,
+}
+
+impl FileStore {
+    /// Open the store at `path`, which is created on the first change if it
+    /// doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or isn't a JSON
+    /// object.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Box<dyn Error>> {
+        let path = path.into();
+
+        let values = match path.exists() {
+            true => from_str(&fs::read_to_string(&path)?)?,
+            false => Map::new(),
+        };
+
+        Ok(Self { path, values })
+    }
+
+    /// Write the values to the file, replacing it in one step.
+    fn write(&self) -> Result<(), Box<dyn Error>> {
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+
+        fs::write(&temp, to_string_pretty(&self.values)?)?;
+        fs::rename(&temp, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl StateStore for FileStore {
+    fn get(&self, key: &str) -> Result<Option<Value>, Box<dyn Error>> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), Box<dyn Error>> {
+        self.values.insert(key.to_string(), value);
+
+        self.write()
+    }
+
+    fn set_all(&mut self, values: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        self.values.extend(values);
+
+        self.write()
+    }
+}
+
+/// Keeps the values in memory only, e.g. for tests.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryStore {
+    values: HashMap<String, Value>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StateStore for InMemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Value>, Box<dyn Error>> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), Box<dyn Error>> {
+        self.values.insert(key.to_string(), value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileStore, InMemoryStore, StateStore};
+    use serde_json::{json, Value};
+    use std::error::Error;
+
+    /// The behavior every store should share.
+    fn check_store(store: &mut dyn StateStore) -> Result<(), Box<dyn Error>> {
+        assert_eq!(store.get("last_band")?, None);
+
+        store.set("last_band", json!("busy"))?;
+        assert_eq!(store.get("last_band")?, Some(json!("busy")));
+
+        store.set("last_band", json!("heavy"))?;
+        assert_eq!(store.get("last_band")?, Some(json!("heavy")));
+
+        store.append("history", json!(1))?;
+        store.append("history", json!(2))?;
+        assert_eq!(store.get("history")?, Some(json!([1, 2])));
+
+        assert!(store.append("last_band", json!(3)).is_err());
+
+        let values = json!({ "last_band": "fresh", "last_update_week": "2023-W03" });
+        if let Value::Object(values) = values {
+            store.set_all(values)?;
+        }
+        assert_eq!(store.get("last_band")?, Some(json!("fresh")));
+        assert_eq!(store.get("last_update_week")?, Some(json!("2023-W03")));
+        assert_eq!(store.get("history")?, Some(json!([1, 2])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_memory_store() -> Result<(), Box<dyn Error>> {
+        check_store(&mut InMemoryStore::new())
+    }
+
+    #[test]
+    fn test_file_store() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!(
+            "burnout-meter-store-{}.json",
+            time::OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+
+        let result = check_store(&mut FileStore::open(&path)?);
+
+        // the values are still there when the file is opened again
+        let reopened = FileStore::open(&path);
+        std::fs::remove_file(&path)?;
+        result?;
+
+        assert_eq!(reopened?.get("history")?, Some(json!([1, 2])));
+        // nothing is left behind from the writes
+        assert!(!path.with_extension("json.tmp").exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{exec::Payload, meter::Band, ratelimit::RateLimit};
+use serde::Serialize;
+use std::time::Duration;
+
+/// The numbers from a run that go into the summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunReport {
+    /// Hours this week.
+    pub hours: f64,
+    /// The burnout limit.
+    pub limit: f64,
+    /// Days of the week so far, including today.
+    pub days: u32,
+    /// Days this week with any coding at all.
+    pub active_days: u32,
+    pub band: Band,
+    /// The rendered meter.
+    pub meter: String,
+    /// What WakaTime said about its rate limit.
+    pub wakatime_rate_limit: Option<RateLimit>,
+    /// What Twitter said about its rate limit, once it's been updated.
+    pub twitter_rate_limit: Option<RateLimit>,
+    /// How long the run took, once it's finished.
+    pub timings: Timings,
+}
+
+/// How long each stage of a run took.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Timings {
+    /// Getting the hours and anything else needed from the sources.
+    pub fetch: Duration,
+    /// Building the meter and the location.
+    pub render: Duration,
+    /// Updating the profile and any posts after it.
+    pub post: Duration,
+    /// The whole run.
+    pub total: Duration,
+}
+
+impl Timings {
+    /// A warning to print if the run took longer than `budget`.
+    pub fn budget_warning(&self, budget: Duration) -> Option<String> {
+        if self.total <= budget {
+            return None;
+        }
+
+        Some(format!(
+            "Run took {:?}, over the budget of {budget:?} (fetch {:?}, render {:?}, post {:?})",
+            self.total, self.fetch, self.render, self.post
+        ))
+    }
+}
+
+impl RunReport {
+    /// Count the days in `daily` with any hours as active.
+    pub fn active_days(daily: &[f64]) -> u32 {
+        daily.iter().filter(|hours| **hours > 0f64).count() as u32
+    }
+}
+
+/// The meter as a JSON object for scripts, printed with `OUTPUT_FORMAT=json`.
+///
+/// The fields are kept as they are, so scripts can rely on them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MeterOutput {
+    /// The rendered meter.
+    pub meter: String,
+    pub hours: f64,
+    pub limit: f64,
+    /// The hours as a percentage of the limit, past 100 over it.
+    pub percentage: f64,
+    /// The hours left before the limit, or zero once it's reached.
+    pub remaining: f64,
+    pub zone: Band,
+}
+
+impl MeterOutput {
+    /// The output for `meter` showing `hours` out of `limit` in `zone`.
+    pub fn new(meter: String, hours: f64, limit: f64, zone: Band) -> Self {
+        Payload::new(String::new(), meter, hours, limit, zone).into()
+    }
+}
+
+/// The same numbers as the exec backend gets, rounded or not.
+impl From<Payload> for MeterOutput {
+    fn from(payload: Payload) -> Self {
+        Self {
+            meter: payload.meter,
+            hours: payload.hours,
+            limit: payload.max,
+            percentage: payload.percent,
+            remaining: payload.remaining,
+            zone: payload.band,
+        }
+    }
+}
+
+impl From<&RunReport> for MeterOutput {
+    fn from(report: &RunReport) -> Self {
+        Self::new(
+            report.meter.clone(),
+            report.hours,
+            report.limit,
+            report.band,
+        )
+    }
+}
+
+/// Sum up the week in a sentence, e.g.
+/// `On pace — 22h of 40h, 2.6h/day average, 3 active days.`
+pub fn summarize(report: &RunReport) -> String {
+    let verdict = match report.band {
+        Band::Fresh => "Slow week",
+        Band::Busy => "On pace",
+        Band::Heavy => "Running hot",
+        Band::Burnout => "Close to burnout",
+        Band::Overflow => "Over the limit",
+    };
+
+    let average = report.hours / report.days.max(1) as f64;
+
+    let active_days = match report.active_days {
+        1 => "1 active day".to_string(),
+        days => format!("{days} active days"),
+    };
+
+    format!(
+        "{verdict} — {}h of {}h, {average:.1}h/day average, {active_days}.",
+        report.hours.round(),
+        report.limit
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{summarize, MeterOutput, RunReport, Timings};
+    use crate::{exec::Payload, meter::Band};
+    use serde_json::{json, Value};
+    use std::{error::Error, time::Duration};
+
+    #[test]
+    fn test_on_pace() {
+        let report = RunReport {
+            hours: 22.2,
+            limit: 40.0,
+            days: 3,
+            active_days: 3,
+            band: Band::Busy,
+            meter: String::new(),
+            wakatime_rate_limit: None,
+            twitter_rate_limit: None,
+            timings: Timings::default(),
+        };
+
+        assert_eq!(
+            summarize(&report),
+            "On pace — 22h of 40h, 7.4h/day average, 3 active days."
+        );
+    }
+
+    #[test]
+    fn test_meter_output() -> Result<(), Box<dyn Error>> {
+        let report = RunReport {
+            hours: 45.0,
+            limit: 40.0,
+            days: 5,
+            active_days: 5,
+            band: Band::Overflow,
+            meter: "🟥🟥🟥🟥".to_string(),
+            wakatime_rate_limit: None,
+            twitter_rate_limit: None,
+            timings: Timings::default(),
+        };
+
+        let output: Value =
+            serde_json::from_str(&serde_json::to_string(&MeterOutput::from(&report))?)?;
+
+        assert_eq!(
+            output,
+            json!({
+                "meter": "🟥🟥🟥🟥",
+                "hours": 45.0,
+                "limit": 40.0,
+                "percentage": 112.5,
+                "remaining": 0.0,
+                "zone": "overflow",
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_meter_output_rounded() {
+        let payload = Payload::new(String::new(), String::new(), 22.456, 40.0, Band::Busy);
+        let output = MeterOutput::from(payload.with_precision(1));
+
+        assert_eq!(
+            (output.hours, output.percentage, output.remaining),
+            (22.5, 56.1, 17.5)
+        );
+    }
+
+    #[test]
+    fn test_over_limit() {
+        let report = RunReport {
+            hours: 45.0,
+            limit: 40.0,
+            days: 5,
+            active_days: 5,
+            band: Band::Overflow,
+            meter: String::new(),
+            wakatime_rate_limit: None,
+            twitter_rate_limit: None,
+            timings: Timings::default(),
+        };
+
+        assert_eq!(
+            summarize(&report),
+            "Over the limit — 45h of 40h, 9.0h/day average, 5 active days."
+        );
+    }
+
+    #[test]
+    fn test_slow_week() {
+        let report = RunReport {
+            hours: 2.5,
+            limit: 40.0,
+            days: 4,
+            active_days: 1,
+            band: Band::Fresh,
+            meter: String::new(),
+            wakatime_rate_limit: None,
+            twitter_rate_limit: None,
+            timings: Timings::default(),
+        };
+
+        assert_eq!(
+            summarize(&report),
+            "Slow week — 3h of 40h, 0.6h/day average, 1 active day."
+        );
+    }
+
+    #[test]
+    fn test_active_days() {
+        assert_eq!(RunReport::active_days(&[0.0, 2.5, 0.0, 1.0]), 2);
+    }
+
+    #[test]
+    fn test_budget_warning() {
+        let timings = Timings {
+            fetch: Duration::from_millis(900),
+            render: Duration::from_millis(5),
+            post: Duration::from_millis(300),
+            total: Duration::from_millis(1205),
+        };
+
+        assert_eq!(timings.budget_warning(Duration::from_secs(2)), None);
+        assert_eq!(
+            timings.budget_warning(Duration::from_secs(1)).as_deref(),
+            Some("Run took 1.205s, over the budget of 1s (fetch 900ms, render 5ms, post 300ms)")
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{meter::Builder, shields};
+
+/// The width and height of a cell of the meter.
+const CELL_SIZE: usize = 20;
+
+/// The space between cells, and around the meter.
+const GAP: usize = 4;
+
+/// The room under the meter for the caption.
+const CAPTION_HEIGHT: usize = 20;
+
+/// The color of a cell that isn't filled.
+const BLANK_COLOR: &str = "lightgray";
+
+/// Render the meter as an SVG image, with a cell for each of its length
+/// filled in the color of its band and `caption` under it, e.g. the caption
+/// for the band from [`crate::meter::caption`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::{meter::Builder, svg::render};
+///
+/// let mut meter = Builder::new();
+/// meter.set_current(22).set_max(40).set_length(8).build()?;
+///
+/// let svg = render(&meter, "Keeping busy");
+/// assert!(svg.starts_with("<svg"));
+/// assert!(svg.contains(">Keeping busy</text>"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn render(meter: &Builder, caption: &str) -> String {
+    let length = meter.length() as usize;
+    let filled = meter.filled() as usize;
+    let color = shields::color(meter.band());
+
+    let width = GAP + length * (CELL_SIZE + GAP);
+    let height = GAP + CELL_SIZE + GAP + CAPTION_HEIGHT;
+
+    let cells: String = (0..length)
+        .map(|cell| {
+            let fill = if cell < filled { color } else { BLANK_COLOR };
+            let x = GAP + cell * (CELL_SIZE + GAP);
+
+            format!(
+                r#"<rect x="{x}" y="{GAP}" width="{CELL_SIZE}" height="{CELL_SIZE}" fill="{fill}"/>"#
+            )
+        })
+        .collect();
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}">"#,
+            "{cells}",
+            r#"<text x="{x}" y="{y}" text-anchor="middle" font-family="sans-serif" font-size="14">{caption}</text>"#,
+            "</svg>",
+        ),
+        width = width,
+        height = height,
+        cells = cells,
+        x = width / 2,
+        y = height - GAP,
+        caption = escape(caption),
+    )
+}
+
+/// Escape `text` to go between XML tags.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::meter::{caption, Band, Builder};
+    use std::{collections::HashMap, error::Error};
+
+    #[test]
+    fn test_render_cells() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_current(22).set_max(40).set_length(8).build()?;
+
+        let svg = render(&meter, "Keeping busy");
+
+        assert_eq!(svg.matches("<rect").count(), 8);
+        assert_eq!(svg.matches(r#"fill="yellow""#).count(), 4);
+        assert_eq!(svg.matches(r#"fill="lightgray""#).count(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_caption_for_each_band() -> Result<(), Box<dyn Error>> {
+        let captions = HashMap::from([
+            (Band::Fresh, "Chill".to_string()),
+            (Band::Busy, "Steady".to_string()),
+            (Band::Heavy, "Careful".to_string()),
+            (Band::Burnout, "Stop!".to_string()),
+            (Band::Overflow, "Way over".to_string()),
+        ]);
+
+        for (hours, expected) in [
+            (10, "Chill"),
+            (22, "Steady"),
+            (30, "Careful"),
+            (39, "Stop!"),
+            (45, "Way over"),
+        ] {
+            let mut meter = Builder::new();
+            meter.set_current(hours).set_max(40).set_length(8).build()?;
+
+            let svg = render(&meter, caption(meter.band(), &captions));
+
+            assert!(svg.contains(&format!(">{expected}</text>")), "{svg}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_default_caption() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_current(39).set_max(40).set_length(8).build()?;
+
+        // only green is configured, so red falls back to its default
+        let captions = HashMap::from([(Band::Fresh, "Chill".to_string())]);
+        let svg = render(&meter, caption(meter.band(), &captions));
+
+        assert!(svg.contains(">Take a break!</text>"), "{svg}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_escapes_caption() -> Result<(), Box<dyn Error>> {
+        let mut meter = Builder::new();
+        meter.set_current(10).set_max(40).set_length(4).build()?;
+
+        let svg = render(&meter, "<b>R&R</b>");
+
+        assert!(svg.contains(">&lt;b&gt;R&amp;R&lt;/b&gt;</text>"), "{svg}");
+
+        Ok(())
+    }
+}
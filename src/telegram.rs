@@ -0,0 +1,175 @@
+use crate::{error::BurnoutError, util::http_client};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde::Deserialize;
+use serde_json::{from_str, json};
+
+/// The Telegram Bot API, unless pointed somewhere else.
+const DEFAULT_BASE_URL: &str = "https://api.telegram.org/";
+
+/// The part of a Bot API response that says whether it worked.
+#[derive(Deserialize, Debug)]
+struct TelegramResponse {
+    ok: bool,
+    description: Option<String>,
+}
+
+/// A Telegram chat, messaged by a bot.
+#[derive(Debug, Clone)]
+pub struct Telegram {
+    client: Client,
+    base_url: Url,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl Telegram {
+    pub fn new(bot_token: &str, chat_id: &str) -> Result<Self, BurnoutError> {
+        Self::with_client(bot_token, chat_id, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(
+        bot_token: &str,
+        chat_id: &str,
+        client: Client,
+    ) -> Result<Self, BurnoutError> {
+        Ok(Self {
+            client,
+            base_url: Url::parse(DEFAULT_BASE_URL)
+                .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?,
+            bot_token: bot_token.to_string(),
+            chat_id: chat_id.to_string(),
+        })
+    }
+
+    /// Point requests at a different server, e.g. a mock in tests.
+    pub fn set_base_url(&mut self, base_url: Url) -> &mut Self {
+        self.base_url = base_url;
+
+        self
+    }
+
+    /// Send `text` to the chat.
+    ///
+    /// https://core.telegram.org/bots/api#sendmessage
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, and [`BurnoutError::Telegram`]
+    /// with the description if the Bot API answers with `ok` false, e.g.
+    /// when the bot isn't in the chat. The bot token is in the URL, so it's
+    /// left out of request errors.
+    pub async fn send_message(&self, text: &str) -> Result<(), BurnoutError> {
+        // `./` keeps the `:` in the token from being read as a scheme
+        let url = self
+            .base_url
+            .join(&format!("./bot{}/sendMessage", self.bot_token))
+            .map_err(|_| BurnoutError::InvalidUrl("Telegram bot URL".to_string()))?;
+
+        // a failed call is still JSON, with the reason in the description
+        let body = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(json!({ "chat_id": self.chat_id, "text": text }).to_string())
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?
+            .text()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+
+        let response: TelegramResponse = from_str(&body)?;
+
+        match response.ok {
+            true => Ok(()),
+            false => Err(BurnoutError::Telegram(
+                response
+                    .description
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Telegram;
+    use crate::error::BurnoutError;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::error::Error;
+
+    fn telegram(mock_server: &MockServer) -> Result<Telegram, Box<dyn Error>> {
+        let mut telegram = Telegram::new("123456:ABC-token", "987654")?;
+        telegram.set_base_url(mock_server.base_url().parse()?);
+
+        Ok(telegram)
+    }
+
+    #[tokio::test]
+    async fn test_send_message() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let message = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/bot123456:ABC-token/sendMessage")
+                .header("content-type", "application/json")
+                .json_body(json!({ "chat_id": "987654", "text": "🟨🟨⬜️⬜️ 22/40 hrs" }));
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "ok": true, "result": { "message_id": 1 } }).to_string());
+        });
+
+        telegram(&mock_server)?
+            .send_message("🟨🟨⬜️⬜️ 22/40 hrs")
+            .await?;
+
+        message.assert();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_send_message_not_ok() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(POST).path("/bot123456:ABC-token/sendMessage");
+            then.status(400)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "ok": false,
+                        "error_code": 400,
+                        "description": "Bad Request: chat not found",
+                    })
+                    .to_string(),
+                );
+        });
+
+        let result = telegram(&mock_server)?.send_message("🟨🟨⬜️⬜️").await;
+
+        assert!(matches!(
+            result,
+            Err(BurnoutError::Telegram(description))
+                if description == "Bad Request: chat not found"
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_error_leaves_out_token() -> Result<(), Box<dyn Error>> {
+        let mut telegram = Telegram::new("123456:ABC-token", "987654")?;
+        telegram.set_base_url("http://127.0.0.1:1".parse()?);
+
+        let error = telegram
+            .send_message("🟨🟨⬜️⬜️")
+            .await
+            .expect_err("nothing is listening");
+
+        assert!(!error.to_string().contains("ABC-token"), "{error}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{error::BurnoutError, meter::Unit};
+
+/// The location template used when `LOCATION_TEMPLATE` isn't set.
+pub const DEFAULT_TEMPLATE: &str = "{meter} {hours}/{max} {hours_label}";
+
+/// The location template used for a percentage when `LOCATION_TEMPLATE`
+/// isn't set, since the hours out of the max don't read as one.
+pub const PERCENT_TEMPLATE: &str = "{meter} {quantity}";
+
+/// The location template used when `LOCATION_TEMPLATE` isn't set, for a
+/// meter in `unit`.
+pub fn default_template(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Hours => DEFAULT_TEMPLATE,
+        Unit::Percent => PERCENT_TEMPLATE,
+    }
+}
+
+/// Every placeholder the location can have.
+pub const PLACEHOLDERS: [&str; 12] = [
+    "meter",
+    "hours",
+    "max",
+    "percent",
+    "remaining",
+    "updated",
+    "hours_label",
+    "quantity",
+    "days",
+    "dominant_project",
+    "languages",
+    "caption",
+];
+
+/// Fill each `{name}` placeholder in `template` with its value.
+///
+/// Placeholders without a value are left as they are.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::template::render;
+///
+/// let location = render("{meter} {hours}h", &[("meter", "🟩⬜️".into()), ("hours", "3".into())]);
+/// assert_eq!(location, "🟩⬜️ 3h");
+/// ```
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |rendered, (name, value)| {
+            rendered.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// Check that every `{name}` in `template` is one of the [`PLACEHOLDERS`], so
+/// a typo is caught before it ends up in the profile.
+///
+/// Braces around anything that isn't a name, like `{ }`, are left alone.
+///
+/// # Errors
+///
+/// Returns [`BurnoutError::UnknownPlaceholder`] with the first name that
+/// isn't a placeholder.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::template::validate;
+///
+/// assert!(validate("Coding: {meter} ({percent}%)").is_ok());
+/// assert!(validate("{meter} {hour}h").is_err());
+/// ```
+pub fn validate(template: &str) -> Result<(), BurnoutError> {
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[..end];
+
+        let is_name =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if is_name && !PLACEHOLDERS.contains(&name) {
+            return Err(BurnoutError::UnknownPlaceholder(name.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, validate, DEFAULT_TEMPLATE};
+    use crate::error::BurnoutError;
+
+    #[test]
+    fn test_render() {
+        let values = [
+            ("meter", "🟨🟨⬜️⬜️".to_string()),
+            ("hours", "22".to_string()),
+            ("max", "40".to_string()),
+            ("updated", "14:32".to_string()),
+            ("hours_label", "hours".to_string()),
+        ];
+
+        assert_eq!(render(DEFAULT_TEMPLATE, &values), "🟨🟨⬜️⬜️ 22/40 hours");
+        assert_eq!(
+            render("{meter} {hours}/{max}h · upd {updated}", &values),
+            "🟨🟨⬜️⬜️ 22/40h · upd 14:32"
+        );
+        assert_eq!(render("{meter} {unknown}", &values), "🟨🟨⬜️⬜️ {unknown}");
+    }
+
+    #[test]
+    fn test_render_hours_label() {
+        let location = |label: &str| {
+            render(
+                DEFAULT_TEMPLATE,
+                &[
+                    ("meter", "🟩⬜️".to_string()),
+                    ("hours", "12".to_string()),
+                    ("max", "40".to_string()),
+                    ("hours_label", label.to_string()),
+                ],
+            )
+        };
+
+        assert_eq!(location("hours"), "🟩⬜️ 12/40 hours");
+        assert_eq!(location("h"), "🟩⬜️ 12/40 h");
+        assert_eq!(location("hrs"), "🟩⬜️ 12/40 hrs");
+        assert_eq!(location("Stunden"), "🟩⬜️ 12/40 Stunden");
+    }
+
+    #[test]
+    fn test_render_each_placeholder() {
+        let values = [
+            ("meter", "🟨🟨⬜️⬜️".to_string()),
+            ("hours", "22".to_string()),
+            ("max", "40".to_string()),
+            ("percent", "55".to_string()),
+            ("remaining", "18".to_string()),
+        ];
+
+        assert_eq!(render("{meter}", &values), "🟨🟨⬜️⬜️");
+        assert_eq!(render("{hours}h", &values), "22h");
+        assert_eq!(render("max {max}", &values), "max 40");
+        assert_eq!(
+            render("Coding: {meter} ({percent}%)", &values),
+            "Coding: 🟨🟨⬜️⬜️ (55%)"
+        );
+        assert_eq!(render("{remaining}h left", &values), "18h left");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(DEFAULT_TEMPLATE).is_ok());
+        assert!(validate("Coding: {meter} ({percent}%), {remaining}h left").is_ok());
+        assert!(validate("{meter} { } {} {hours").is_ok());
+
+        assert!(matches!(
+            validate("{meter} {precent}%"),
+            Err(BurnoutError::UnknownPlaceholder(name)) if name == "precent"
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    meter::Builder,
+    preview::{Key, Preview},
+};
+use crossterm::{
+    cursor::MoveToColumn,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    style::Print,
+    terminal::{self, Clear, ClearType},
+};
+use std::{error::Error, io};
+
+/// Scrub the hours of a meter from `BURNOUT_LIMIT` and `METER_LENGTH` with
+/// the arrow keys, redrawing it on one line as they're pressed, until `q` or
+/// escape.
+///
+/// Left and right change the hours by 1, up and down by 10, `p` switches to
+/// the next palette, and `a` switches to ASCII and back.
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let mut preview = Preview::new(Builder::new());
+
+    println!("←/→ 1h, ↑/↓ 10h, p for the next palette, a for ASCII, q to quit");
+
+    let raw_mode = RawMode::enable()?;
+    let result = draw_until_quit(&mut preview);
+    drop(raw_mode);
+
+    println!();
+
+    result
+}
+
+fn draw_until_quit(preview: &mut Preview) -> Result<(), Box<dyn Error>> {
+    let mut stdout = io::stdout();
+
+    loop {
+        let line = preview.render()?;
+
+        // go back to the start of the line and clear it
+        execute!(
+            stdout,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(line)
+        )?;
+
+        if let Event::Key(event) = event::read()? {
+            if let Some(key) = key(event) {
+                if !preview.apply(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// The key the preview understands for a key `event`, if any.
+fn key(event: KeyEvent) -> Option<Key> {
+    // some terminals report the release too
+    if event.kind != KeyEventKind::Press {
+        return None;
+    }
+
+    match event.code {
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::Left => Some(Key::Left),
+        // raw mode leaves ctrl-c to us
+        KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => Some(Key::Quit),
+        KeyCode::Char('a' | 'A') => Some(Key::ToggleAscii),
+        KeyCode::Char('p' | 'P') => Some(Key::NextPalette),
+        KeyCode::Char('q' | 'Q') | KeyCode::Esc => Some(Key::Quit),
+        _ => None,
+    }
+}
+
+/// Keeps the terminal in raw mode, so keys arrive as they're pressed rather
+/// than a line at a time, until dropped.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::key;
+    use crate::preview::Key;
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+
+    #[test]
+    fn test_key() {
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(key(press(KeyCode::Up)), Some(Key::Up));
+        assert_eq!(key(press(KeyCode::Left)), Some(Key::Left));
+        assert_eq!(key(press(KeyCode::Char('a'))), Some(Key::ToggleAscii));
+        assert_eq!(key(press(KeyCode::Char('p'))), Some(Key::NextPalette));
+        assert_eq!(key(press(KeyCode::Esc)), Some(Key::Quit));
+        assert_eq!(key(press(KeyCode::Char('x'))), None);
+
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(key(ctrl_c), Some(Key::Quit));
+        assert_eq!(key(press(KeyCode::Char('c'))), None);
+
+        let release = KeyEvent::new_with_kind_and_state(
+            KeyCode::Char('q'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+            KeyEventState::NONE,
+        );
+        assert_eq!(key(release), None);
+    }
+}
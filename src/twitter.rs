@@ -1,15 +1,169 @@
-use reqwest::{Client, Method, Url};
+use crate::{
+    error::BurnoutError,
+    ratelimit::{self, RateLimit},
+    redact::log_request,
+    util::http_client,
+};
+use reqwest::{Client, Method, Request, Response, StatusCode, Url};
 use reqwest_oauth1::OAuthClientProvider;
 use reqwest_oauth1::Secrets;
 use serde::{Deserialize, Serialize};
 use serde_json::from_str;
-use std::error::Error;
+use std::{cell::Cell, fmt, str::FromStr, time::Duration};
+use time::OffsetDateTime;
+
+/// The most characters Twitter allows in the profile location.
+pub const LOCATION_MAX_LENGTH: usize = 30;
+
+/// The most characters Twitter allows in the profile description.
+pub const DESCRIPTION_MAX_LENGTH: usize = 160;
+
 #[derive(Deserialize, Debug, Serialize)]
 pub struct Profile {
     pub location: Option<String>,
+    pub description: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// The ellipsis put where text was cut off to fit.
+const ELLIPSIS: char = '…';
+
+/// How long Twitter counts `text` as, by the weighting of its twitter-text
+/// library.
+///
+/// Latin, Greek, and Cyrillic letters, along with common punctuation,
+/// count as 1. Anything else, such as CJK characters and emoji, counts as 2,
+/// and the variation selector, skin tone, or joined emoji after an emoji
+/// don't add to it. So `🟨🟨⬜️` is 6, not the 4 characters it's made of.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::twitter::weighted_length;
+///
+/// assert_eq!(weighted_length("22/40 hours"), 11);
+/// assert_eq!(weighted_length("🟨🟨⬜️"), 6);
+/// ```
+pub fn weighted_length(text: &str) -> usize {
+    let mut length = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{FE0E}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}' => {}
+            // the emoji after a joiner is part of the one before it
+            '\u{200D}' => {
+                chars.next();
+            }
+            c => length += char_weight(c),
+        }
+    }
+
+    length
+}
+
+/// How much an emoji, like each cell of the meter, counts toward Twitter's
+/// limits.
+pub const EMOJI_WEIGHT: usize = 2;
+
+/// How much one character counts toward Twitter's limits.
+fn char_weight(c: char) -> usize {
+    match c as u32 {
+        0..=4351 | 8192..=8205 | 8208..=8223 | 8242..=8247 => 1,
+        _ => EMOJI_WEIGHT,
+    }
+}
+
+/// Shorten `text` to fit in `max` by [`weighted_length`], cutting the text
+/// after `meter` and ending it with `…` so the meter is kept whole.
+///
+/// Returns `text` as it is if it already fits, or if it doesn't have `meter`
+/// in it or the meter doesn't fit on its own, so there's nothing to cut.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::twitter::truncate_to_fit;
+///
+/// let location = "🟨🟨🟨⬜️⬜️ 22/40 hours of Rust this week";
+/// assert_eq!(
+///     truncate_to_fit(location, "🟨🟨🟨⬜️⬜️", 30),
+///     "🟨🟨🟨⬜️⬜️ 22/40 hours of Ru…"
+/// );
+/// ```
+pub fn truncate_to_fit(text: &str, meter: &str, max: usize) -> String {
+    if weighted_length(text) <= max {
+        return text.to_string();
+    }
+
+    let Some(start) = text.find(meter) else {
+        return text.to_string();
+    };
+    let (kept, trailing) = text.split_at(start + meter.len());
+
+    let Some(room) = max.checked_sub(weighted_length(kept) + char_weight(ELLIPSIS)) else {
+        return text.to_string();
+    };
+
+    let mut length = 0;
+    let trailing: String = trailing
+        .chars()
+        .take_while(|c| {
+            length += char_weight(*c);
+            length <= room
+        })
+        .collect();
+
+    format!("{kept}{}{ELLIPSIS}", trailing.trim_end())
+}
+
+/// Which field of the profile the meter goes in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetField {
+    #[default]
+    Location,
+    /// The bio, which has room for more and isn't flagged as often.
+    Description,
+}
+
+impl TargetField {
+    /// The most characters Twitter allows in the field.
+    pub fn max_length(&self) -> usize {
+        match self {
+            Self::Location => LOCATION_MAX_LENGTH,
+            Self::Description => DESCRIPTION_MAX_LENGTH,
+        }
+    }
+}
+
+impl fmt::Display for TargetField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Location => write!(f, "location"),
+            Self::Description => write!(f, "description"),
+        }
+    }
+}
+
+impl FromStr for TargetField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "location" => Ok(Self::Location),
+            "description" => Ok(Self::Description),
+            _ => Err(format!("Unknown TWITTER_TARGET_FIELD value: {s}")),
+        }
+    }
+}
+
+/// A posted status.
+#[derive(Deserialize, Debug, Serialize)]
+pub struct Tweet {
+    pub id_str: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Credentials {
     pub consumer_key: String,
     pub consumer_secret: String,
@@ -20,25 +174,152 @@ pub struct Credentials {
 pub trait FormField: Into<String> + Serialize + Clone {}
 impl<T> FormField for T where T: Into<String> + Serialize + Clone {}
 
+/// The ways a request to Twitter can fail that might be worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// A 5xx response.
+    ServerError,
+    /// A `429 Too Many Requests` response.
+    RateLimited,
+    /// The request never got a response, e.g. the connection failed.
+    Transport,
+}
+
+impl Failure {
+    /// The failure a response with `status` counts as, if any.
+    fn from_status(status: StatusCode) -> Option<Self> {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => Some(Self::RateLimited),
+            status if status.is_server_error() => Some(Self::ServerError),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ServerError => write!(f, "server error"),
+            Self::RateLimited => write!(f, "rate limited"),
+            Self::Transport => write!(f, "transport error"),
+        }
+    }
+}
+
+/// Which failures to retry, from the most conservative to the most
+/// aggressive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetryOn {
+    /// Never retry.
+    None,
+    /// Only 5xx responses.
+    #[default]
+    ServerErrors,
+    /// 5xx and 429 responses.
+    ServerErrorsAndRateLimits,
+    /// Any failure, including requests that never got a response.
+    Transport,
+}
+
+impl RetryOn {
+    /// Whether the policy retries `failure`.
+    pub fn is_retryable(&self, failure: Failure) -> bool {
+        match self {
+            Self::None => false,
+            Self::ServerErrors => failure == Failure::ServerError,
+            Self::ServerErrorsAndRateLimits => failure != Failure::Transport,
+            Self::Transport => true,
+        }
+    }
+}
+
+impl FromStr for RetryOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "5xx" => Ok(Self::ServerErrors),
+            "5xx+429" => Ok(Self::ServerErrorsAndRateLimits),
+            "transport" => Ok(Self::Transport),
+            _ => Err(format!("Unknown TWITTER_RETRY_ON value: {s}")),
+        }
+    }
+}
+
+/// How many times a failed request is retried by default.
+const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// The wait before the first retry by default, doubled for each one after.
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
 #[derive(Debug, Clone)]
 pub struct Twitter {
     client: Client,
     credentials: Credentials,
     base_url: Url,
+    retry_on: RetryOn,
+    max_retries: u32,
+    base_delay: Duration,
+    max_rate_limit_wait: Option<Duration>,
+    rate_limit: Cell<Option<RateLimit>>,
 }
 
 impl Twitter {
-    pub fn new(credentials: Credentials) -> Result<Self, Box<dyn Error>> {
-        let client = Client::new();
+    pub fn new(credentials: Credentials) -> Result<Self, BurnoutError> {
+        Self::with_client(credentials, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(credentials: Credentials, client: Client) -> Result<Self, BurnoutError> {
         let base_url = "https://api.twitter.com".parse().ok().unwrap();
 
         Ok(Self {
             client,
             credentials,
             base_url,
+            retry_on: RetryOn::default(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_rate_limit_wait: None,
+            rate_limit: Cell::new(None),
         })
     }
 
+    /// Retry requests that fail in the ways `retry_on` covers up to
+    /// `max_retries` times, waiting `base_delay` before the first retry and
+    /// twice as long before each one after.
+    pub fn with_retries(
+        mut self,
+        retry_on: RetryOn,
+        max_retries: u32,
+        base_delay: Duration,
+    ) -> Self {
+        self.retry_on = retry_on;
+        self.max_retries = max_retries;
+        self.base_delay = base_delay;
+
+        self
+    }
+
+    /// Set which failures are retried.
+    pub fn set_retry_on(&mut self, retry_on: RetryOn) -> &mut Self {
+        self.retry_on = retry_on;
+
+        self
+    }
+
+    /// When a profile update is rate limited, wait for the limit to reset and
+    /// try once more if that's no longer than `max_wait`, rather than
+    /// returning [`BurnoutError::TwitterRateLimited`] straight away. `None`,
+    /// the default, never waits.
+    pub fn set_max_rate_limit_wait(&mut self, max_wait: Option<Duration>) -> &mut Self {
+        self.max_rate_limit_wait = max_wait;
+
+        self
+    }
+
     /// Update the location field in your Twitter profile from the Twitter API
     ///
     /// https://developer.twitter.com/en/docs/accounts-and-users/manage-account-settings/api-reference/post-account-update_profile
@@ -51,58 +332,267 @@ impl Twitter {
     /// # Examples
     ///
     /// ```no_run
-    /// use twitter::update_twitter_profile_location;
-    /// use std::error::Error;
+    /// use rust_burnout_meter::{
+    ///     error::BurnoutError,
+    ///     twitter::{Credentials, Twitter},
+    /// };
     /// #[tokio::main]
-    /// async fn main() -> Result<(), Box<dyn Error>> {
+    /// async fn main() -> Result<(), BurnoutError> {
+    ///     let credentials = Credentials {
+    ///         consumer_key: "...".to_string(),
+    ///         consumer_secret: "...".to_string(),
+    ///         access_token: "...".to_string(),
+    ///         access_token_secret: "...".to_string(),
+    ///     };
+    ///     let twitter = Twitter::new(credentials)?;
     ///     let location = "San Francisco, CA";
-    ///     let result = update_twitter_profile_location(location, None).await?;
+    ///     let result = twitter.update_location(location).await?;
     ///     println!("Updated location to {}", result.location.unwrap());
     ///     Ok(())
     /// }
     /// ```
-    pub async fn update_location(
+    pub async fn update_location(&self, location: impl FormField) -> Result<Profile, BurnoutError> {
+        self.update_profile(TargetField::Location, location).await
+    }
+
+    /// Update the profile description (bio) from the Twitter API
+    ///
+    /// https://developer.twitter.com/en/docs/twitter-api/v1/accounts-and-users/manage-account-settings/api-reference/post-account-update_profile
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn update_description(
+        &self,
+        description: impl FormField,
+    ) -> Result<Profile, BurnoutError> {
+        self.update_profile(TargetField::Description, description)
+            .await
+    }
+
+    /// Set `field` of the profile to `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BurnoutError::LocationTooLong`] without sending anything if
+    /// `value` is over the field's limit by [`weighted_length`],
+    /// [`BurnoutError::TwitterRateLimited`] with when the limit resets if
+    /// it's still rate limited after any wait set by
+    /// [`Twitter::set_max_rate_limit_wait`], and an error if the request
+    /// fails or the response cannot be parsed.
+    pub async fn update_profile(
         &self,
-        location: impl FormField,
-    ) -> Result<Profile, Box<dyn Error>> {
+        field: TargetField,
+        value: impl FormField,
+    ) -> Result<Profile, BurnoutError> {
         let endpoint = "/1.1/account/update_profile.json";
 
-        let url = self.base_url.join(endpoint).unwrap();
+        let length = weighted_length(&value.clone().into());
+        if length > field.max_length() {
+            return Err(BurnoutError::LocationTooLong {
+                length,
+                max: field.max_length(),
+            });
+        }
 
-        let secrets = Secrets::new(
-            &self.credentials.consumer_key,
-            &self.credentials.consumer_secret,
-        )
-        .token(
-            &self.credentials.access_token,
-            &self.credentials.access_token_secret,
-        );
+        let mut waited = false;
 
-        let client = self.client.clone();
+        let response = loop {
+            let response = self
+                .post_form(endpoint, &field.to_string(), value.clone())
+                .await?;
 
-        let response = client
-            .oauth1(secrets)
-            .request(Method::POST, url)
-            .form(&[("location", location)]);
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                break response;
+            }
 
-        let response = response.send().await?;
+            let reset_at = ratelimit::reset(response.headers())
+                .and_then(|reset| OffsetDateTime::from_unix_timestamp(reset).ok());
+
+            match self.rate_limit_wait(reset_at, OffsetDateTime::now_utc()) {
+                Some(wait) if !waited => {
+                    log::warn!("Twitter rate limited the update, retrying in {wait:?}");
+
+                    tokio::time::sleep(wait).await;
+                    waited = true;
+                }
+                _ => return Err(BurnoutError::TwitterRateLimited { reset_at }),
+            }
+        };
 
         let body = response.text().await?;
-        let result: Result<Profile, _> = from_str(&body);
 
-        match result {
-            Ok(update) => Ok(update),
-            Err(e) => Err(e.into()),
+        Ok(from_str(&body)?)
+    }
+
+    /// How long to wait at `now` for a rate limit resetting at `reset_at`,
+    /// or `None` if it's not known or longer than the most set to wait.
+    fn rate_limit_wait(
+        &self,
+        reset_at: Option<OffsetDateTime>,
+        now: OffsetDateTime,
+    ) -> Option<Duration> {
+        let wait = (reset_at? - now).max(time::Duration::ZERO).unsigned_abs();
+
+        (wait <= self.max_rate_limit_wait?).then_some(wait)
+    }
+
+    /// Post a status from the Twitter API
+    ///
+    /// https://developer.twitter.com/en/docs/twitter-api/v1/tweets/post-and-engage/api-reference/post-statuses-update
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn post_update(&self, text: impl FormField) -> Result<Tweet, BurnoutError> {
+        let endpoint = "/1.1/statuses/update.json";
+
+        let response = self.post_form(endpoint, "status", text).await?;
+
+        let body = response.text().await?;
+
+        Ok(from_str(&body)?)
+    }
+
+    /// Build a POST of a form with a single `field` to `url`, signed with the
+    /// credentials.
+    fn signed_form(
+        &self,
+        url: Url,
+        field: &str,
+        value: impl FormField,
+    ) -> Result<Request, BurnoutError> {
+        let request = self
+            .client
+            .clone()
+            .oauth1(self.secrets())
+            .request(Method::POST, url)
+            .form(&[(field, value)])
+            .generate_signature()
+            .map_err(reqwest_oauth1::Error::from)?
+            .build()?;
+
+        Ok(request)
+    }
+
+    /// Post a form with a single `field` to `endpoint`, retrying the failures
+    /// set by [`Twitter::with_retries`].
+    ///
+    /// The request is signed again for each try, and logged with its
+    /// secrets masked. After the last retry the response or error is
+    /// returned as it is.
+    async fn post_form(
+        &self,
+        endpoint: &str,
+        field: &str,
+        value: impl FormField,
+    ) -> Result<Response, BurnoutError> {
+        let url = self
+            .base_url
+            .join(endpoint)
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+        let mut attempt = 0;
+
+        loop {
+            let request = self.signed_form(url.clone(), field, value.clone())?;
+            log_request(&request);
+
+            let result = self.client.execute(request).await;
+
+            let failure = match &result {
+                Ok(response) => Failure::from_status(response.status()),
+                Err(_) => Some(Failure::Transport),
+            };
+
+            match failure {
+                Some(failure)
+                    if attempt < self.max_retries && self.retry_on.is_retryable(failure) =>
+                {
+                    let delay = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+                    log::warn!("Twitter request failed with a {failure}, retrying in {delay:?}");
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => {
+                    let response = result?;
+                    self.rate_limit
+                        .set(RateLimit::from_headers(response.headers()));
+
+                    return Ok(response);
+                }
+            }
         }
     }
+
+    /// The rate limit from the last response, if Twitter sent one.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit.get()
+    }
+
+    /// Point requests at a different server, e.g. a mock in tests.
+    pub fn set_base_url(&mut self, base_url: Url) -> &mut Self {
+        self.base_url = base_url;
+
+        self
+    }
+
+    /// The OAuth 1.0a secrets to sign requests with.
+    fn secrets(&self) -> Secrets<'_> {
+        Secrets::new(
+            &self.credentials.consumer_key,
+            &self.credentials.consumer_secret,
+        )
+        .token(
+            &self.credentials.access_token,
+            &self.credentials.access_token_secret,
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Credentials, Twitter};
+    use super::{
+        truncate_to_fit, weighted_length, Credentials, Failure, RetryOn, TargetField, Twitter,
+    };
+    use crate::{error::BurnoutError, redact::redact_request};
     use httpmock::prelude::*;
     use serde_json::json;
-    use std::error::Error;
+    use std::{
+        error::Error,
+        time::{Duration, Instant},
+    };
+    use time::{macros::datetime, OffsetDateTime};
+
+    fn credentials() -> Credentials {
+        Credentials {
+            consumer_key: "consumer_key".to_string(),
+            consumer_secret: "consumer_secret".to_string(),
+            access_token: "access_token".to_string(),
+            access_token_secret: "access_token_secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signed_form_is_logged_redacted() -> Result<(), Box<dyn Error>> {
+        let client = Twitter::new(credentials())?;
+        let request = client.signed_form(
+            "https://api.twitter.com/1.1/account/update_profile.json".parse()?,
+            "location",
+            "Kalamazoo, MI",
+        )?;
+
+        // the signature names the consumer key and access token
+        let authorization = request.headers()["authorization"].to_str()?;
+        assert!(authorization.contains("access_token"), "{authorization}");
+
+        let logged = redact_request(&request);
+        assert!(!logged.contains("consumer_key"), "{logged}");
+        assert!(!logged.contains("access_token"), "{logged}");
+        assert!(logged.contains("authorization: ****"), "{logged}");
+
+        Ok(())
+    }
 
     #[tokio::test]
     async fn test_update_twitter_profile_location() -> Result<(), Box<dyn Error>> {
@@ -119,14 +609,7 @@ mod tests {
                 .body(json!({ "location": mock_location }).to_string());
         });
 
-        let credentials = Credentials {
-            consumer_key: "consumer_key".to_string(),
-            consumer_secret: "consumer_secret".to_string(),
-            access_token: "access_token".to_string(),
-            access_token_secret: "access_token_secret".to_string(),
-        };
-
-        let mut client = Twitter::new(credentials)?;
+        let mut client = Twitter::new(credentials())?;
 
         client.base_url = mock_server.base_url().parse()?;
         let result = client.update_location(mock_location).await;
@@ -138,4 +621,279 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_update_twitter_profile_description() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock_description = "Writing Rust · 🟨🟨🟨⬜️⬜️ 22/40 hours";
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/account/update_profile.json")
+                .header_exists("authorization")
+                .x_www_form_urlencoded_tuple("description", mock_description);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({ "location": "Kalamazoo, MI", "description": mock_description })
+                        .to_string(),
+                );
+        });
+
+        let mut client = Twitter::new(credentials())?;
+
+        client.set_base_url(mock_server.base_url().parse()?);
+        let profile = client.update_description(mock_description).await?;
+
+        mock.assert();
+        assert_eq!(profile.description, Some(mock_description.to_string()));
+        assert_eq!(profile.location, Some("Kalamazoo, MI".to_string()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_location_too_long() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(200);
+        });
+
+        let mut client = Twitter::new(credentials())?;
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        // 28 characters, but 31 by Twitter's count
+        let location = "🟨🟨🟨⬜️⬜️⬜️⬜️⬜️ 22.75/40 hours";
+        let result = client.update_location(location).await;
+
+        mock.assert_hits(0);
+        assert!(matches!(
+            result,
+            Err(BurnoutError::LocationTooLong {
+                length: 31,
+                max: 30
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_location_rate_limited() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST).path("/1.1/account/update_profile.json");
+            then.status(429)
+                .header("content-type", "application/json")
+                .header("x-rate-limit-remaining", "0")
+                .header("x-rate-limit-reset", "1674000000")
+                .body(
+                    json!({ "errors": [{ "code": 88, "message": "Rate limit exceeded" }] })
+                        .to_string(),
+                );
+        });
+
+        let mut client = Twitter::new(credentials())?;
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let result = client.update_location("🟨🟨⬜️⬜️").await;
+
+        mock.assert();
+        assert!(matches!(
+            result,
+            Err(BurnoutError::TwitterRateLimited { reset_at: Some(reset_at) })
+                if reset_at == datetime!(2023-01-18 00:00:00 UTC)
+        ));
+
+        // a reset that's already passed is waited for, but only once
+        client.set_max_rate_limit_wait(Some(Duration::from_secs(60)));
+        let result = client.update_location("🟨🟨⬜️⬜️").await;
+
+        mock.assert_hits(3);
+        assert!(matches!(
+            result,
+            Err(BurnoutError::TwitterRateLimited { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limit_wait() -> Result<(), Box<dyn Error>> {
+        let mut client = Twitter::new(credentials())?;
+        let now = datetime!(2023-01-18 00:00:00 UTC);
+        let reset_at = Some(now + time::Duration::seconds(90));
+
+        // never waits unless set to
+        assert_eq!(client.rate_limit_wait(reset_at, now), None);
+
+        client.set_max_rate_limit_wait(Some(Duration::from_secs(120)));
+        assert_eq!(
+            client.rate_limit_wait(reset_at, now),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            client.rate_limit_wait(Some(OffsetDateTime::UNIX_EPOCH), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(client.rate_limit_wait(None, now), None);
+
+        client.set_max_rate_limit_wait(Some(Duration::from_secs(60)));
+        assert_eq!(client.rate_limit_wait(reset_at, now), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weighted_length() {
+        assert_eq!(weighted_length(""), 0);
+        assert_eq!(weighted_length("Kalamazoo, MI"), 13);
+        assert_eq!(weighted_length("🟩⬜️⬜️⬜️"), 8);
+        // a ZWJ sequence and a skin tone count as one emoji
+        assert_eq!(weighted_length("🧑‍💻"), 2);
+        assert_eq!(weighted_length("👍🏽"), 2);
+        assert_eq!(weighted_length("日本"), 4);
+    }
+
+    #[test]
+    fn test_truncate_to_fit() {
+        let meter = "🟨🟨🟨⬜️⬜️⬜️⬜️⬜️";
+        let location = format!("{meter} 22/40 hours of Rust");
+
+        let truncated = truncate_to_fit(&location, meter, 30);
+        assert_eq!(truncated, format!("{meter} 22/40 hours…"));
+        assert_eq!(weighted_length(&truncated), 30);
+
+        // a space left at the end of the cut is dropped
+        assert_eq!(
+            truncate_to_fit(&location, meter, 31),
+            format!("{meter} 22/40 hours…")
+        );
+
+        // the text before the meter is kept too
+        assert_eq!(
+            truncate_to_fit(&format!("Me {meter} 22/40 hours"), meter, 30),
+            format!("Me {meter} 22/40 ho…")
+        );
+
+        // fits already, or can't be cut without cutting the meter
+        assert_eq!(truncate_to_fit("22/40 hours", meter, 30), "22/40 hours");
+        assert_eq!(truncate_to_fit(&location, meter, 10), location);
+        assert_eq!(truncate_to_fit(&location, "🟥", 30), location);
+    }
+
+    #[test]
+    fn test_parse_target_field() {
+        assert_eq!("location".parse(), Ok(TargetField::Location));
+        assert_eq!("Description".parse(), Ok(TargetField::Description));
+        assert!("bio".parse::<TargetField>().is_err());
+        assert_eq!(TargetField::Description.max_length(), 160);
+    }
+
+    #[tokio::test]
+    async fn test_post_update() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let mock_status = "🟨🟨🟨⬜️⬜️ 22/40 hours";
+        let mock = mock_server.mock(|when, then| {
+            when.method(POST)
+                .path("/1.1/statuses/update.json")
+                .header_exists("authorization")
+                .x_www_form_urlencoded_tuple("status", mock_status);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "id_str": "1615000000000000000", "text": mock_status }).to_string());
+        });
+
+        let mut client = Twitter::new(credentials())?;
+
+        client.set_base_url(mock_server.base_url().parse()?);
+        let tweet = client.post_update(mock_status).await?;
+
+        mock.assert();
+        assert_eq!(tweet.text, mock_status);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_policies() {
+        use Failure::*;
+
+        let cases = [
+            (RetryOn::None, [false, false, false]),
+            (RetryOn::ServerErrors, [true, false, false]),
+            (RetryOn::ServerErrorsAndRateLimits, [true, true, false]),
+            (RetryOn::Transport, [true, true, true]),
+        ];
+
+        for (retry_on, expected) in cases {
+            let retried = [ServerError, RateLimited, Transport].map(|f| retry_on.is_retryable(f));
+            assert_eq!(retried, expected, "{retry_on:?}");
+        }
+
+        assert_eq!("5xx".parse(), Ok(RetryOn::ServerErrors));
+        assert_eq!("5xx+429".parse(), Ok(RetryOn::ServerErrorsAndRateLimits));
+        assert_eq!("transport".parse(), Ok(RetryOn::Transport));
+        assert_eq!("none".parse(), Ok(RetryOn::None));
+        assert!("always".parse::<RetryOn>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_status() -> Result<(), Box<dyn Error>> {
+        // each policy against each status, and whether it retried
+        let cases = [
+            (RetryOn::None, 500, false),
+            (RetryOn::None, 429, false),
+            (RetryOn::ServerErrors, 500, true),
+            (RetryOn::ServerErrors, 429, false),
+            (RetryOn::ServerErrorsAndRateLimits, 500, true),
+            (RetryOn::ServerErrorsAndRateLimits, 429, true),
+            (RetryOn::Transport, 500, true),
+            (RetryOn::Transport, 429, true),
+        ];
+
+        for (retry_on, status, retried) in cases {
+            let mock_server = MockServer::start();
+            let mock = mock_server.mock(|when, then| {
+                when.method(POST).path("/1.1/account/update_profile.json");
+                then.status(status);
+            });
+
+            let mut client = Twitter::new(credentials())?.with_retries(retry_on, 2, Duration::ZERO);
+            client.set_base_url(mock_server.base_url().parse()?);
+
+            // the failure still comes back after the retries
+            assert!(client.update_location("Kalamazoo, MI").await.is_err());
+
+            let hits = if retried { 3 } else { 1 };
+            mock.assert_hits(hits);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_transport_error() -> Result<(), Box<dyn Error>> {
+        for (retry_on, retried) in [
+            (RetryOn::ServerErrorsAndRateLimits, false),
+            (RetryOn::Transport, true),
+        ] {
+            // nothing listens on port 1, so the connection is refused
+            let mut client =
+                Twitter::new(credentials())?.with_retries(retry_on, 1, Duration::from_millis(200));
+            client.set_base_url("http://127.0.0.1:1".parse()?);
+
+            let started = Instant::now();
+            assert!(client.update_location("Kalamazoo, MI").await.is_err());
+
+            // only a retry waits
+            assert_eq!(
+                started.elapsed() >= Duration::from_millis(200),
+                retried,
+                "{retry_on:?}"
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -1,31 +1,522 @@
+use crate::error::BurnoutError;
 use dotenv::dotenv;
-use std::{env, error::Error, str::FromStr, sync::Once};
+#[cfg(feature = "network")]
+use reqwest::Client;
+#[cfg(feature = "network")]
+use std::time::Duration;
+use std::{env, str::FromStr, sync::Once};
 
 static INIT: Once = Once::new();
 
-pub fn get_env_var<T: FromStr>(key: &str) -> Result<T, Box<dyn Error>> {
+pub fn get_env_var<T: FromStr>(key: &str) -> Result<T, BurnoutError> {
     INIT.call_once(|| match dotenv().ok() {
-        Some(_) => println!(".env file detected, loading..."),
-        None => println!("No .env file found."),
+        Some(_) => log::debug!(".env file detected, loading..."),
+        None => log::debug!("No .env file found."),
     });
 
     match env::var(key) {
         Ok(val) => {
-            let parsed: Result<T, Box<dyn Error>> = match val.parse::<T>() {
+            let parsed: Result<T, BurnoutError> = match val.parse::<T>() {
                 Ok(parsed) => Ok(parsed),
-                Err(_) => Err(format!("Failed to parse {key}").into()),
+                Err(_) => Err(BurnoutError::InvalidEnv(key.to_string())),
             };
 
             parsed
         }
-        Err(_) => Err(format!("{key} not set").into()),
+        Err(_) => Err(BurnoutError::MissingEnv(key.to_string())),
     }
 }
 
-pub fn days_since_monday(offset_hours: i8) -> i64 {
+/// Count the whole days from Monday to `now` in the given timezone offset, so
+/// Monday is 0 and Sunday is 6.
+///
+/// Going back that many days from today lands on Monday, even when the week
+/// straddles the new year.
+pub fn days_since_monday(now: time::OffsetDateTime, offset_hours: i8) -> i64 {
+    days_since_week_start(now, offset_hours, time::Weekday::Monday)
+}
+
+/// Count the whole days from Monday to `now` at `offset`, which can be part
+/// of an hour, like UTC+5:30 in India or UTC+5:45 in Nepal.
+pub fn days_since_monday_offset(now: time::OffsetDateTime, offset: time::UtcOffset) -> i64 {
+    days_since_week_start_offset(now, offset, time::Weekday::Monday)
+}
+
+/// Count the whole days from the `start` of the week to `now` in the given
+/// timezone offset, so the start day is 0 and the day before it is 6.
+pub fn days_since_week_start(
+    now: time::OffsetDateTime,
+    offset_hours: i8,
+    start: time::Weekday,
+) -> i64 {
     let offset = time::UtcOffset::from_hms(offset_hours, 0, 0).unwrap_or(time::UtcOffset::UTC);
-    let now = time::OffsetDateTime::now_utc().to_offset(offset);
-    let monday = now - time::Duration::days(now.weekday().number_from_monday() as i64);
-    let days = now - monday;
-    days.whole_days()
+
+    days_since_week_start_offset(now, offset, start)
+}
+
+/// Count the whole days from the `start` of the week to `now` at `offset`.
+pub fn days_since_week_start_offset(
+    now: time::OffsetDateTime,
+    offset: time::UtcOffset,
+    start: time::Weekday,
+) -> i64 {
+    let today = now.to_offset(offset).date().weekday();
+    let days = today.number_days_from_monday() as i64 - start.number_days_from_monday() as i64;
+
+    days.rem_euclid(7)
+}
+
+/// Parse a day of the week for `WEEK_START`, e.g. `sunday` or `Sun`.
+///
+/// # Errors
+///
+/// Returns an error if it isn't a day of the week.
+pub fn parse_weekday(value: &str) -> Result<time::Weekday, String> {
+    use time::Weekday::*;
+
+    let value = value.trim().to_lowercase();
+
+    [
+        Monday, Tuesday, Wednesday, Thursday, Friday, Saturday, Sunday,
+    ]
+    .into_iter()
+    .find(|day| {
+        let name = day.to_string().to_lowercase();
+
+        value == name || value == name[..3]
+    })
+    .ok_or_else(|| format!("Unknown WEEK_START value: {value}"))
+}
+
+/// The offset of `offset_minutes` from UTC, or UTC if it's a day or more.
+pub fn offset_from_minutes(offset_minutes: i16) -> time::UtcOffset {
+    time::UtcOffset::from_whole_seconds(offset_minutes as i32 * 60).unwrap_or(time::UtcOffset::UTC)
+}
+
+/// Format `now` as a compact `HH:MM` timestamp in the given timezone offset.
+pub fn format_updated(now: time::OffsetDateTime, offset_hours: i8) -> String {
+    let offset = time::UtcOffset::from_hms(offset_hours, 0, 0).unwrap_or(time::UtcOffset::UTC);
+
+    format_updated_offset(now, offset)
+}
+
+/// Format `now` as a compact `HH:MM` timestamp at `offset`.
+pub fn format_updated_offset(now: time::OffsetDateTime, offset: time::UtcOffset) -> String {
+    now.to_offset(offset)
+        .format(time::macros::format_description!("[hour]:[minute]"))
+        .expect("Failed to format time")
+}
+
+/// Identify the ISO week `now` falls in for the given timezone offset, e.g.
+/// `2023-W03`, so runs in the same week can be grouped together.
+pub fn week_id(now: time::OffsetDateTime, offset_hours: i8) -> String {
+    week_id_starting(now, offset_hours, time::Weekday::Monday)
+}
+
+/// Identify the week `now` falls in like [`week_id`], for weeks beginning
+/// on `start`. Each is named for the ISO week most of its days are in.
+pub fn week_id_starting(
+    now: time::OffsetDateTime,
+    offset_hours: i8,
+    start: time::Weekday,
+) -> String {
+    let offset = time::UtcOffset::from_hms(offset_hours, 0, 0).unwrap_or(time::UtcOffset::UTC);
+
+    week_id_starting_offset(now, offset, start)
+}
+
+/// Identify the week `now` falls in at `offset`, for weeks beginning on `start`.
+pub fn week_id_starting_offset(
+    now: time::OffsetDateTime,
+    offset: time::UtcOffset,
+    start: time::Weekday,
+) -> String {
+    // move the start day onto Monday so the days after it stay in its week
+    let shift = (7 - start.number_days_from_monday() as i64) % 7;
+    let (year, week, _) = (now.to_offset(offset) + time::Duration::days(shift)).to_iso_week_date();
+
+    format!("{year}-W{week:02}")
+}
+
+/// Identify the day `now` falls on in the given timezone offset, e.g. `2023-01-18`.
+pub fn day_id(now: time::OffsetDateTime, offset_hours: i8) -> String {
+    let offset = time::UtcOffset::from_hms(offset_hours, 0, 0).unwrap_or(time::UtcOffset::UTC);
+
+    day_id_offset(now, offset)
+}
+
+/// Identify the day `now` falls on at `offset`.
+pub fn day_id_offset(now: time::OffsetDateTime, offset: time::UtcOffset) -> String {
+    now.to_offset(offset)
+        .date()
+        .format(time::macros::format_description!("[year]-[month]-[day]"))
+        .expect("Failed to format date")
+}
+
+/// Whether `flag` was passed on the command line.
+pub fn has_flag(flag: &str) -> bool {
+    env::args().skip(1).any(|arg| arg == flag)
+}
+
+/// How long a whole request can take by default, so a hung API can't hang
+/// the run.
+#[cfg(feature = "network")]
+pub const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 30;
+
+#[cfg(feature = "network")]
+/// Build the HTTP client shared by the API clients, using the
+/// `CONNECT_TIMEOUT_SECS` env var if set, and `HTTP_TIMEOUT_SECONDS` for the
+/// whole request.
+///
+/// A request timeout of `0` turns it off.
+pub fn http_client() -> Result<Client, BurnoutError> {
+    let connect_timeout = get_env_var("CONNECT_TIMEOUT_SECS")
+        .ok()
+        .map(Duration::from_secs);
+    let request_timeout =
+        match get_env_var("HTTP_TIMEOUT_SECONDS").unwrap_or(DEFAULT_HTTP_TIMEOUT_SECS) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        };
+
+    build_client(connect_timeout, request_timeout)
+}
+
+/// Build an HTTP client with separate timeouts for establishing the
+/// connection and for the whole request, connecting included.
+///
+/// Timeouts left as `None` are not applied.
+#[cfg(feature = "network")]
+pub fn build_client(
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+) -> Result<Client, BurnoutError> {
+    let mut builder = Client::builder();
+
+    if let Some(connect_timeout) = connect_timeout {
+        builder = builder.connect_timeout(connect_timeout);
+    }
+
+    if let Some(request_timeout) = request_timeout {
+        builder = builder.timeout(request_timeout);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Meter glyphs and their `:shortcode:` equivalents.
+const SHORTCODES: [(&str, &str); 7] = [
+    ("🟦", ":blue_square:"),
+    ("🟩", ":green_square:"),
+    ("🟨", ":yellow_square:"),
+    ("🟧", ":orange_square:"),
+    ("🟥", ":red_square:"),
+    ("⬜️", ":white_large_square:"),
+    ("🌙", ":crescent_moon:"),
+];
+
+/// Replace the meter emoji in `text` with their `:shortcode:` names, for
+/// platforms that render shortcodes rather than raw emoji.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::util::emoji_to_shortcode;
+///
+/// assert_eq!(emoji_to_shortcode("🟩⬜️"), ":green_square::white_large_square:");
+/// ```
+pub fn emoji_to_shortcode(text: &str) -> String {
+    SHORTCODES
+        .iter()
+        .fold(text.to_string(), |text, (emoji, shortcode)| {
+            text.replace(emoji, shortcode)
+        })
+}
+
+/// Replace the meter emoji in `text` with `empty` for blank cells and
+/// `filled` for the rest, dropping the colors.
+///
+/// # Examples
+///
+/// ```
+/// use rust_burnout_meter::util::emoji_to_ascii;
+///
+/// assert_eq!(emoji_to_ascii("🟩[🟨]⬜️", '#', '-'), "#[#]-");
+/// ```
+pub fn emoji_to_ascii(text: &str, filled: char, empty: char) -> String {
+    SHORTCODES
+        .iter()
+        .fold(text.to_string(), |text, (emoji, _)| match *emoji {
+            "⬜️" => text.replace(emoji, &empty.to_string()),
+            _ => text.replace(emoji, &filled.to_string()),
+        })
+}
+
+/// The `:shortcode:` name for a single meter glyph, or the glyph itself if
+/// it doesn't have one.
+pub fn shortcode(glyph: &str) -> &str {
+    SHORTCODES
+        .iter()
+        .find(|(emoji, _)| *emoji == glyph)
+        .map_or(glyph, |(_, shortcode)| shortcode)
+}
+
+/// Replace the `:shortcode:` names in `text` with the meter emoji.
+///
+/// The inverse of [`emoji_to_shortcode`].
+pub fn shortcode_to_emoji(text: &str) -> String {
+    SHORTCODES
+        .iter()
+        .fold(text.to_string(), |text, (emoji, shortcode)| {
+            text.replace(shortcode, emoji)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "network")]
+    use std::error::Error;
+
+    #[test]
+    fn test_format_updated() {
+        let now = time::macros::datetime!(2023-01-18 22:05 UTC);
+
+        assert_eq!(format_updated(now, 0), "22:05");
+        assert_eq!(format_updated(now, -5), "17:05");
+        // wraps past midnight
+        assert_eq!(format_updated(now, 3), "01:05");
+    }
+
+    #[test]
+    fn test_days_since_monday() {
+        // Monday 28 December 2020 starts ISO week 53 of 2020, the window is
+        // just today
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2020-12-28 12:00 UTC), 0),
+            0
+        );
+        // Thursday 31 December 2020, the window is Monday 28 to Thursday 31
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2020-12-31 12:00 UTC), 0),
+            3
+        );
+        // Friday 1 January 2021 is still in week 53, the window is Monday 28
+        // December to Friday 1 January
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2021-01-01 12:00 UTC), 0),
+            4
+        );
+        // Sunday 3 January 2021 ends week 53, the window is the whole week
+        // from Monday 28 December
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2021-01-03 12:00 UTC), 0),
+            6
+        );
+        // Monday 4 January 2021 starts week 1, the window is just today
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2021-01-04 12:00 UTC), 0),
+            0
+        );
+    }
+
+    #[test]
+    fn test_days_since_monday_across_offset() {
+        // Tuesday 31 December 2024 is in ISO week 1 of 2025, the window is
+        // Monday 30 to Tuesday 31
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2024-12-31 12:00 UTC), 0),
+            1
+        );
+        // 23:00 on Sunday 3 January 2021 in UTC is already Monday 4 January
+        // at UTC+2, the window is just that Monday
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2021-01-03 23:00 UTC), 2),
+            0
+        );
+        // 01:00 on Monday 4 January 2021 in UTC is still Sunday at UTC-5, the
+        // window is the whole of week 53
+        assert_eq!(
+            days_since_monday(time::macros::datetime!(2021-01-04 01:00 UTC), -5),
+            6
+        );
+    }
+
+    #[test]
+    fn test_days_since_monday_part_hour_offset() {
+        // 18:45 on Sunday 3 January 2021 in UTC is 00:15 on Monday at UTC+5:30,
+        // but still 23:45 on Sunday rounded down to UTC+5
+        let now = time::macros::datetime!(2021-01-03 18:45 UTC);
+        assert_eq!(days_since_monday_offset(now, offset_from_minutes(330)), 0);
+        assert_eq!(days_since_monday(now, 5), 6);
+
+        // 18:20 is 00:05 on Monday at UTC+5:45
+        let now = time::macros::datetime!(2021-01-03 18:20 UTC);
+        assert_eq!(days_since_monday_offset(now, offset_from_minutes(345)), 0);
+        assert_eq!(days_since_monday_offset(now, offset_from_minutes(330)), 6);
+
+        // 05:15 on Monday 4 January is still 23:45 on Sunday at UTC-5:30
+        let now = time::macros::datetime!(2021-01-04 05:15 UTC);
+        assert_eq!(days_since_monday_offset(now, offset_from_minutes(-330)), 6);
+
+        // whole hours agree with the hours version
+        let now = time::macros::datetime!(2021-01-03 23:00 UTC);
+        assert_eq!(
+            days_since_monday_offset(now, offset_from_minutes(120)),
+            days_since_monday(now, 2)
+        );
+
+        assert_eq!(offset_from_minutes(24 * 60), time::UtcOffset::UTC);
+    }
+
+    #[test]
+    fn test_days_since_week_start() {
+        use time::Weekday::{Monday, Sunday};
+
+        // Sunday 15 January 2023 starts a Sunday week and ends a Monday one
+        let sunday = time::macros::datetime!(2023-01-15 12:00 UTC);
+        assert_eq!(days_since_week_start(sunday, 0, Sunday), 0);
+        assert_eq!(days_since_week_start(sunday, 0, Monday), 6);
+
+        // Monday 16 January starts a Monday week
+        let monday = time::macros::datetime!(2023-01-16 12:00 UTC);
+        assert_eq!(days_since_week_start(monday, 0, Sunday), 1);
+        assert_eq!(days_since_week_start(monday, 0, Monday), 0);
+
+        // Saturday 21 January ends a Sunday week
+        let saturday = time::macros::datetime!(2023-01-21 12:00 UTC);
+        assert_eq!(days_since_week_start(saturday, 0, Sunday), 6);
+        assert_eq!(days_since_week_start(saturday, 0, Monday), 5);
+
+        // late Saturday in UTC is already Sunday a few hours east
+        let saturday_night = time::macros::datetime!(2023-01-21 23:00 UTC);
+        assert_eq!(days_since_week_start(saturday_night, 2, Sunday), 0);
+
+        // the Monday version is the same as starting on Monday
+        assert_eq!(days_since_monday(sunday, 0), 6);
+        assert_eq!(
+            days_since_monday_offset(monday, offset_from_minutes(330)),
+            days_since_week_start_offset(monday, offset_from_minutes(330), Monday)
+        );
+    }
+
+    #[test]
+    fn test_parse_weekday() {
+        assert_eq!(parse_weekday("sunday"), Ok(time::Weekday::Sunday));
+        assert_eq!(parse_weekday("Monday"), Ok(time::Weekday::Monday));
+        assert_eq!(parse_weekday("sat"), Ok(time::Weekday::Saturday));
+        assert!(parse_weekday("someday").is_err());
+        assert!(parse_weekday("").is_err());
+    }
+
+    #[test]
+    fn test_day_id() {
+        let now = time::macros::datetime!(2023-01-18 22:05 UTC);
+
+        assert_eq!(day_id(now, 0), "2023-01-18");
+        assert_eq!(day_id(now, 3), "2023-01-19");
+    }
+
+    #[test]
+    fn test_week_id() {
+        assert_eq!(
+            week_id(time::macros::datetime!(2023-01-18 12:00 UTC), 0),
+            "2023-W03"
+        );
+        // Sunday night in UTC is already Monday a few hours east
+        let sunday = time::macros::datetime!(2023-01-22 22:00 UTC);
+        assert_eq!(week_id(sunday, 0), "2023-W03");
+        assert_eq!(week_id(sunday, 3), "2023-W04");
+
+        // a Sunday week runs from Sunday 22 to Saturday 28 January
+        let start = time::Weekday::Sunday;
+        assert_eq!(week_id_starting(sunday, 0, start), "2023-W04");
+        assert_eq!(
+            week_id_starting(time::macros::datetime!(2023-01-28 12:00 UTC), 0, start),
+            "2023-W04"
+        );
+        assert_eq!(
+            week_id_starting(time::macros::datetime!(2023-01-21 12:00 UTC), 0, start),
+            "2023-W03"
+        );
+    }
+
+    #[test]
+    fn test_partial_hour_offset() {
+        // 18:45 UTC on Sunday is already 00:15 on Monday in India
+        let now = time::macros::datetime!(2023-01-22 18:45 UTC);
+        let india = offset_from_minutes(330);
+
+        assert_eq!(format_updated_offset(now, india), "00:15");
+        assert_eq!(day_id_offset(now, india), "2023-01-23");
+        assert_eq!(
+            week_id_starting_offset(now, india, time::Weekday::Monday),
+            "2023-W04"
+        );
+        // but still Sunday to the whole hours either side
+        assert_eq!(day_id(now, 5), "2023-01-22");
+        assert_eq!(week_id(now, 5), "2023-W03");
+    }
+
+    #[test]
+    fn test_emoji_to_shortcode() {
+        let meter = "🟧🟧🟧⬜️";
+        let shortcodes = emoji_to_shortcode(meter);
+
+        assert_eq!(
+            shortcodes,
+            ":orange_square::orange_square::orange_square::white_large_square:"
+        );
+        assert_eq!(shortcode_to_emoji(&shortcodes), meter);
+
+        assert_eq!(shortcode("🟥"), ":red_square:");
+        assert_eq!(shortcode("x"), "x");
+
+        // text around the meter is left alone
+        assert_eq!(
+            emoji_to_shortcode("🟩🟨🟥 12/40 hours"),
+            ":green_square::yellow_square::red_square: 12/40 hours"
+        );
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_build_client_request_timeout() -> Result<(), Box<dyn Error>> {
+        let mock_server = httpmock::MockServer::start();
+        let slow = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/");
+            then.status(200).delay(Duration::from_secs(2));
+        });
+
+        let client = build_client(
+            Some(Duration::from_secs(1)),
+            Some(Duration::from_millis(100)),
+        )?;
+        let error = client.get(mock_server.base_url()).send().await.unwrap_err();
+
+        slow.assert();
+        assert!(error.is_timeout(), "{error:?}");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "network")]
+    #[tokio::test]
+    async fn test_build_client_connect_timeout() -> Result<(), Box<dyn Error>> {
+        let mock_server = httpmock::MockServer::start();
+        let slow = mock_server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/");
+            then.status(200).delay(Duration::from_millis(500));
+        });
+
+        // connecting is quick, so a slow response outlasts the connect
+        // timeout without tripping it
+        let client = build_client(Some(Duration::from_millis(100)), None)?;
+        let response = client.get(mock_server.base_url()).send().await?;
+
+        slow.assert();
+        assert!(response.status().is_success());
+
+        Ok(())
+    }
 }
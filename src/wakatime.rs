@@ -1,12 +1,30 @@
-use reqwest::{Client, Url};
+use crate::{
+    cache::{load_cache, save_cache, CachedHours, DirLock},
+    error::BurnoutError,
+    ratelimit::RateLimit,
+    redact::{log_request, redact_url},
+    util::http_client,
+};
+use reqwest::{header::RETRY_AFTER, Client, Request, Response, StatusCode, Url};
 use serde::Deserialize;
 use serde_json::from_str;
-use std::error::Error;
-use time::{self, format_description::well_known::Rfc3339, OffsetDateTime};
+use std::{
+    cell::Cell,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use time::{
+    self, format_description::well_known::Rfc3339, macros::format_description, OffsetDateTime,
+    UtcOffset,
+};
 
 #[derive(Deserialize, Debug)]
 struct Summary {
     cumulative_total: Option<Total>,
+    #[serde(default)]
+    data: Vec<Day>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -14,25 +32,182 @@ struct Total {
     decimal: String,
 }
 
+/// A single day from the summaries endpoint.
+#[derive(Deserialize, Debug)]
+struct Day {
+    grand_total: GrandTotal,
+    #[serde(default)]
+    projects: Vec<NamedTotal>,
+    #[serde(default)]
+    languages: Vec<NamedTotal>,
+}
+
+/// The time spent on one project or language in a day.
+#[derive(Deserialize, Debug)]
+struct NamedTotal {
+    name: String,
+    total_seconds: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GrandTotal {
+    total_seconds: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct Durations {
+    data: Vec<Session>,
+}
+
+/// A single continuous block of activity from the durations endpoint.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Session {
+    /// When the block started, as a unix timestamp in seconds.
+    #[serde(default)]
+    pub time: f64,
+    /// Length of the block in seconds.
+    pub duration: f64,
+}
+
+/// Where to write the raw WakaTime responses for debugging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpTarget {
+    Stderr,
+    File(PathBuf),
+}
+
 #[derive(Debug, Clone)]
 pub struct WakaTime {
     client: Client,
     base_url: Url,
     api_key: String,
+    dump_target: Option<DumpTarget>,
+    offset: UtcOffset,
+    max_retries: u32,
+    base_delay: Duration,
+    max_retry_wait: Duration,
+    rate_limit: Cell<Option<RateLimit>>,
+    min_interval: Option<(PathBuf, Duration)>,
 }
 
+/// How old another process's lock can get before it's taken over, as left
+/// from one that died holding it.
+///
+/// A slow call can outlive it, but the lock is only ever removed by whoever
+/// holds it at the time, see [`DirLock`].
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// How often to check whether another process has finished its call.
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// How many times a failed request is retried by default.
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// The wait before the first retry by default, doubled for each one after.
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// The longest a `Retry-After` header is waited for by default, so a
+/// scheduled run can't be held up for hours.
+const DEFAULT_MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
 impl WakaTime {
-    pub fn new(api_key: &str) -> Result<Self, Box<dyn Error>> {
-        let base_url = "https://wakatime.com".parse()?;
+    pub fn new(api_key: &str) -> Result<Self, BurnoutError> {
+        Self::with_client(api_key, http_client()?)
+    }
+
+    /// Create the client, sending requests through `client`, e.g. one shared
+    /// with the other sources and profiles.
+    pub fn with_client(api_key: &str, client: Client) -> Result<Self, BurnoutError> {
+        let base_url = Url::parse("https://wakatime.com")
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
         let api_key = api_key.to_string();
 
         Ok(Self {
-            client: Client::new(),
+            client,
             base_url,
             api_key,
+            dump_target: None,
+            offset: UtcOffset::UTC,
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_retry_wait: DEFAULT_MAX_RETRY_WAIT,
+            rate_limit: Cell::new(None),
+            min_interval: None,
         })
     }
 
+    /// Share the weekly total between processes through `dir`, so a process
+    /// asking within `interval` of the last call gets its hours rather than
+    /// calling again, e.g. when a cron job and a manual run overlap.
+    ///
+    /// Calls are taken in turn with a lock file, so the second process waits
+    /// for the first to finish and then reuses its result.
+    pub fn set_min_interval(&mut self, dir: PathBuf, interval: Duration) -> &mut Self {
+        self.min_interval = Some((dir, interval));
+
+        self
+    }
+
+    /// Write each raw response body to `dump_target` before it's parsed.
+    pub fn set_dump_target(&mut self, dump_target: Option<DumpTarget>) -> &mut Self {
+        self.dump_target = dump_target;
+
+        self
+    }
+
+    /// Make requests as the account with `api_key`, e.g. for a copy of a
+    /// client set up for another account.
+    pub fn set_api_key(&mut self, api_key: &str) -> &mut Self {
+        self.api_key = api_key.to_string();
+
+        self
+    }
+
+    /// Retry requests that fail with a 429, 500, 502 or 503 or can't connect
+    /// up to `max_retries` times, waiting `base_delay` before the first retry
+    /// and twice as long before each one after, or as long as a 429 says in
+    /// its `Retry-After` header, up to [`WakaTime::with_max_retry_wait`].
+    pub fn with_retries(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.base_delay = base_delay;
+
+        self
+    }
+
+    /// Wait at most `max_wait` before a retry, however long a `Retry-After`
+    /// header asks for.
+    pub fn with_max_retry_wait(mut self, max_wait: Duration) -> Self {
+        self.max_retry_wait = max_wait;
+
+        self
+    }
+
+    /// Query the days in the given timezone offset rather than UTC, so the
+    /// range lines up with the user's local week.
+    pub fn set_timezone_offset(&mut self, offset_hours: i8) -> &mut Self {
+        self.set_utc_offset(UtcOffset::from_hms(offset_hours, 0, 0).unwrap_or(UtcOffset::UTC))
+    }
+
+    /// Query the days at `offset` rather than UTC, for offsets that aren't
+    /// whole hours.
+    pub fn set_utc_offset(&mut self, offset: UtcOffset) -> &mut Self {
+        self.offset = offset;
+
+        self
+    }
+
+    /// The rate limit from the last response, if WakaTime sent one.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit.get()
+    }
+
+    /// Point requests at a different server, e.g. a mock in tests.
+    pub fn set_base_url(&mut self, base_url: Url) -> &mut Self {
+        self.base_url = base_url;
+
+        self
+    }
+
     /// Get the total time spent coding in the last n days
     /// from the WakaTime API
     ///
@@ -46,52 +221,422 @@ impl WakaTime {
     /// # Examples
     ///
     /// ```no_run
-    /// use wakatime::get_time_last_n_days;
-    /// use std::error::Error;
+    /// use rust_burnout_meter::{error::BurnoutError, wakatime::WakaTime};
     /// #[tokio::main]
-    /// async fn main() -> Result<(), Box<dyn Error>> {
-    ///     let hours = get_time_last_n_days(30, None).await?;
+    /// async fn main() -> Result<(), BurnoutError> {
+    ///     let wakatime = WakaTime::new("waka_...")?;
+    ///     let hours = wakatime.get_time_last_n_days(30).await?;
     ///     println!("{} hours in the last 30 days", hours.unwrap_or(0f64));
     ///     Ok(())
     /// }
     /// ```
-    pub async fn get_time_last_n_days(&self, days: i64) -> Result<Option<f64>, Box<dyn Error>> {
-        let start_date = Self::get_start_datetime(days);
-        let end_date = Self::get_end_datetime();
+    pub async fn get_time_last_n_days(&self, days: i64) -> Result<Option<f64>, BurnoutError> {
+        let Some((dir, interval)) = &self.min_interval else {
+            return self.get_total(days, None).await;
+        };
+
+        let _lock = lock_dir(dir).await?;
+
+        let path = dir.join(format!("wakatime-last-{days}d.json"));
+        let now = OffsetDateTime::now_utc();
+        let interval = time::Duration::try_from(*interval).unwrap_or(time::Duration::MAX);
+
+        if let Some(hours) = load_cache(&path)?.and_then(|last| last.fresh_within(now, interval)) {
+            return Ok(Some(hours));
+        }
+
+        let hours = self.get_total(days, None).await?;
+
+        if let Some(hours) = hours {
+            let last = CachedHours {
+                hours,
+                timestamp: now.unix_timestamp(),
+            };
+            save_cache(&path, &last)?;
+        }
+
+        Ok(hours)
+    }
+
+    /// Get the total time spent coding on `project` alone in the last n days
+    /// from the WakaTime API.
+    ///
+    /// https://wakatime.com/developers#summaries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn get_time_last_n_days_for_project(
+        &self,
+        days: i64,
+        project: &str,
+    ) -> Result<Option<f64>, BurnoutError> {
+        self.get_total(days, Some(project)).await
+    }
+
+    /// The cumulative total of the summaries for the last n days, for only
+    /// `project` if it's set.
+    ///
+    /// WakaTime sometimes leaves the cumulative total out, e.g. when the range
+    /// spans a change of plan, so then the days are added up instead. With no
+    /// days either there are no hours.
+    async fn get_total(
+        &self,
+        days: i64,
+        project: Option<&str>,
+    ) -> Result<Option<f64>, BurnoutError> {
+        let result = self.get_summary(days, project).await?;
+
+        match result.cumulative_total {
+            Some(total) => Ok(Some(parse_decimal(&total.decimal)?)),
+            None if result.data.is_empty() => Ok(None),
+            None => Ok(Some(
+                result
+                    .data
+                    .iter()
+                    .map(|day| day.grand_total.total_seconds)
+                    .sum::<f64>()
+                    / 3600f64,
+            )),
+        }
+    }
+
+    /// Get the hours spent coding on each of the last n days, oldest first,
+    /// from the WakaTime API.
+    ///
+    /// https://wakatime.com/developers#summaries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn get_daily_time_last_n_days(&self, days: i64) -> Result<Vec<f64>, BurnoutError> {
+        let result = self.get_summary(days, None).await?;
+
+        Ok(result
+            .data
+            .iter()
+            .map(|day| day.grand_total.total_seconds / 3600f64)
+            .collect())
+    }
+
+    /// Get the hours spent on each project in the last n days, most first,
+    /// from the WakaTime API.
+    ///
+    /// https://wakatime.com/developers#summaries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn get_project_hours_last_n_days(
+        &self,
+        days: i64,
+    ) -> Result<Vec<(String, f64)>, BurnoutError> {
+        let result = self.get_summary(days, None).await?;
+
+        Ok(sum_by_name(
+            result.data.iter().flat_map(|day| &day.projects),
+        ))
+    }
+
+    /// Get the hours spent in each language in the last n days, most first,
+    /// from the WakaTime API.
+    ///
+    /// https://wakatime.com/developers#summaries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn get_language_hours_last_n_days(
+        &self,
+        days: i64,
+    ) -> Result<Vec<(String, f64)>, BurnoutError> {
+        let result = self.get_summary(days, None).await?;
+
+        Ok(sum_by_name(
+            result.data.iter().flat_map(|day| &day.languages),
+        ))
+    }
+
+    /// Get the hours spent coding in each of the last n weeks, oldest first,
+    /// from the WakaTime API.
+    ///
+    /// Weeks are counted back in blocks of seven days from today, so the
+    /// last one always ends today.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn get_weekly_time_last_n_weeks(&self, weeks: i64) -> Result<Vec<f64>, BurnoutError> {
+        let daily = self.get_daily_time_last_n_days(weeks * 7 - 1).await?;
+
+        let mut weekly: Vec<f64> = daily.rchunks(7).map(|week| week.iter().sum()).collect();
+        weekly.reverse();
+
+        Ok(weekly)
+    }
+
+    /// Request the summaries for the last n days, for only `project` if it's
+    /// set.
+    async fn get_summary(&self, days: i64, project: Option<&str>) -> Result<Summary, BurnoutError> {
+        let now = OffsetDateTime::now_utc();
+        let start_date = Self::get_start_datetime(now, days, self.offset);
+        let end_date = Self::get_end_datetime(now, self.offset);
 
         let endpoint = "/api/v1/users/current/summaries";
-        let url = self.base_url.join(endpoint)?;
-
-        let response = self
-            .client
-            .get(url)
-            .query(&vec![
-                ("api_key", &self.api_key),
-                ("start", &start_date),
-                ("end", &end_date),
-            ])
-            .send()
-            .await?;
+        let url = self
+            .base_url
+            .join(endpoint)
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+
+        let mut query = vec![
+            ("api_key", self.api_key.as_str()),
+            ("start", &start_date),
+            ("end", &end_date),
+        ];
+
+        if let Some(project) = project {
+            query.push(("project", project));
+        }
+
+        let request = self.client.get(url).query(&query).build()?;
 
+        let response = self.send(request).await?;
+
+        let url = response.url().clone();
         let body = response.text().await?;
+        self.dump_response(&url, &body)?;
 
-        let result: Summary = from_str(&body)?;
+        Ok(from_str(&body)?)
+    }
 
-        match result.cumulative_total {
-            Some(total) => Ok(Some(total.decimal.parse()?)),
-            None => Ok(None),
+    /// Send `request`, retrying transient failures as set by
+    /// [`WakaTime::with_retries`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BurnoutError::RateLimited`] if WakaTime is still rate
+    /// limiting after the last retry, or the last error if the request still
+    /// fails.
+    async fn send(&self, request: Request) -> Result<Response, BurnoutError> {
+        let mut attempt = 0;
+
+        loop {
+            // only GET requests are sent, which have no body and always copy
+            let this_try = request
+                .try_clone()
+                .expect("Failed to copy the WakaTime request");
+            log_request(&this_try);
+
+            let retries_left = attempt < self.max_retries;
+            let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+
+            let delay = match self.client.execute(this_try).await {
+                Ok(response) => {
+                    self.rate_limit
+                        .set(RateLimit::from_headers(response.headers()));
+
+                    let status = response.status();
+
+                    if !is_retryable(status) {
+                        return Ok(response);
+                    }
+
+                    if !retries_left {
+                        return match status {
+                            StatusCode::TOO_MANY_REQUESTS => Err(BurnoutError::RateLimited),
+                            _ => Err(BurnoutError::WakaTimeStatus(status)),
+                        };
+                    }
+
+                    let delay = retry_after(&response)
+                        .unwrap_or(backoff)
+                        .min(self.max_retry_wait);
+                    log::warn!("WakaTime returned {status}, retrying in {delay:?}");
+
+                    delay
+                }
+                Err(e) if e.is_connect() && retries_left => {
+                    log::warn!("Failed to connect to WakaTime, retrying in {backoff:?}: {e}");
+
+                    backoff
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Write the raw response to the dump target, if one is set, with the
+    /// API key masked.
+    fn dump_response(&self, url: &Url, body: &str) -> Result<(), BurnoutError> {
+        let dump_target = match &self.dump_target {
+            Some(dump_target) => dump_target,
+            None => return Ok(()),
+        };
+
+        let dump = format_dump(url, body, &self.api_key);
+
+        match dump_target {
+            DumpTarget::Stderr => eprintln!("{dump}"),
+            DumpTarget::File(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{dump}")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the total time spent coding in the last n days from the WakaTime
+    /// durations endpoint, capping any single continuous session at
+    /// `max_session_hours` before summing, for only `project` if it's set.
+    ///
+    /// Long uninterrupted blocks are usually a sign the editor was left open,
+    /// so capping them gives a truer picture of the time actually spent.
+    ///
+    /// https://wakatime.com/developers#durations
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the daily requests fail or a response cannot be parsed.
+    pub async fn get_capped_time_last_n_days(
+        &self,
+        days: i64,
+        max_session_hours: f64,
+        project: Option<&str>,
+    ) -> Result<f64, BurnoutError> {
+        let seconds: Vec<f64> = self
+            .get_sessions_last_n_days(days, project)
+            .await?
+            .iter()
+            .map(|session| session.duration)
+            .collect();
+
+        Ok(sum_capped_sessions(&seconds, max_session_hours))
+    }
+
+    /// Get every continuous block of activity in the last n days from the
+    /// WakaTime durations endpoint, on only `project` if it's set.
+    ///
+    /// https://wakatime.com/developers#durations
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the daily requests fail or a response cannot be parsed.
+    pub async fn get_sessions_last_n_days(
+        &self,
+        days: i64,
+        project: Option<&str>,
+    ) -> Result<Vec<Session>, BurnoutError> {
+        let endpoint = "/api/v1/users/current/durations";
+        let url = self
+            .base_url
+            .join(endpoint)
+            .map_err(|e| BurnoutError::InvalidUrl(e.to_string()))?;
+        let today = OffsetDateTime::now_utc().to_offset(self.offset).date();
+
+        let mut sessions = Vec::new();
+
+        // the durations endpoint only accepts a single day, so request each one
+        for day in 0..=days {
+            let date = Self::get_date(today - time::Duration::days(day));
+
+            let mut query = vec![("api_key", self.api_key.as_str()), ("date", &date)];
+            if let Some(project) = project {
+                query.push(("project", project));
+            }
+
+            let request = self.client.get(url.clone()).query(&query).build()?;
+
+            let response = self.send(request).await?;
+
+            let url = response.url().clone();
+            let body = response.text().await?;
+            self.dump_response(&url, &body)?;
+
+            let result: Durations = from_str(&body)?;
+
+            sessions.extend(result.data);
         }
+
+        Ok(sessions)
+    }
+
+    /// Gets the date as a `YYYY-MM-DD` string for the WakaTime durations request.
+    fn get_date(date: time::Date) -> String {
+        date.format(format_description!("[year]-[month]-[day]"))
+            .expect("Failed to format date")
+    }
+
+    /// Gets the start date, `days` before `now` in the `offset` timezone, as
+    /// an ISO string for the WakaTime API request.
+    fn get_start_datetime(now: OffsetDateTime, days: i64, offset: UtcOffset) -> String {
+        get_datetime((now - time::Duration::days(days)).to_offset(offset))
+    }
+
+    /// Gets the end date, `now` in the `offset` timezone, as an ISO string for
+    /// the WakaTime API request.
+    fn get_end_datetime(now: OffsetDateTime, offset: UtcOffset) -> String {
+        get_datetime(now.to_offset(offset))
+    }
+}
+
+/// Wait for the lock on `dir`, taking it over from a process that's held it
+/// for [`LOCK_STALE_AFTER`].
+async fn lock_dir(dir: &Path) -> Result<DirLock, BurnoutError> {
+    loop {
+        if let Some(lock) = DirLock::try_acquire(dir, LOCK_STALE_AFTER)? {
+            return Ok(lock);
+        }
+
+        tokio::time::sleep(LOCK_POLL).await;
     }
+}
+
+/// Add up the hours for each name across the days, most first.
+fn sum_by_name<'a>(totals: impl Iterator<Item = &'a NamedTotal>) -> Vec<(String, f64)> {
+    let mut hours_by_name: Vec<(String, f64)> = Vec::new();
 
-    /// Gets the start date as an ISO string for the WakaTime API request.
-    fn get_start_datetime(days: i64) -> String {
-        get_datetime(OffsetDateTime::now_utc() - time::Duration::days(days))
+    for total in totals {
+        let hours = total.total_seconds / 3600f64;
+
+        match hours_by_name
+            .iter_mut()
+            .find(|(name, _)| *name == total.name)
+        {
+            Some((_, sum)) => *sum += hours,
+            None => hours_by_name.push((total.name.clone(), hours)),
+        }
     }
 
-    /// Gets the end date as an ISO string for the WakaTime API request.
-    fn get_end_datetime() -> String {
-        get_datetime(OffsetDateTime::now_utc())
+    hours_by_name.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    hours_by_name
+}
+
+/// Whether a request that got `status` is worth trying again.
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+    )
+}
+
+/// How long a rate limited `response` asks to wait in its `Retry-After`
+/// header, if it gives a number of seconds.
+fn retry_after(response: &Response) -> Option<Duration> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
     }
+
+    let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+
+    seconds.trim().parse().ok().map(Duration::from_secs)
 }
 
 /// Uses the `time` crate to get the date from the `OffsetDateTime` and
@@ -106,18 +651,72 @@ fn get_datetime(mut offset_date_time: OffsetDateTime) -> String {
         .expect("Failed to format date")
 }
 
+/// Parse a decimal from WakaTime, which may have been localized by a proxy
+/// to use a comma as the decimal separator, e.g. `155,00` or `1.155,00`.
+fn parse_decimal(decimal: &str) -> Result<f64, BurnoutError> {
+    let decimal = decimal.trim();
+
+    let normalized = match (decimal.rfind(','), decimal.rfind('.')) {
+        // the comma comes last, so it's the decimal separator
+        (Some(comma), dot) if dot.map_or(true, |dot| comma > dot) => {
+            decimal.replace('.', "").replace(',', ".")
+        }
+        // otherwise any commas are separating thousands
+        _ => decimal.replace(',', ""),
+    };
+
+    match normalized.parse() {
+        Ok(parsed) => Ok(parsed),
+        Err(_) => Err(BurnoutError::InvalidDecimal(decimal.to_string())),
+    }
+}
+
+/// Format a raw response for dumping, with the request URL on the first line
+/// and the body below it. Any occurrence of `api_key` is masked.
+fn format_dump(url: &Url, body: &str, api_key: &str) -> String {
+    let dump = format!("GET {}\n{body}", redact_url(url));
+
+    match api_key.is_empty() {
+        true => dump,
+        false => dump.replace(api_key, "****"),
+    }
+}
+
+/// Sums session lengths given in seconds, capping each one at `max_hours`,
+/// and returns the total in hours.
+fn sum_capped_sessions(seconds: &[f64], max_hours: f64) -> f64 {
+    let max_seconds = max_hours * 3600f64;
+
+    seconds
+        .iter()
+        .map(|duration| duration.min(max_seconds))
+        .sum::<f64>()
+        / 3600f64
+}
+
 #[cfg(test)]
 mod tests {
-    use super::WakaTime;
-    use httpmock::prelude::*;
+    use super::{is_retryable, parse_decimal, sum_capped_sessions, DumpTarget, WakaTime};
+    use crate::{error::BurnoutError, util::build_client};
+    use httpmock::{prelude::*, Mock};
+    use reqwest::StatusCode;
     use serde_json::json;
-    use std::error::Error;
-    use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+    use std::{
+        collections::BTreeMap,
+        error::Error,
+        path::PathBuf,
+        sync::Mutex,
+        time::{Duration, Instant},
+    };
+    use time::{
+        format_description::well_known::Rfc3339, macros::datetime, OffsetDateTime, UtcOffset,
+    };
 
     #[test]
     fn test_get_start_and_end_dates() -> Result<(), Box<dyn Error>> {
-        let start = WakaTime::get_start_datetime(30);
-        let end = WakaTime::get_end_datetime();
+        let now = OffsetDateTime::now_utc();
+        let start = WakaTime::get_start_datetime(now, 30, UtcOffset::UTC);
+        let end = WakaTime::get_end_datetime(now, UtcOffset::UTC);
 
         let start_datetime = OffsetDateTime::parse(start.as_str(), &Rfc3339)?;
         let end_datetime = OffsetDateTime::parse(end.as_str(), &Rfc3339)?;
@@ -130,12 +729,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_start_date_is_local_monday() -> Result<(), Box<dyn Error>> {
+        // 01:00 on Monday 23 January in UTC is still 20:00 on Sunday at
+        // UTC-5, six days into the local week that began Monday 16 January
+        let now = datetime!(2023-01-23 01:00 UTC);
+        let offset = UtcOffset::from_hms(-5, 0, 0)?;
+        let days = crate::util::days_since_monday(now, -5);
+
+        let start = WakaTime::get_start_datetime(now, days, offset);
+        let end = WakaTime::get_end_datetime(now, offset);
+
+        assert_eq!(start, "2023-01-16T20:00:00-05:00");
+        assert_eq!(end, "2023-01-22T20:00:00-05:00");
+        // where counting back in UTC would have started on the Tuesday
+        assert_eq!(
+            WakaTime::get_start_datetime(now, days, UtcOffset::UTC),
+            "2023-01-17T01:00:00Z"
+        );
+
+        // 22:00 on Sunday 22 January in UTC is already Monday at UTC+3, so
+        // the local week is just today
+        let now = datetime!(2023-01-22 22:00 UTC);
+        let offset = UtcOffset::from_hms(3, 0, 0)?;
+        let days = crate::util::days_since_monday(now, 3);
+
+        let start =
+            OffsetDateTime::parse(&WakaTime::get_start_datetime(now, days, offset), &Rfc3339)?;
+        assert_eq!(start.date(), time::macros::date!(2023 - 01 - 23));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_get_time_last_n_days() -> Result<(), Box<dyn Error>> {
         let mock_server = MockServer::start();
 
-        let start_date = WakaTime::get_start_datetime(30);
-        let end_date = WakaTime::get_end_datetime();
+        let now = OffsetDateTime::now_utc();
+        let start_date = WakaTime::get_start_datetime(now, 30, UtcOffset::UTC);
+        let end_date = WakaTime::get_end_datetime(now, UtcOffset::UTC);
 
         let mock = mock_server.mock(|when, then| {
             when.method(GET)
@@ -178,4 +810,604 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_get_time_last_n_days_without_cumulative_total() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "data": [
+                            { "grand_total": { "total_seconds": 16200.0 } },
+                            { "grand_total": { "total_seconds": 0.0 } },
+                            { "grand_total": { "total_seconds": 27000.0 } }
+                        ]
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = WakaTime::new("test")?;
+        client.base_url = mock_server.base_url().parse()?;
+
+        let hours = client.get_time_last_n_days(2).await?;
+
+        mock.assert();
+        // 4.5 + 0 + 7.5
+        assert_eq!(hours, Some(12f64));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_time_last_n_days_for_project() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/v1/users/current/summaries")
+                .query_param("project", "burnout-meter");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "12.25" } }).to_string());
+        });
+
+        let mut client = WakaTime::new("test")?;
+        client.base_url = mock_server.base_url().parse()?;
+
+        let hours = client
+            .get_time_last_n_days_for_project(7, "burnout-meter")
+            .await?;
+
+        mock.assert();
+        assert_eq!(hours, Some(12.25));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_capped_sessions() {
+        // a 6 hour block is capped to 2 hours, shorter blocks pass through
+        let seconds = [6f64 * 3600f64, 1800f64, 3600f64];
+        assert_eq!(sum_capped_sessions(&seconds, 2f64), 3.5);
+
+        // nothing is capped when every block is under the threshold
+        assert_eq!(sum_capped_sessions(&seconds, 8f64), 7.5);
+    }
+
+    #[tokio::test]
+    async fn test_get_capped_time_last_n_days() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let date = WakaTime::get_date(OffsetDateTime::now_utc().date());
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/v1/users/current/durations")
+                .query_param_exists("api_key")
+                .query_param("date", &date);
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                json!({
+                    "data": [
+                        { "project": "burnout-meter", "time": 1674000000.0, "duration": 21600.0 },
+                        { "project": "burnout-meter", "time": 1674030000.0, "duration": 2700.0 },
+                        { "project": "dotfiles", "time": 1674040000.0, "duration": 900.0 }
+                    ]
+                })
+                .to_string(),
+            );
+        });
+
+        let mut client = WakaTime::new("test")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let hours = client.get_capped_time_last_n_days(0, 3f64, None).await?;
+
+        mock.assert();
+
+        // 6 hours capped to 3, plus 45 and 15 minutes uncapped
+        assert_eq!(hours, 4f64);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_sessions_for_project() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/v1/users/current/durations")
+                .query_param("project", "burnout-meter");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "data": [
+                            { "project": "burnout-meter", "time": 1674000000.0, "duration": 3600.0 }
+                        ]
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = WakaTime::new("test")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let sessions = client
+            .get_sessions_last_n_days(1, Some("burnout-meter"))
+            .await?;
+
+        // a request for each day, both for the project
+        mock.assert_hits(2);
+        assert_eq!(sessions.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_daily_time_last_n_days() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "cumulative_total": { "decimal": "4.50" },
+                        "data": [
+                            { "grand_total": { "decimal": "1.00", "total_seconds": 3600.0 } },
+                            { "grand_total": { "decimal": "0.00", "total_seconds": 0.0 } },
+                            { "grand_total": { "decimal": "3.50", "total_seconds": 12600.0 } }
+                        ]
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = WakaTime::new("test")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let daily = client.get_daily_time_last_n_days(2).await?;
+
+        mock.assert();
+
+        assert_eq!(daily, vec![1f64, 0f64, 3.5f64]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_project_hours_last_n_days() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "data": [
+                            {
+                                "grand_total": { "total_seconds": 10800.0 },
+                                "projects": [
+                                    { "name": "proj-x", "total_seconds": 7200.0 },
+                                    { "name": "dotfiles", "total_seconds": 3600.0 }
+                                ]
+                            },
+                            {
+                                "grand_total": { "total_seconds": 9000.0 },
+                                "projects": [{ "name": "proj-x", "total_seconds": 9000.0 }]
+                            }
+                        ]
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = WakaTime::new("test")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let projects = client.get_project_hours_last_n_days(1).await?;
+
+        mock.assert();
+
+        assert_eq!(
+            projects,
+            vec![("proj-x".to_string(), 4.5), ("dotfiles".to_string(), 1.0)]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_language_hours_last_n_days() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(
+                    json!({
+                        "data": [
+                            {
+                                "grand_total": { "total_seconds": 10800.0 },
+                                "languages": [
+                                    { "name": "Python", "total_seconds": 3600.0 },
+                                    { "name": "Rust", "total_seconds": 7200.0 }
+                                ]
+                            },
+                            {
+                                "grand_total": { "total_seconds": 1800.0 },
+                                "languages": [{ "name": "Go", "total_seconds": 1800.0 }]
+                            }
+                        ]
+                    })
+                    .to_string(),
+                );
+        });
+
+        let mut client = WakaTime::new("test")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let languages = client.get_language_hours_last_n_days(1).await?;
+
+        mock.assert();
+
+        assert_eq!(
+            languages,
+            vec![
+                ("Rust".to_string(), 2.0),
+                ("Python".to_string(), 1.0),
+                ("Go".to_string(), 0.5)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dump_response() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let body = json!({ "cumulative_total": { "decimal": "12.34" } }).to_string();
+
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(&body);
+        });
+
+        let path = std::env::temp_dir().join(format!(
+            "burnout-meter-dump-{}.txt",
+            OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+
+        let mut client = WakaTime::new("waka_secret_key")?;
+        client.base_url = mock_server.base_url().parse()?;
+        client.set_dump_target(Some(DumpTarget::File(path.clone())));
+
+        let hours = client.get_time_last_n_days(7).await?;
+        let dump = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(hours, Some(12.34));
+        assert!(dump.contains(&body), "raw body missing from dump: {dump}");
+        assert!(dump.contains("api_key=****"), "api_key not masked: {dump}");
+        assert!(!dump.contains("waka_secret_key"), "api_key leaked: {dump}");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_weekly_time_last_n_weeks() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        // two weeks, one hour a day then two hours a day
+        let days: Vec<_> = (0..14)
+            .map(|day| {
+                let seconds = if day < 7 { 3600.0 } else { 7200.0 };
+                json!({ "grand_total": { "total_seconds": seconds } })
+            })
+            .collect();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": null, "data": days }).to_string());
+        });
+
+        let mut client = WakaTime::new("test")?;
+
+        client.base_url = mock_server.base_url().parse()?;
+        let weekly = client.get_weekly_time_last_n_weeks(2).await?;
+
+        mock.assert();
+
+        assert_eq!(weekly, vec![7f64, 14f64]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_decimal() -> Result<(), Box<dyn Error>> {
+        assert_eq!(parse_decimal("155.00")?, 155f64);
+        assert_eq!(parse_decimal("155,00")?, 155f64);
+        assert_eq!(parse_decimal("12,5")?, 12.5);
+        assert_eq!(parse_decimal("1.155,25")?, 1155.25);
+        assert_eq!(parse_decimal("1,155.25")?, 1155.25);
+        assert!(parse_decimal("lots").is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(429);
+        });
+
+        let mut client = WakaTime::new("api_key")?.with_retries(0, Duration::ZERO);
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let error = client.get_time_last_n_days(7).await.unwrap_err();
+
+        mock.assert();
+        assert!(matches!(error, BurnoutError::RateLimited));
+
+        Ok(())
+    }
+
+    /// Requests seen by [`first_two_requests`] for each API key, so tests
+    /// running at the same time don't share a count.
+    static REQUESTS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+    /// Matches the first two requests with each API key only.
+    fn first_two_requests(request: &HttpMockRequest) -> bool {
+        let api_key = request
+            .query_params
+            .iter()
+            .flatten()
+            .find(|(name, _)| name == "api_key")
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+
+        let mut requests = REQUESTS.lock().unwrap();
+        let count = requests.entry(api_key).or_insert(0);
+        *count += 1;
+
+        *count <= 2
+    }
+
+    /// Mock summaries that fail with `status` for the first two requests,
+    /// then succeed. Each test should use its own API key.
+    fn flaky_summaries<'a>(
+        mock_server: &'a MockServer,
+        status: u16,
+        retry_after: Option<&str>,
+    ) -> (Mock<'a>, Mock<'a>) {
+        let failing = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/api/v1/users/current/summaries")
+                .matches(first_two_requests);
+            let then = then.status(status);
+
+            if let Some(retry_after) = retry_after {
+                then.header("Retry-After", retry_after);
+            }
+        });
+        let ok = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(json!({ "cumulative_total": { "decimal": "12.50" } }).to_string());
+        });
+
+        (failing, ok)
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let (failing, ok) = flaky_summaries(&mock_server, 503, None);
+
+        let mut client = WakaTime::new("transient")?.with_retries(3, Duration::from_millis(10));
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let started = Instant::now();
+        let hours = client.get_time_last_n_days(7).await?;
+
+        assert_eq!(hours, Some(12.5));
+        // two failures and the success
+        failing.assert_hits(2);
+        ok.assert_hits(1);
+        // waiting 10ms then 20ms
+        assert!(started.elapsed() >= Duration::from_millis(30));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let (failing, ok) = flaky_summaries(&mock_server, 500, None);
+
+        let mut client = WakaTime::new("gives_up")?.with_retries(1, Duration::from_millis(1));
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let error = client.get_time_last_n_days(7).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            BurnoutError::WakaTimeStatus(StatusCode::INTERNAL_SERVER_ERROR)
+        ));
+        assert_eq!(
+            error.to_string(),
+            "WakaTime returned 500 Internal Server Error"
+        );
+        failing.assert_hits(2);
+        ok.assert_hits(0);
+
+        Ok(())
+    }
+
+    fn shared_dir(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!(
+            "burnout-meter-{name}-{}",
+            OffsetDateTime::now_utc().unix_timestamp_nanos()
+        ));
+        std::fs::create_dir(&dir)?;
+
+        Ok(dir)
+    }
+
+    #[tokio::test]
+    async fn test_min_interval_across_processes() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let summaries = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .delay(Duration::from_millis(200))
+                .body(json!({ "cumulative_total": { "decimal": "12.50" } }).to_string());
+        });
+
+        let dir = shared_dir("min-interval")?;
+        let process = || -> Result<WakaTime, Box<dyn Error>> {
+            let mut wakatime = WakaTime::new("shared")?;
+            wakatime
+                .set_base_url(mock_server.base_url().parse()?)
+                .set_min_interval(dir.clone(), Duration::from_secs(60));
+
+            Ok(wakatime)
+        };
+        let (cron, manual) = (process()?, process()?);
+
+        // the manual run starts while the cron job is still waiting on WakaTime
+        let (from_cron, from_manual) =
+            tokio::join!(cron.get_time_last_n_days(7), manual.get_time_last_n_days(7));
+        std::fs::remove_dir_all(&dir)?;
+
+        summaries.assert_hits(1);
+        assert_eq!(from_cron?, Some(12.5));
+        assert_eq!(from_manual?, Some(12.5));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_min_interval_elapsed() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let summaries = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .body(json!({ "cumulative_total": { "decimal": "12.50" } }).to_string());
+        });
+
+        let dir = shared_dir("min-interval-elapsed")?;
+        for _ in 0..2 {
+            let mut wakatime = WakaTime::new("shared")?;
+            wakatime
+                .set_base_url(mock_server.base_url().parse()?)
+                .set_min_interval(dir.clone(), Duration::ZERO);
+
+            assert_eq!(wakatime.get_time_last_n_days(7).await?, Some(12.5));
+        }
+        std::fs::remove_dir_all(&dir)?;
+
+        summaries.assert_hits(2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let slow = mock_server.mock(|when, then| {
+            when.method(GET).path("/api/v1/users/current/summaries");
+            then.status(200)
+                .delay(Duration::from_secs(2))
+                .body(json!({ "cumulative_total": { "decimal": "1.00" } }).to_string());
+        });
+
+        let http = build_client(None, Some(Duration::from_millis(100)))?;
+        let mut client = WakaTime::with_client("timeout", http)?.with_retries(0, Duration::ZERO);
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let error = client.get_time_last_n_days(7).await.unwrap_err();
+
+        slow.assert();
+        assert!(matches!(error, BurnoutError::Timeout(_)), "{error:?}");
+        assert_eq!(error.to_string(), "Timed out waiting for 127.0.0.1");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_after() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let (failing, ok) = flaky_summaries(&mock_server, 429, Some("1"));
+
+        // the header's second wins over the tiny base delay
+        let mut client = WakaTime::new("retry_after")?.with_retries(3, Duration::from_millis(1));
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let started = Instant::now();
+        client.get_time_last_n_days(7).await?;
+
+        failing.assert_hits(2);
+        ok.assert_hits(1);
+        assert!(started.elapsed() >= Duration::from_secs(2));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_capped() -> Result<(), Box<dyn Error>> {
+        let mock_server = MockServer::start();
+        let (failing, ok) = flaky_summaries(&mock_server, 429, Some("3600"));
+
+        // an hour is cut down to the max wait
+        let mut client = WakaTime::new("retry_after_capped")?
+            .with_retries(3, Duration::from_millis(1))
+            .with_max_retry_wait(Duration::from_millis(10));
+        client.set_base_url(mock_server.base_url().parse()?);
+
+        let started = Instant::now();
+        client.get_time_last_n_days(7).await?;
+
+        failing.assert_hits(2);
+        ok.assert_hits(1);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        for status in [429, 500, 502, 503] {
+            assert!(
+                is_retryable(StatusCode::from_u16(status).unwrap()),
+                "{status}"
+            );
+        }
+
+        for status in [200, 400, 401, 404, 504] {
+            assert!(
+                !is_retryable(StatusCode::from_u16(status).unwrap()),
+                "{status}"
+            );
+        }
+    }
 }
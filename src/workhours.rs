@@ -0,0 +1,140 @@
+use crate::wakatime::Session;
+use std::str::FromStr;
+use time::{OffsetDateTime, UtcOffset, Weekday};
+
+/// The multiplier for coding outside work hours when `OFF_HOURS_MULTIPLIER`
+/// isn't set.
+pub const DEFAULT_OFF_HOURS_MULTIPLIER: f64 = 1.5;
+
+/// A weekday work window, where time coded outside it counts for more
+/// toward burnout than time inside it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkHours {
+    /// The hour work starts, from 0 to 23.
+    pub start: u8,
+    /// The hour work ends, exclusive, from 1 to 24.
+    pub end: u8,
+    /// How much each hour outside the window counts for.
+    pub off_hours_multiplier: f64,
+    /// The timezone offset the window is in.
+    pub offset: UtcOffset,
+}
+
+impl WorkHours {
+    /// Whether the unix `timestamp` falls on a weekday inside the window.
+    pub fn is_work_time(&self, timestamp: f64) -> bool {
+        let datetime = match OffsetDateTime::from_unix_timestamp(timestamp as i64) {
+            Ok(datetime) => datetime.to_offset(self.offset),
+            Err(_) => return false,
+        };
+
+        let weekend = matches!(datetime.weekday(), Weekday::Saturday | Weekday::Sunday);
+        let hour = datetime.hour();
+
+        !weekend && hour >= self.start && hour < self.end
+    }
+
+    /// Total the `sessions` in hours, each capped at `max_session_hours` if
+    /// set, with the ones that started outside the window multiplied.
+    pub fn weigh(&self, sessions: &[Session], max_session_hours: Option<f64>) -> f64 {
+        let max_seconds = max_session_hours.map_or(f64::INFINITY, |hours| hours * 3600f64);
+
+        sessions
+            .iter()
+            .map(|session| {
+                let seconds = session.duration.min(max_seconds);
+
+                match self.is_work_time(session.time) {
+                    true => seconds,
+                    false => seconds * self.off_hours_multiplier,
+                }
+            })
+            .sum::<f64>()
+            / 3600f64
+    }
+}
+
+/// Parse a window like `9-17`, with the default multiplier in UTC.
+impl FromStr for WorkHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid WORK_HOURS, expected e.g. 9-17: {s}");
+
+        let (start, end) = s.split_once('-').ok_or_else(error)?;
+        let start: u8 = start.trim().parse().map_err(|_| error())?;
+        let end: u8 = end.trim().parse().map_err(|_| error())?;
+
+        if start >= end || end > 24 {
+            return Err(error());
+        }
+
+        Ok(Self {
+            start,
+            end,
+            off_hours_multiplier: DEFAULT_OFF_HOURS_MULTIPLIER,
+            offset: UtcOffset::UTC,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkHours;
+    use crate::wakatime::Session;
+    use time::macros::{datetime, offset};
+
+    fn session(start: time::OffsetDateTime, hours: f64) -> Session {
+        Session {
+            time: start.unix_timestamp() as f64,
+            duration: hours * 3600f64,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let work_hours: WorkHours = "9-17".parse().unwrap();
+
+        assert_eq!((work_hours.start, work_hours.end), (9, 17));
+        assert!("17-9".parse::<WorkHours>().is_err());
+        assert!("nine to five".parse::<WorkHours>().is_err());
+    }
+
+    #[test]
+    fn test_same_hours_weigh_differently() {
+        let work_hours: WorkHours = "9-17".parse().unwrap();
+
+        // Wednesday afternoon
+        let daytime = [session(datetime!(2023-01-18 13:00 UTC), 4.0)];
+        // Wednesday late night and Saturday morning
+        let off_hours = [
+            session(datetime!(2023-01-18 23:00 UTC), 2.0),
+            session(datetime!(2023-01-21 10:00 UTC), 2.0),
+        ];
+
+        assert_eq!(work_hours.weigh(&daytime, None), 4.0);
+        assert_eq!(work_hours.weigh(&off_hours, None), 6.0);
+    }
+
+    #[test]
+    fn test_window_in_timezone() {
+        let work_hours = WorkHours {
+            offset: offset!(-5),
+            ..("9-17".parse().unwrap())
+        };
+
+        // 15:00 UTC is 10:00 at UTC-5
+        assert!(work_hours.is_work_time(datetime!(2023-01-18 15:00 UTC).unix_timestamp() as f64));
+        // 10:00 UTC is 05:00 at UTC-5
+        assert!(!work_hours.is_work_time(datetime!(2023-01-18 10:00 UTC).unix_timestamp() as f64));
+    }
+
+    #[test]
+    fn test_weigh_caps_sessions() {
+        let work_hours: WorkHours = "9-17".parse().unwrap();
+
+        let sessions = [session(datetime!(2023-01-18 20:00 UTC), 6.0)];
+
+        assert_eq!(work_hours.weigh(&sessions, Some(2.0)), 3.0);
+    }
+}
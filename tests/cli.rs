@@ -0,0 +1,89 @@
+#![cfg(feature = "network")]
+
+use clap::{error::ErrorKind, Parser};
+use rust_burnout_meter::{
+    app::OutputFormat,
+    cli::{Args, Command},
+};
+
+fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+    Args::try_parse_from(std::iter::once("rust-burnout-meter").chain(args.iter().copied()))
+}
+
+#[test]
+fn test_parse_each_command() {
+    assert_eq!(parse(&["update"]).unwrap().command(), Command::Update);
+    assert_eq!(parse(&["meter"]).unwrap().command(), Command::Meter);
+    assert_eq!(parse(&["hours"]).unwrap().command(), Command::Hours);
+}
+
+#[test]
+fn test_parse_without_command() {
+    // the update has always been what a bare run does
+    assert_eq!(parse(&[]).unwrap().command(), Command::Update);
+
+    let args = parse(&["--suggest-limit"]).unwrap();
+    assert_eq!(args.command(), Command::Update);
+    assert!(args.suggest_limit);
+}
+
+#[test]
+fn test_parse_command_among_flags() {
+    let args = parse(&["--config", "burnout.toml", "hours"]).unwrap();
+    assert_eq!(args.command(), Command::Hours);
+    assert_eq!(args.config, Some("burnout.toml".into()));
+
+    // a profile named like a command is still the profile
+    let args = parse(&["--profile", "meter", "hours"]).unwrap();
+    assert_eq!(args.command(), Command::Hours);
+    assert_eq!(args.profile.as_deref(), Some("meter"));
+    assert_eq!(
+        parse(&["--profile", "meter"]).unwrap().command(),
+        Command::Update
+    );
+    assert_eq!(
+        parse(&["meter", "--profile", "work"]).unwrap().command(),
+        Command::Meter
+    );
+}
+
+#[test]
+fn test_parse_flags() {
+    let args = parse(&["--format", "json", "--force", "--dump-wakatime"]).unwrap();
+    assert_eq!(args.format, Some(OutputFormat::Json));
+    assert!(args.force);
+    assert!(args.dump_wakatime);
+
+    let args = parse(&["--validate-config", "burnout.toml"]).unwrap();
+    assert_eq!(args.validate_config, Some("burnout.toml".into()));
+}
+
+#[test]
+fn test_parse_unknown_command() {
+    assert_eq!(
+        parse(&["meters"]).unwrap_err().kind(),
+        ErrorKind::InvalidValue
+    );
+}
+
+#[test]
+fn test_parse_unknown_format() {
+    let error = parse(&["--format", "jsn"]).unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::ValueValidation);
+    assert!(error
+        .to_string()
+        .contains("Unknown OUTPUT_FORMAT value: jsn"));
+}
+
+#[test]
+fn test_parse_one_print_at_a_time() {
+    assert_eq!(
+        parse(&["--print", "--svg"]).unwrap_err().kind(),
+        ErrorKind::ArgumentConflict
+    );
+    assert_eq!(
+        parse(&["--flag-that-isnt"]).unwrap_err().kind(),
+        ErrorKind::UnknownArgument
+    );
+}
@@ -0,0 +1,17 @@
+use rust_burnout_meter::meter::{Band, Builder, MeterStyle};
+use std::error::Error;
+
+#[test]
+fn test_build_meter_from_library() -> Result<(), Box<dyn Error>> {
+    let mut meter = Builder::new();
+    meter.set_max(40).set_length(8).set_current(30).build()?;
+
+    assert_eq!(meter, "🟧🟧🟧🟧🟧🟧⬜️⬜️");
+    assert_eq!(meter.band(), Band::Heavy);
+
+    meter.set_style(MeterStyle::Shortcode).build()?;
+
+    assert!(meter.to_string().starts_with(":orange_square:"));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "network")]
+
+use httpmock::prelude::*;
+use serde_json::{json, Value};
+use std::{error::Error, fs, process::Command};
+
+#[test]
+fn test_json_output_is_all_of_stdout() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start();
+    mock_server.mock(|when, then| {
+        when.method(GET).path("/api/v1/users/current/summaries");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(json!({ "cumulative_total": { "decimal": "20.00" } }).to_string());
+    });
+    let location = mock_server.mock(|when, then| {
+        when.method(POST).path("/1.1/account/update_profile.json");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(json!({ "location": "🟨🟨🟨🟨⬜️⬜️⬜️⬜️" }).to_string());
+    });
+
+    // somewhere without a .env or state of its own
+    let dir = std::env::temp_dir().join(format!("burnout-json-output-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-burnout-meter"))
+        .args(["--format", "json"])
+        .current_dir(&dir)
+        .env_clear()
+        .env("WAKATIME_API_KEY", "api_key")
+        .env("TWITTER_CONSUMER_KEY", "consumer_key")
+        .env("TWITTER_CONSUMER_SECRET", "consumer_secret")
+        .env("TWITTER_ACCESS_TOKEN", "access_token")
+        .env("TWITTER_ACCESS_TOKEN_SECRET", "access_token_secret")
+        .env("WAKATIME_BASE_URL", mock_server.base_url())
+        .env("TWITTER_API_URL", mock_server.base_url())
+        .env("LOCATION_TEMPLATE", "{meter}")
+        .output()?;
+    fs::remove_dir_all(&dir)?;
+
+    assert!(output.status.success(), "{output:?}");
+    location.assert();
+
+    // the whole of stdout is the one object, with the logs kept to stderr
+    let stdout: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(stdout["meter"], "🟨🟨🟨🟨⬜️⬜️⬜️⬜️");
+    assert_eq!(stdout["hours"], 20.0);
+    assert_eq!(stdout["limit"], 40.0);
+    assert_eq!(stdout["percentage"], 50.0);
+    assert_eq!(stdout["remaining"], 20.0);
+    assert_eq!(stdout["zone"], "busy");
+    assert!(String::from_utf8(output.stderr)?.contains("Generated Meter"));
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_format_is_an_error() -> Result<(), Box<dyn Error>> {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-burnout-meter"))
+        .args(["--format", "jsn"])
+        .current_dir(std::env::temp_dir())
+        .env_clear()
+        .env("WAKATIME_API_KEY", "api_key")
+        .env("TWITTER_CONSUMER_KEY", "consumer_key")
+        .env("TWITTER_CONSUMER_SECRET", "consumer_secret")
+        .env("TWITTER_ACCESS_TOKEN", "access_token")
+        .env("TWITTER_ACCESS_TOKEN_SECRET", "access_token_secret")
+        .output()?;
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Unknown OUTPUT_FORMAT value: jsn"));
+
+    Ok(())
+}